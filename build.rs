@@ -0,0 +1,66 @@
+//! Writes the sizes of the files that the game loads at startup, for the progress of the loading
+//! screen: the asset manifest, every path listed in it, and the translation files. With the
+//! `embed-assets` feature, also writes the table of the manifest and its files that
+//! `external::embedded_assets` includes in the binary.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const MANIFEST_PATH: &str = "assets/manifest.txt";
+const TRANSLATIONS_FOLDER: &str = "assets/translations";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", MANIFEST_PATH);
+    println!("cargo:rerun-if-changed={}", TRANSLATIONS_FOLDER);
+    let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let manifest = fs::read_to_string(Path::new(&root).join(MANIFEST_PATH))
+        .unwrap_or_else(|e| panic!("can't read {}: {}", MANIFEST_PATH, e));
+    let mut paths = vec![MANIFEST_PATH.to_string()];
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().ends_with(".path") {
+                paths.push(value.trim().to_string());
+            }
+        }
+    }
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
+
+    let mut translations = fs::read_dir(Path::new(&root).join(TRANSLATIONS_FOLDER))
+        .unwrap_or_else(|e| panic!("can't read {}: {}", TRANSLATIONS_FOLDER, e))
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".txt"))
+        .map(|name| format!("{}/{}", TRANSLATIONS_FOLDER, name))
+        .collect::<Vec<_>>();
+    translations.sort();
+    let mut sizes = String::from("pub static SIZES: &[(&str, usize)] = &[\n");
+    for path in paths.iter().chain(&translations) {
+        let absolute = Path::new(&root).join(path);
+        println!("cargo:rerun-if-changed={}", absolute.display());
+        // a missing file is only a warning of the manifest checks when the game starts
+        if let Ok(metadata) = fs::metadata(&absolute) {
+            sizes += &format!("    ({:?}, {}),\n", path, metadata.len());
+        }
+    }
+    sizes += "];\n";
+    fs::write(out.join("asset_sizes.rs"), sizes).unwrap();
+
+    if env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_none() {
+        return;
+    }
+    let mut table = String::from("pub static FILES: &[(&str, &[u8])] = &[\n");
+    for path in &paths {
+        let absolute = Path::new(&root).join(path);
+        table += &format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            path,
+            absolute.display().to_string()
+        );
+    }
+    table += "];\n";
+    fs::write(out.join("embedded_assets.rs"), table).unwrap();
+}
@@ -0,0 +1,52 @@
+// Browser functions imported by the wasm build. The rust side is in src/external/storage.rs and
+// src/external/locale.rs. Must be loaded after mq_js_bundle.js and before calling load().
+miniquad_add_plugin({
+    name: "double_clicker",
+    version: "0.1.0",
+    register_plugin: function (importObject) {
+        function read_string(ptr, len) {
+            return new TextDecoder().decode(new Uint8Array(wasm_memory.buffer, ptr, len));
+        }
+        // returns how many bytes were written
+        function write_string(text, ptr, max_len) {
+            const bytes = new TextEncoder().encode(text);
+            const len = Math.min(bytes.length, max_len);
+            new Uint8Array(wasm_memory.buffer, ptr, len).set(bytes.subarray(0, len));
+            return len;
+        }
+        function storage_get(key_ptr, key_len) {
+            try {
+                return window.localStorage.getItem(read_string(key_ptr, key_len));
+            } catch (e) {
+                // localStorage can be disabled, e.g. in some iframes
+                return null;
+            }
+        }
+
+        importObject.env.double_clicker_storage_len = function (key_ptr, key_len) {
+            const value = storage_get(key_ptr, key_len);
+            return value === null ? -1 : new TextEncoder().encode(value).length;
+        };
+        importObject.env.double_clicker_storage_get = function (key_ptr, key_len, out_ptr, out_len) {
+            const value = storage_get(key_ptr, key_len);
+            write_string(value === null ? "" : value, out_ptr, out_len);
+        };
+        importObject.env.double_clicker_storage_set = function (key_ptr, key_len, value_ptr, value_len) {
+            try {
+                window.localStorage.setItem(read_string(key_ptr, key_len), read_string(value_ptr, value_len));
+            } catch (e) {
+                console.warn("could not save to localStorage: " + e);
+            }
+        };
+        importObject.env.double_clicker_storage_remove = function (key_ptr, key_len) {
+            try {
+                window.localStorage.removeItem(read_string(key_ptr, key_len));
+            } catch (e) {
+                console.warn("could not remove from localStorage: " + e);
+            }
+        };
+        importObject.env.double_clicker_browser_language = function (out_ptr, out_len) {
+            return write_string(navigator.language || "", out_ptr, out_len);
+        };
+    },
+});
@@ -0,0 +1,117 @@
+//! Plays the game without a window, with a simple strategy, as fast as possible. Useful to check
+//! the balance of the economy and to export timelines without playing by hand.
+//!
+//! `cargo run --bin simulator -- --frames 36000 --difficulty hard --mode timed --export simulation`
+
+use std::collections::HashMap;
+
+use double_clicker::external::backends::Seconds;
+use double_clicker::external::cli::{
+    args, flag_value, has_flag, DIFFICULTY_FLAG, EXPORT_FLAG, FRAMES_FLAG, MODE_FLAG,
+};
+use double_clicker::external::export::export_run;
+use double_clicker::screen::GuiActions;
+use double_clicker::world::acts::Act;
+use double_clicker::world::config::{Difficulty, GameMode, WorldConfig};
+use double_clicker::world::heores::Hero;
+use double_clicker::world::World;
+
+/// The game runs at about 60 frames per second, and the economy advances once per frame.
+const FRAME_DURATION: Seconds = 1.0 / 60.0;
+const DEFAULT_FRAMES: i64 = 10 * 60 * 60;
+const DEFAULT_EXPORT_NAME: &str = "simulation";
+
+fn main() {
+    let args = args();
+    let frames = match flag_value(&args, FRAMES_FLAG) {
+        Some(frames) => frames.parse().unwrap_or_else(|_| {
+            eprintln!(
+                "{} expects a number of frames, got {:?}",
+                FRAMES_FLAG, frames
+            );
+            std::process::exit(2)
+        }),
+        None => DEFAULT_FRAMES,
+    };
+
+    let difficulty = match flag_value(&args, DIFFICULTY_FLAG) {
+        // custom games can't be configured from here
+        Some(id) => Difficulty::from_id(id)
+            .filter(|difficulty| *difficulty != Difficulty::Custom)
+            .unwrap_or_else(|| {
+                eprintln!(
+                    "{} expects easy, normal or hard, got {:?}",
+                    DIFFICULTY_FLAG, id
+                );
+                std::process::exit(2)
+            }),
+        None => Difficulty::Normal,
+    };
+
+    let mode = match flag_value(&args, MODE_FLAG) {
+        Some(id) => GameMode::from_id(id).unwrap_or_else(|| {
+            eprintln!(
+                "{} expects classic, timed, endless or zen, got {:?}",
+                MODE_FLAG, id
+            );
+            std::process::exit(2)
+        }),
+        None => GameMode::Classic,
+    };
+
+    let mut world = World::new(WorldConfig {
+        mode,
+        ..WorldConfig::preset(difficulty)
+    });
+    let mut time = world.previous_frame_timestamp;
+    for _ in 0..frames {
+        time += FRAME_DURATION;
+        world.update_at(choose_actions(&world), time);
+    }
+    println!(
+        "after {} frames of {} on {}: {:?}, {} € saved, dirtiness {}/{}",
+        frames,
+        mode.id(),
+        difficulty.id(),
+        world.stage(),
+        world.money_euros(),
+        world.dirtiness_units(),
+        world.max_dirtiness_units()
+    );
+
+    if has_flag(&args, EXPORT_FLAG) {
+        let name = flag_value(&args, EXPORT_FLAG)
+            .filter(|name| !name.starts_with("--"))
+            .unwrap_or(DEFAULT_EXPORT_NAME);
+        match export_run(&world, name) {
+            Ok(paths) => println!("exported {}", paths.join(", ")),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1)
+            }
+        }
+    }
+}
+
+/// Clicks both buttons every frame, and buys the most expensive character it can afford, choosing
+/// a villain if cleaning is faster than dirtying and a hero otherwise. Continues after winning.
+fn choose_actions(world: &World) -> GuiActions {
+    let want_villain = world.cleaning_speed() > world.dirtying_speed();
+    let to_buy = Hero::list()
+        .iter()
+        .filter(|hero| hero.is_hero() != want_villain)
+        .filter(|hero| world.price(hero) <= world.money_euros())
+        .max_by_key(|hero| world.price(hero));
+    let heroes_bought = HashMap::from_iter(
+        Hero::list()
+            .iter()
+            .map(|hero| (*hero, Some(hero) == to_buy)),
+    );
+    GuiActions {
+        clean_pressed: true,
+        dirty_pressed: true,
+        continue_playing: world.stage() == Act::GameWon,
+        heroes_bought,
+        ..GuiActions::default()
+    }
+}
@@ -0,0 +1,294 @@
+//! The list of assets to load, read from `assets/manifest.txt` at startup so that adding art only
+//! needs editing that file.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use macroquad::prelude::{FileError, FilterMode};
+
+pub const MANIFEST_PATH: &str = "assets/manifest.txt";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureEntry {
+    pub id: String,
+    pub path: String,
+    pub filter: FilterMode,
+    /// The id of the texture to use instead if this one can't be loaded.
+    pub fallback: Option<String>,
+    /// Whether the game can't start without this texture or its fallback. Otherwise, a placeholder
+    /// is drawn instead.
+    pub critical: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundEntry {
+    pub id: String,
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct AssetManifest {
+    /// In the order of the file, which is the order they are loaded.
+    pub textures: Vec<TextureEntry>,
+    /// Loaded after the textures. None of them is required, the game is silent without them.
+    pub sounds: Vec<SoundEntry>,
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    File(FileError),
+    /// A line of the manifest that can't be understood, with its line number.
+    Manifest {
+        line: usize,
+        problem: String,
+    },
+    /// The game uses a texture that the manifest doesn't list.
+    MissingTexture(String),
+    UnknownFallback {
+        id: String,
+        fallback: String,
+    },
+    /// Textures marked as critical that couldn't be loaded, nor their fallbacks, with the reason.
+    CriticalMissing(Vec<String>),
+}
+
+impl Display for AssetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetError::File(e) => write!(f, "{}", e),
+            AssetError::Manifest { line, problem } => {
+                write!(f, "{} line {}: {}", MANIFEST_PATH, line, problem)
+            }
+            AssetError::MissingTexture(id) => {
+                write!(f, "{} has no texture with id {:?}", MANIFEST_PATH, id)
+            }
+            AssetError::UnknownFallback { id, fallback } => write!(
+                f,
+                "{}: texture {:?} falls back to {:?}, which is not in the manifest",
+                MANIFEST_PATH, id, fallback
+            ),
+            AssetError::CriticalMissing(errors) => {
+                write!(f, "critical textures missing: {}", errors.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+impl From<FileError> for AssetError {
+    fn from(e: FileError) -> Self {
+        AssetError::File(e)
+    }
+}
+
+impl AssetManifest {
+    pub fn parse(text: &str) -> Result<Self, AssetError> {
+        let mut textures: Vec<TextureEntry> = Vec::new();
+        let mut sounds: Vec<SoundEntry> = Vec::new();
+        let mut lines_of_ids = HashMap::new();
+        for (line_number, line) in text.lines().enumerate() {
+            let line_number = line_number + 1;
+            let problem = |problem: String| AssetError::Manifest {
+                line: line_number,
+                problem,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| problem(format!("expected `key = value`, got {:?}", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+            let (kind, rest) = key.split_once('.').unwrap_or((key, ""));
+            let (id, field) = rest
+                .rsplit_once('.')
+                .filter(|_| kind == "texture" || kind == "sound")
+                .ok_or_else(|| {
+                    problem(format!(
+                        "expected `texture.<id>.<field>` or `sound.<id>.<field>`, got {:?}",
+                        key
+                    ))
+                })?;
+            if kind == "sound" {
+                let index = match sounds.iter().position(|entry| entry.id == id) {
+                    Some(index) => index,
+                    None => {
+                        lines_of_ids.insert((kind, id.to_string()), line_number);
+                        sounds.push(SoundEntry {
+                            id: id.to_string(),
+                            path: String::new(),
+                        });
+                        sounds.len() - 1
+                    }
+                };
+                match field {
+                    "path" => sounds[index].path = value.to_string(),
+                    _ => return Err(problem(format!("unknown field {:?}", field))),
+                }
+                continue;
+            }
+            let index = match textures.iter().position(|entry| entry.id == id) {
+                Some(index) => index,
+                None => {
+                    lines_of_ids.insert((kind, id.to_string()), line_number);
+                    textures.push(TextureEntry {
+                        id: id.to_string(),
+                        path: String::new(),
+                        filter: FilterMode::Linear,
+                        fallback: None,
+                        critical: false,
+                    });
+                    textures.len() - 1
+                }
+            };
+            let entry = &mut textures[index];
+            match field {
+                "path" => entry.path = value.to_string(),
+                "filter" => {
+                    entry.filter = match value {
+                        "linear" => FilterMode::Linear,
+                        "nearest" => FilterMode::Nearest,
+                        _ => return Err(problem(format!("unknown filter {:?}", value))),
+                    }
+                }
+                "fallback" => entry.fallback = Some(value.to_string()),
+                "critical" => {
+                    entry.critical = match value {
+                        "true" => true,
+                        "false" => false,
+                        _ => {
+                            return Err(problem(format!("expected true or false, got {:?}", value)))
+                        }
+                    }
+                }
+                _ => return Err(problem(format!("unknown field {:?}", field))),
+            }
+        }
+        let paths = textures
+            .iter()
+            .map(|entry| ("texture", &entry.id, &entry.path))
+            .chain(sounds.iter().map(|entry| ("sound", &entry.id, &entry.path)));
+        for (kind, id, path) in paths {
+            if path.is_empty() {
+                return Err(AssetError::Manifest {
+                    line: lines_of_ids[&(kind, id.clone())],
+                    problem: format!("{} {:?} has no path", kind, id),
+                });
+            }
+        }
+        for entry in &textures {
+            if let Some(fallback) = &entry.fallback {
+                if !textures.iter().any(|other| &other.id == fallback) {
+                    return Err(AssetError::UnknownFallback {
+                        id: entry.id.clone(),
+                        fallback: fallback.clone(),
+                    });
+                }
+            }
+        }
+        Ok(Self { textures, sounds })
+    }
+
+    /// Fails with the first of `ids` that is not in the manifest.
+    pub fn check_textures<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), AssetError> {
+        for id in ids {
+            if !self.textures.iter().any(|entry| entry.id == id) {
+                return Err(AssetError::MissingTexture(id.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::audio::{music_id, SoundEffect};
+    use crate::screen::textures::Texture;
+    use crate::world::acts::Act;
+
+    #[test]
+    fn test_parse() {
+        let manifest = AssetManifest::parse(
+            "# comment\n\
+             texture.hero.path = a/hero.png\n\
+             texture.pixel.path = b.png\n\
+             texture.pixel.filter = nearest\n\
+             texture.pixel.critical = true\n\
+             texture.hero.fallback = pixel\n\
+             sound.click.path = click.wav\n",
+        )
+        .unwrap();
+        let ids = manifest.textures.iter().map(|entry| entry.id.as_str());
+        assert_eq!(ids.collect::<Vec<_>>(), ["hero", "pixel"]);
+        assert_eq!(manifest.textures[0].path, "a/hero.png");
+        assert_eq!(manifest.textures[0].fallback.as_deref(), Some("pixel"));
+        assert_eq!(manifest.textures[1].filter, FilterMode::Nearest);
+        assert!(!manifest.textures[0].critical && manifest.textures[1].critical);
+        let click = SoundEntry {
+            id: "click".to_string(),
+            path: "click.wav".to_string(),
+        };
+        assert_eq!(manifest.sounds, [click]);
+        assert!(manifest.check_textures(["pixel"]).is_ok());
+        let missing = manifest.check_textures(["pixel", "villain"]).unwrap_err();
+        assert_eq!(
+            missing.to_string(),
+            "assets/manifest.txt has no texture with id \"villain\""
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text| AssetManifest::parse(text).unwrap_err().to_string();
+        assert_eq!(
+            error("\ntexture.a.path = a.png\ntexture.a.size = 3"),
+            "assets/manifest.txt line 3: unknown field \"size\""
+        );
+        assert_eq!(
+            error("texture.a.filter = nearest"),
+            "assets/manifest.txt line 1: texture \"a\" has no path"
+        );
+        assert!(error("music.a.path = a.ogg").contains("expected `texture.<id>.<field>`"));
+        assert_eq!(
+            error("sound.a.volume = 3"),
+            "assets/manifest.txt line 1: unknown field \"volume\""
+        );
+        assert!(error("texture.a.path = a.png\ntexture.a.fallback = b").contains("\"b\""));
+    }
+
+    #[test]
+    fn test_shipped_manifest_has_every_texture() {
+        let text = std::fs::read_to_string(MANIFEST_PATH).unwrap();
+        let manifest = AssetManifest::parse(&text).unwrap();
+        manifest
+            .check_textures(Texture::list().iter().map(|texture| texture.id()))
+            .unwrap();
+        let paths = manifest.textures.iter().map(|entry| &entry.path);
+        for path in paths.chain(manifest.sounds.iter().map(|entry| &entry.path)) {
+            assert!(
+                std::path::Path::new(path).exists(),
+                "{} doesn't exist",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_shipped_manifest_has_every_effect() {
+        let text = std::fs::read_to_string(MANIFEST_PATH).unwrap();
+        let manifest = AssetManifest::parse(&text).unwrap();
+        let has_sound = |id: &str| manifest.sounds.iter().any(|entry| entry.id == id);
+        for effect in SoundEffect::list() {
+            assert!(has_sound(&effect.id()), "no sound for {:?}", effect);
+        }
+        for act in [Act::Act1, Act::Act2, Act::Act3] {
+            assert!(has_sound(&music_id(act)), "no music for {:?}", act);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Packs the loaded images into a few big textures, so that drawing a frame binds fewer textures.
+//! Images too big for an atlas keep a texture of their own.
+
+use std::collections::HashMap;
+
+use macroquad::prelude::{FilterMode, Image, Rect, Texture2D, BLANK};
+
+use crate::screen::textures::Sprite;
+
+/// Widely supported as a texture size, even by old phones.
+pub const ATLAS_SIDE: u16 = 2048;
+/// Around each image, repeating its border, so that linear filtering doesn't blend in the pixels of
+/// its neighbours.
+const PADDING: u16 = 1;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Placement {
+    pub atlas: usize,
+    /// Of the image itself, inside the padding.
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Places rectangles of `sizes` in atlases of `side` by `side`, in rows from the tallest to the
+/// shortest. Returns None for the ones that don't fit even in an empty atlas.
+pub fn pack(sizes: &[(u16, u16)], side: u16, padding: u16) -> Vec<Option<Placement>> {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| std::cmp::Reverse(sizes[*i].1));
+    let mut placements = vec![None; sizes.len()];
+    let (mut atlas, mut x, mut y, mut row_height) = (0, 0u32, 0u32, 0u32);
+    for i in order {
+        let (w, h) = sizes[i];
+        let (w, h) = (w as u32 + 2 * padding as u32, h as u32 + 2 * padding as u32);
+        let side = side as u32;
+        if w > side || h > side {
+            continue;
+        }
+        if x + w > side {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+        if y + h > side {
+            atlas += 1;
+            x = 0;
+            y = 0;
+            row_height = 0;
+        }
+        placements[i] = Some(Placement {
+            atlas,
+            x: (x + padding as u32) as u16,
+            y: (y + padding as u32) as u16,
+        });
+        x += w;
+        row_height = row_height.max(h);
+    }
+    placements
+}
+
+/// Copies `image` into `atlas` at `x`, `y`, repeating its border pixels over the padding.
+fn blit(atlas: &mut Image, image: &Image, x: u16, y: u16, padding: u16) {
+    let (w, h) = (image.width as i32, image.height as i32);
+    if w == 0 || h == 0 {
+        return;
+    }
+    let padding = padding as i32;
+    for dy in -padding..h + padding {
+        for dx in -padding..w + padding {
+            let source_x = dx.clamp(0, w - 1) as usize;
+            let source_y = dy.clamp(0, h - 1) as usize;
+            let source = (source_y * image.width as usize + source_x) * 4;
+            let target_x = (x as i32 + dx) as usize;
+            let target_y = (y as i32 + dy) as usize;
+            let target = (target_y * atlas.width as usize + target_x) * 4;
+            atlas.bytes[target..target + 4].copy_from_slice(&image.bytes[source..source + 4]);
+        }
+    }
+}
+
+/// One atlas per filter mode at least, because the filter is set per texture.
+pub fn build_atlases(images: Vec<(String, Image, FilterMode)>) -> HashMap<String, Sprite> {
+    let mut sprites = HashMap::new();
+    for filter in [FilterMode::Linear, FilterMode::Nearest] {
+        let images = images
+            .iter()
+            .filter(|(_, _, image_filter)| *image_filter == filter)
+            .collect::<Vec<_>>();
+        let sizes = images
+            .iter()
+            .map(|(_, image, _)| (image.width, image.height))
+            .collect::<Vec<_>>();
+        let placements = pack(&sizes, ATLAS_SIDE, PADDING);
+        // only as big as needed, the last atlas is usually not full
+        let mut extents = Vec::<(u16, u16)>::new();
+        for (size, placement) in sizes.iter().zip(&placements) {
+            if let Some(placement) = placement {
+                if extents.len() <= placement.atlas {
+                    extents.resize(placement.atlas + 1, (0, 0));
+                }
+                let extent = &mut extents[placement.atlas];
+                extent.0 = extent.0.max(placement.x + size.0 + PADDING);
+                extent.1 = extent.1.max(placement.y + size.1 + PADDING);
+            }
+        }
+        let mut atlases = extents
+            .iter()
+            .map(|(w, h)| Image::gen_image_color(*w, *h, BLANK))
+            .collect::<Vec<_>>();
+        for ((_, image, _), placement) in images.iter().zip(&placements) {
+            if let Some(placement) = placement {
+                blit(
+                    &mut atlases[placement.atlas],
+                    image,
+                    placement.x,
+                    placement.y,
+                    PADDING,
+                );
+            }
+        }
+        let textures = atlases
+            .iter()
+            .map(|atlas| {
+                let texture = Texture2D::from_image(atlas);
+                texture.set_filter(filter);
+                texture
+            })
+            .collect::<Vec<_>>();
+        for ((id, image, _), placement) in images.into_iter().zip(placements) {
+            let sprite = match placement {
+                Some(placement) => Sprite {
+                    texture: textures[placement.atlas],
+                    source: Rect::new(
+                        placement.x as f32,
+                        placement.y as f32,
+                        image.width as f32,
+                        image.height as f32,
+                    ),
+                },
+                None => {
+                    let texture = Texture2D::from_image(image);
+                    texture.set_filter(filter);
+                    Sprite::whole(texture)
+                }
+            };
+            sprites.insert(id.clone(), sprite);
+        }
+    }
+    sprites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(a: (Placement, (u16, u16)), b: (Placement, (u16, u16))) -> bool {
+        let ((a, (aw, ah)), (b, (bw, bh))) = (a, b);
+        a.atlas == b.atlas
+            && a.x < b.x + bw + 2 * PADDING
+            && b.x < a.x + aw + 2 * PADDING
+            && a.y < b.y + bh + 2 * PADDING
+            && b.y < a.y + ah + 2 * PADDING
+    }
+
+    #[test]
+    fn test_pack_without_overlaps() {
+        let sizes = [(256, 256), (200, 250), (529, 380), (2800, 1752), (256, 256)];
+        let placements = pack(&sizes, 1024, PADDING);
+        assert_eq!(placements[3], None, "too big for an atlas");
+        let placed = placements
+            .iter()
+            .zip(sizes)
+            .filter_map(|(p, size)| p.map(|p| (p, size)))
+            .collect::<Vec<_>>();
+        assert_eq!(placed.len(), 4);
+        for (i, a) in placed.iter().enumerate() {
+            assert!(a.0.x as u32 + a.1 .0 as u32 + PADDING as u32 <= 1024);
+            assert!(a.0.y as u32 + a.1 .1 as u32 + PADDING as u32 <= 1024);
+            for b in &placed[i + 1..] {
+                assert!(!overlaps(*a, *b), "{:?} and {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_overflows_to_another_atlas() {
+        let placements = pack(&[(60, 60); 5], 128, PADDING);
+        let atlases = placements.iter().map(|p| p.unwrap().atlas);
+        assert_eq!(atlases.collect::<Vec<_>>(), [0, 0, 0, 0, 1]);
+        assert_eq!(placements[1].unwrap().x, 63);
+        assert_eq!(placements[2].unwrap().y, 63);
+    }
+
+    #[test]
+    fn test_blit_repeats_the_border() {
+        let mut atlas = Image::gen_image_color(4, 4, BLANK);
+        let mut image = Image::gen_image_color(2, 1, BLANK);
+        image.bytes.copy_from_slice(&[1, 1, 1, 1, 2, 2, 2, 2]);
+        blit(&mut atlas, &image, 1, 1, 1);
+        let pixel = |x: usize, y: usize| atlas.bytes[(y * 4 + x) * 4];
+        assert_eq!(
+            [pixel(0, 0), pixel(1, 0), pixel(2, 0), pixel(3, 0)],
+            [1, 1, 2, 2]
+        );
+        assert_eq!(
+            [pixel(0, 1), pixel(1, 1), pixel(2, 1), pixel(3, 1)],
+            [1, 1, 2, 2]
+        );
+        assert_eq!([pixel(0, 2), pixel(3, 2), pixel(0, 3)], [1, 2, 0]);
+    }
+}
@@ -0,0 +1,339 @@
+//! Sound effects for what the player does and for the alerts, and background music that changes
+//! with the act. The sounds are listed in the asset manifest like the textures, but the game works
+//! without any of them: a sound that couldn't be loaded, or a machine without an audio device,
+//! means silence.
+
+use std::collections::HashMap;
+
+use macroquad::audio::{play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+
+use crate::external::backends::Seconds;
+use crate::external::settings::Settings;
+use crate::world::acts::Act;
+use crate::world::alerts::Alert;
+use crate::world::World;
+
+/// Relative to the effects, so that the clicks can be heard over it.
+const MUSIC_VOLUME: f32 = 0.4;
+/// How much the volume buttons change the volume.
+pub const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SoundEffect {
+    CleanClick,
+    DirtyClick,
+    Buy,
+    Sell,
+    Alert(Alert),
+    GameOver,
+    GameWon,
+}
+
+impl SoundEffect {
+    pub fn list() -> Vec<SoundEffect> {
+        let mut effects = vec![
+            SoundEffect::CleanClick,
+            SoundEffect::DirtyClick,
+            SoundEffect::Buy,
+            SoundEffect::Sell,
+            SoundEffect::GameOver,
+            SoundEffect::GameWon,
+        ];
+        effects.extend(Alert::list().iter().map(|alert| SoundEffect::Alert(*alert)));
+        effects
+    }
+
+    /// The id of its sound in the asset manifest.
+    pub fn id(&self) -> String {
+        match self {
+            SoundEffect::CleanClick => "click_clean".to_string(),
+            SoundEffect::DirtyClick => "click_dirty".to_string(),
+            SoundEffect::Buy => "buy".to_string(),
+            SoundEffect::Sell => "sell".to_string(),
+            SoundEffect::Alert(alert) => format!("alert_{}", alert.id()),
+            SoundEffect::GameOver => "game_over".to_string(),
+            SoundEffect::GameWon => "game_won".to_string(),
+        }
+    }
+
+    /// The action that failed, if this is the alert of that failure. Only the alert is played.
+    fn replaces(&self) -> Option<SoundEffect> {
+        match self {
+            SoundEffect::Alert(Alert::CannotClean) => Some(SoundEffect::CleanClick),
+            SoundEffect::Alert(Alert::InsufficientMoney) => Some(SoundEffect::Buy),
+            SoundEffect::Alert(Alert::CannotSell) => Some(SoundEffect::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// The id in the asset manifest of the music of `act`. Acts without music are silent.
+pub fn music_id(act: Act) -> String {
+    format!("music_{}", act.id())
+}
+
+/// The sounds that could be loaded, by their id in the asset manifest.
+#[derive(Default)]
+pub struct Sounds {
+    sounds: HashMap<String, Sound>,
+}
+
+impl Sounds {
+    pub fn new(sounds: HashMap<String, Sound>) -> Self {
+        Self { sounds }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Sound> {
+        self.sounds.get(id).copied()
+    }
+}
+
+/// Decides which effects to play by looking at what changed in the world since the last frame.
+/// It doesn't touch the audio device, so that it can be tested.
+#[derive(Default)]
+struct Cues {
+    /// Recorded from the input before the world updates.
+    pending: Vec<SoundEffect>,
+    /// Of the newest alert already played, because alerts stay in the world for a while.
+    last_alert_time: Seconds,
+    /// The continuous alerts that were raised in the last frame, which only play when they start.
+    continuous: Vec<Alert>,
+    /// None before the first frame, which is not a change of act.
+    act: Option<Act>,
+}
+
+impl Cues {
+    fn record(&mut self, effect: SoundEffect) {
+        if !self.pending.contains(&effect) {
+            self.pending.push(effect);
+        }
+    }
+
+    fn take(&mut self, world: &World) -> Vec<SoundEffect> {
+        // the world ignores the input until the player starts another run or continues playing
+        let input_ignored = self
+            .act
+            .is_some_and(|previous| previous.is_final() || previous == Act::GameWon);
+        if input_ignored {
+            self.pending.clear();
+        }
+        let mut alerts = Vec::new();
+        for (time, alert) in &world.alerts {
+            let is_new = if alert.is_continuous() {
+                !self.continuous.contains(alert)
+            } else {
+                *time > self.last_alert_time
+            };
+            if is_new && !alerts.contains(alert) {
+                alerts.push(*alert);
+            }
+        }
+        self.last_alert_time = world
+            .alerts
+            .iter()
+            .map(|(time, _)| *time)
+            .fold(self.last_alert_time, Seconds::max);
+        self.continuous = world
+            .alerts
+            .iter()
+            .map(|(_, alert)| *alert)
+            .filter(Alert::is_continuous)
+            .collect();
+        for alert in alerts {
+            let effect = SoundEffect::Alert(alert);
+            if let Some(replaced) = effect.replaces() {
+                self.pending.retain(|pending| *pending != replaced);
+            }
+            self.record(effect);
+        }
+        let act = world.stage();
+        if self.act.is_some_and(|previous| previous != act) {
+            match act {
+                Act::GameOver | Act::TimeUp => self.record(SoundEffect::GameOver),
+                Act::GameWon => self.record(SoundEffect::GameWon),
+                _ => {}
+            }
+        }
+        self.act = Some(act);
+        std::mem::take(&mut self.pending)
+    }
+}
+
+pub struct Audio {
+    sounds: Sounds,
+    volume: f32,
+    muted: bool,
+    /// The id and sound of the music that is playing.
+    music: Option<(String, Sound)>,
+    cues: Cues,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new(Sounds::default(), &Settings::default())
+    }
+}
+
+impl Audio {
+    pub fn new(sounds: Sounds, settings: &Settings) -> Self {
+        Self {
+            sounds,
+            volume: settings.volume,
+            muted: settings.muted,
+            music: None,
+            cues: Cues::default(),
+        }
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Rounded to the volume steps, between 0 and 1.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = ((volume / VOLUME_STEP).round() * VOLUME_STEP).clamp(0.0, 1.0);
+        self.update_music_volume();
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.update_music_volume();
+    }
+
+    /// Plays `effect` in the next call to `update`, unless the world rejects the action.
+    pub fn record(&mut self, effect: SoundEffect) {
+        self.cues.record(effect);
+    }
+
+    /// Plays the effects of this frame and switches to the music of the current act. Returns the
+    /// effects even if muted, for other kinds of feedback.
+    pub fn update(&mut self, world: &World) -> Vec<SoundEffect> {
+        let effects = self.cues.take(world);
+        let volume = self.effective_volume();
+        if volume > 0.0 {
+            for effect in &effects {
+                if let Some(sound) = self.sounds.get(&effect.id()) {
+                    play_sound(
+                        sound,
+                        PlaySoundParams {
+                            looped: false,
+                            volume,
+                        },
+                    );
+                }
+            }
+        }
+        let music = music_id(world.stage());
+        if self.music.as_ref().map(|(id, _)| id) != Some(&music) {
+            if let Some((_, sound)) = self.music.take() {
+                stop_sound(sound);
+            }
+            if let Some(sound) = self.sounds.get(&music) {
+                play_sound(
+                    sound,
+                    PlaySoundParams {
+                        looped: true,
+                        volume: volume * MUSIC_VOLUME,
+                    },
+                );
+                self.music = Some((music, sound));
+            }
+        }
+        effects
+    }
+
+    fn effective_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+
+    fn update_music_volume(&self) {
+        if let Some((_, sound)) = &self.music {
+            set_sound_volume(*sound, self.effective_volume() * MUSIC_VOLUME);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen::GuiActions;
+    use crate::world::config::WorldConfig;
+
+    #[test]
+    fn test_ids_are_unique() {
+        let mut ids = SoundEffect::list()
+            .iter()
+            .map(SoundEffect::id)
+            .chain(Act::list().iter().map(|act| music_id(*act)))
+            .collect::<Vec<_>>();
+        let count = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+
+    #[test]
+    fn test_alerts_play_once() {
+        let mut world = World::new(WorldConfig::default());
+        let mut cues = Cues::default();
+        assert_eq!(cues.take(&world), []);
+
+        world.alerts.push((1.0, Alert::CannotSell));
+        world.alerts.push((1.0, Alert::TooDirty));
+        let effects = cues.take(&world);
+        assert_eq!(
+            effects,
+            [
+                SoundEffect::Alert(Alert::CannotSell),
+                SoundEffect::Alert(Alert::TooDirty)
+            ]
+        );
+        // the same alerts, and the continuous one raised again
+        world.alerts.push((2.0, Alert::TooDirty));
+        assert_eq!(cues.take(&world), []);
+
+        world.alerts.push((3.0, Alert::CannotSell));
+        assert_eq!(cues.take(&world), [SoundEffect::Alert(Alert::CannotSell)]);
+    }
+
+    #[test]
+    fn test_failed_actions_only_play_the_alert() {
+        let mut world = World::new(WorldConfig::default());
+        let mut cues = Cues::default();
+        cues.take(&world);
+
+        cues.record(SoundEffect::CleanClick);
+        cues.record(SoundEffect::Sell);
+        // as the world does when there is not enough dirt to clean
+        world.alerts.push((1.0, Alert::CannotClean));
+        assert_eq!(
+            cues.take(&world),
+            [SoundEffect::Sell, SoundEffect::Alert(Alert::CannotClean)]
+        );
+    }
+
+    #[test]
+    fn test_endings_play_on_change() {
+        let mut world = World::new(WorldConfig::default());
+        let mut cues = Cues::default();
+        let mut effects = Vec::new();
+        for _ in 0..world.max_dirtiness_units() + 10 {
+            world.update(GuiActions {
+                dirty_pressed: true,
+                ..GuiActions::default()
+            });
+            effects.extend(cues.take(&world));
+        }
+        assert_eq!(world.stage(), Act::GameOver);
+        let game_overs = effects.iter().filter(|e| **e == SoundEffect::GameOver);
+        assert_eq!(game_overs.count(), 1);
+    }
+}
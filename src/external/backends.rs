@@ -1,26 +1,49 @@
-use macroquad::prelude::Texture2D;
 pub use macroquad::prelude::Vec2;
 
+use crate::external::asset_manifest::AssetManifest;
+use crate::external::audio::{Audio, Sounds};
 use crate::external::basic_input::BasicInput;
+use crate::external::locale::choose_initial_language;
+use crate::external::saved_game;
+use crate::external::settings::Settings;
 use crate::external::texture_drawer::TextureDrawer;
 use crate::screen::textures::Textures;
-use crate::screen::translations::{get_translation, Language};
+use crate::screen::translations::Translations;
 use crate::screen::Screen;
+use crate::world::config::WorldConfig;
 use crate::world::World;
 
-const DEFAULT_LANGUAGE: Language = Language::Spanish;
-pub fn factory(textures: Vec<Texture2D>) -> (Screen, World) {
+/// `manifest` is what the textures were loaded from, to reload them with the `hot-reload` feature.
+#[cfg_attr(not(feature = "hot-reload"), allow(unused_variables))]
+pub fn factory(
+    textures: Textures,
+    translations: Translations,
+    sounds: Sounds,
+    manifest: &AssetManifest,
+) -> (Screen, World) {
+    let settings = Settings::load();
+    let language = choose_initial_language(&settings);
+    let saved_game = saved_game::load();
+    let first_launch = saved_game.is_none();
+    let mut world = saved_game.unwrap_or_else(|| World::new(WorldConfig::default()));
+    saved_game::load_shared(&mut world);
+    let mut drawer = TextureDrawer::new(textures, translations, language);
+    drawer.set_audio(Audio::new(sounds, &settings));
+    drawer.set_effects(settings.effects);
+    #[cfg(feature = "hot-reload")]
+    drawer.watch_assets(manifest);
+    if first_launch {
+        drawer.open_new_game(world.config().clone(), false);
+        drawer.open_name_entry(world.player_name());
+    }
     (
         Screen {
-            drawer: Box::new(TextureDrawer::new(
-                Textures::new(textures),
-                get_translation(DEFAULT_LANGUAGE),
-            )),
+            drawer: Box::new(drawer),
             // drawer: Box::new(TexturelessDrawer::new_with_texture(t)),
             // drawer: Box::new(TextDrawer::new()),
             input_source: Box::new(BasicInput),
         },
-        World::new(),
+        world,
     )
 }
 
@@ -7,16 +7,22 @@ use crate::screen::drawer_trait::{Button, DrawerTrait};
 use crate::screen::input_source_trait::InputSourceTrait;
 use crate::screen::GuiActions;
 use crate::world::heores::Hero;
+use crate::world::history::HistoryWindow;
 
 pub struct BasicInput;
 
 impl InputSourceTrait for BasicInput {
     fn get_gui_actions(&self, drawer: &mut dyn DrawerTrait) -> GuiActions {
-        let dirty_pressed = drawer.button(Button::Dirty) || is_key_pressed(KeyCode::E);
-        let clean_pressed = drawer.button(Button::Clean) || is_key_pressed(KeyCode::L);
-        let next_arrangement = drawer.button(Button::Arrangement) || is_key_pressed(KeyCode::C);
-        let restart =
-            drawer.button(Button::Restart) || drawer.button(Button::ContinueAfterGameOver);
+        // checked before typing, so that the Escape that leaves the text input doesn't quit
+        let typing = drawer.is_typing();
+        let player_name = drawer.player_name();
+        let shortcut = |key| !typing && is_key_pressed(key);
+        let dirty_pressed = drawer.button(Button::Dirty) || shortcut(KeyCode::E);
+        let clean_pressed = drawer.button(Button::Clean) || shortcut(KeyCode::L);
+        let next_arrangement = drawer.button(Button::Arrangement) || shortcut(KeyCode::C);
+        // both open the new-game screen
+        drawer.button(Button::Restart);
+        drawer.button(Button::ContinueAfterGameOver);
         let continue_playing = drawer.button(Button::ContinuePlaying);
         let heroes_bought = HashMap::from_iter(
             Hero::list()
@@ -31,16 +37,33 @@ impl InputSourceTrait for BasicInput {
 
         drawer.button(Button::ChangeLanguageToSpanish);
         drawer.button(Button::ChangeLanguageToEnglish);
+        drawer.button(Button::VolumeDown);
+        drawer.button(Button::VolumeUp);
+        drawer.button(Button::Mute);
+        drawer.button(Button::Effects);
         drawer.button(Button::DebugFps);
         drawer.button(Button::ExtraControls);
-        drawer.button(Button::Restart);
+        drawer.button(Button::Stats);
+        drawer.button(Button::Export);
+        drawer.button(Button::History);
+        drawer.button(Button::SaveSlots);
+        for window in HistoryWindow::list() {
+            drawer.button(Button::HistoryWindow(*window));
+        }
+        let slot_action = drawer.slot_action();
+        // last, so that the click that closes the new-game screen doesn't reach what is below
+        let new_game = drawer.new_game();
 
         GuiActions {
-            quit: is_key_pressed(KeyCode::Escape),
+            quit: shortcut(KeyCode::Escape),
             clean_pressed,
             dirty_pressed,
             next_arrangement,
-            restart,
+            restart: false,
+            new_game,
+            paused: drawer.is_paused(),
+            player_name,
+            slot_action,
             continue_playing,
             heroes_bought,
             heroes_sold,
@@ -0,0 +1,61 @@
+//! Command line options. On wasm there are no arguments, so every flag is absent.
+
+pub const LANGUAGE_FLAG: &str = "--lang";
+pub const CHECK_TRANSLATIONS_FLAG: &str = "--check-translations";
+/// For the simulator: how many frames to play.
+pub const FRAMES_FLAG: &str = "--frames";
+/// For the simulator: `easy`, `normal` or `hard`.
+pub const DIFFICULTY_FLAG: &str = "--difficulty";
+/// For the simulator: `classic`, `timed`, `endless` or `zen`.
+pub const MODE_FLAG: &str = "--mode";
+/// For the simulator: export the timeline of the run, optionally with a file name.
+pub const EXPORT_FLAG: &str = "--export";
+
+pub fn args() -> Vec<String> {
+    std::env::args().collect()
+}
+
+pub fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| arg == flag)
+}
+
+/// Supports both `--flag value` and `--flag=value`.
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().map(|v| v.as_str());
+        } else if let Some(value) = arg.strip_prefix(flag).and_then(|v| v.strip_prefix('=')) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_flag_value() {
+        let separate = args(&["game", "--lang", "es"]);
+        assert_eq!(flag_value(&separate, LANGUAGE_FLAG), Some("es"));
+        let joined = args(&["game", "--lang=en_GB"]);
+        assert_eq!(flag_value(&joined, LANGUAGE_FLAG), Some("en_GB"));
+        let missing_value = args(&["game", "--lang"]);
+        assert_eq!(flag_value(&missing_value, LANGUAGE_FLAG), None);
+        let other_flag = args(&["game", "--language=es"]);
+        assert_eq!(flag_value(&other_flag, LANGUAGE_FLAG), None);
+    }
+
+    #[test]
+    fn test_has_flag() {
+        let args = args(&["game", "--check-translations"]);
+        assert!(has_flag(&args, CHECK_TRANSLATIONS_FLAG));
+        assert!(!has_flag(&args, LANGUAGE_FLAG));
+    }
+}
@@ -0,0 +1,67 @@
+//! Where the asset files are read from. With the `embed-assets` feature, the files listed in the
+//! asset manifest are also in the binary, which uses them when they are not on disk.
+
+use macroquad::prelude::{load_file, FileError};
+
+#[cfg(feature = "embed-assets")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+}
+
+mod sizes {
+    include!(concat!(env!("OUT_DIR"), "/asset_sizes.rs"));
+}
+
+/// Reads `path` from disk, or from the binary if it was embedded and it's not on disk. A file on
+/// disk is preferred so that players can replace the art without recompiling.
+pub async fn load_asset(path: &str) -> Result<Vec<u8>, FileError> {
+    match load_file(path).await {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => embedded_file(path).map(|bytes| bytes.to_vec()).ok_or(e),
+    }
+}
+
+/// Like `load_asset`, for text files.
+pub async fn load_asset_string(path: &str) -> Result<String, FileError> {
+    let bytes = load_asset(path).await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The size that `path` had when the game was built, to know how much is left to load before
+/// reading it. None for files that didn't exist then.
+pub fn expected_size(path: &str) -> Option<usize> {
+    sizes::SIZES
+        .iter()
+        .find(|(sized_path, _)| *sized_path == path)
+        .map(|(_, size)| *size)
+}
+
+#[cfg(feature = "embed-assets")]
+fn embedded_file(path: &str) -> Option<&'static [u8]> {
+    embedded::FILES
+        .iter()
+        .find(|(embedded_path, _)| *embedded_path == path)
+        .map(|(_, bytes)| *bytes)
+}
+
+#[cfg(not(feature = "embed-assets"))]
+fn embedded_file(_path: &str) -> Option<&'static [u8]> {
+    None
+}
+
+#[cfg(all(test, feature = "embed-assets"))]
+mod tests {
+    use super::*;
+    use crate::external::asset_manifest::{AssetManifest, MANIFEST_PATH};
+
+    #[test]
+    fn test_manifest_files_are_embedded() {
+        let text = String::from_utf8(embedded_file(MANIFEST_PATH).unwrap().to_vec()).unwrap();
+        let manifest = AssetManifest::parse(&text).unwrap();
+        let paths = manifest.textures.iter().map(|entry| &entry.path);
+        for path in paths.chain(manifest.sounds.iter().map(|entry| &entry.path)) {
+            let bytes = embedded_file(path).unwrap();
+            assert_eq!(bytes, std::fs::read(path).unwrap());
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Writes the timeline of the current run as CSV and JSON, for balancing the game. The layout of
+//! the files is documented in `docs/export.md`.
+
+use crate::world::World;
+
+#[cfg(not(target_family = "wasm"))]
+pub const EXPORT_FOLDER: &str = "exports";
+
+/// Writes `<name>.timeline.csv`, `<name>.events.csv` and `<name>.json`, and returns their paths.
+pub fn export_run(world: &World, name: &str) -> Result<Vec<String>, String> {
+    let timeline = world.timeline();
+    let files = [
+        (format!("{}.timeline.csv", name), timeline.timeline_csv()),
+        (format!("{}.events.csv", name), timeline.events_csv()),
+        (format!("{}.json", name), timeline.to_json(world.stats())),
+    ];
+    files
+        .iter()
+        .map(|(file_name, contents)| platform::write(file_name, contents))
+        .collect()
+}
+
+/// A name that doesn't repeat between exports, like `run_1700000000`.
+pub fn unique_name() -> String {
+    format!("run_{}", crate::external::backends::now() as i64)
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod platform {
+    use std::path::PathBuf;
+
+    use crate::external::export::EXPORT_FOLDER;
+
+    pub fn write(file_name: &str, contents: &str) -> Result<String, String> {
+        let path = PathBuf::from(EXPORT_FOLDER).join(file_name);
+        std::fs::create_dir_all(EXPORT_FOLDER)
+            .and_then(|_| std::fs::write(&path, contents))
+            .map(|_| path.display().to_string())
+            .map_err(|e| format!("could not export {:?}: {}", path, e))
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod platform {
+    pub fn write(_file_name: &str, _contents: &str) -> Result<String, String> {
+        Err("exporting files is only supported on native".to_string())
+    }
+}
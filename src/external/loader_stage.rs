@@ -1,48 +1,56 @@
 use crate::external::backends::factory;
 use macroquad::prelude::{
-    clear_background, draw_rectangle, next_frame, screen_height, screen_width, FileError, Rect,
-    BLACK, WHITE,
+    clear_background, draw_rectangle, next_frame, screen_height, screen_width, Rect, BLACK, WHITE,
 };
 
+use crate::external::asset_manifest::{AssetError, AssetManifest, MANIFEST_PATH};
+use crate::external::embedded_assets::load_asset_string;
+
+use crate::external::sound_loader::SoundLoader;
 use crate::external::texture_drawer::draw::draw_panel_border;
 use crate::external::texture_drawer::{CLEAN_COLOR, DIRTY_COLOR};
 use crate::external::texture_loader::{Progress, TextureLoader};
+use crate::external::translation_loader::TranslationLoader;
 use crate::external::widgets::anchor::Anchor;
 use crate::external::widgets::button::Interaction;
 use crate::external::widgets::text::TextRect;
+use crate::screen::textures::Texture;
 use crate::screen::Screen;
 use crate::world::World;
 
-const TEXTURE_PATHS: &[&str] = &[
-    "assets/images/buttons/buttonBLU-256-yes.png",
-    "assets/images/buttons/buttonBLU-256-no.png",
-    "assets/images/buttons/buttonPUR-256-yes.png",
-    "assets/images/buttons/buttonPUR-256-no.png",
-    "assets/images/characters/heroe1.png",
-    "assets/images/characters/villano1.png",
-    "assets/images/characters/heroe2.png",
-    "assets/images/characters/villano2.png",
-    "assets/images/characters/heroe3.png",
-    "assets/images/characters/villano3.png",
-    "assets/images/buttons/buttonBLU256-1.png",
-    "assets/images/buttons/buttonBLU256-2.png",
-    "assets/images/buttons/buttonBLU256-3.png",
-    "assets/images/buttons/buttonPUR256-1.png",
-    "assets/images/buttons/buttonPUR256-2.png",
-    "assets/images/buttons/buttonPUR256-3.png",
-    "assets/images/background/BGpatronsmol.png",
-    "assets/images/background/BGmargin.png",
-];
+/// How many textures or sounds are requested at the same time. Browsers limit the connections to a
+/// host to about 6 anyway.
+const MAX_FILES_IN_FLIGHT: usize = 6;
+
 pub struct LoaderStage;
 
 impl LoaderStage {
-    pub async fn setup() -> Result<(Screen, World), FileError> {
-        let mut loader = TextureLoader::new(TEXTURE_PATHS);
+    pub async fn setup() -> Result<(Screen, World), AssetError> {
+        let manifest = AssetManifest::parse(&load_asset_string(MANIFEST_PATH).await?)?;
+        manifest.check_textures(Texture::list().iter().map(|texture| texture.id()))?;
+        let mut texture_loader = TextureLoader::new(&manifest, MAX_FILES_IN_FLIGHT);
+        let mut translation_loader = TranslationLoader::new();
+        let mut sound_loader = SoundLoader::new(&manifest, MAX_FILES_IN_FLIGHT);
+        let mut textures = None;
+        let mut translations = None;
         loop {
-            if let Some(textures) = loader.get_textures()? {
-                return Ok(factory(textures));
+            if textures.is_none() {
+                textures = texture_loader.get_textures()?;
+            } else if translations.is_none() {
+                translations = translation_loader.get_translations();
+            } else if let Some(sounds) = sound_loader.get_sounds() {
+                return Ok(factory(
+                    textures.take().unwrap(),
+                    translations.take().unwrap(),
+                    sounds,
+                    &manifest,
+                ));
             }
-            Self::draw_loading(loader.get_progress());
+            Self::draw_loading(
+                texture_loader.get_progress()
+                    + translation_loader.get_progress()
+                    + sound_loader.get_progress(),
+            );
             next_frame().await;
         }
     }
@@ -54,8 +62,11 @@ impl LoaderStage {
         let height = screen_height();
         let text_rect = TextRect::new(
             &format!(
-                "Loading... ({}/{})",
-                progress.loaded, progress.total_to_load
+                "Loading... ({}/{}, {}/{} KB)",
+                progress.loaded,
+                progress.total_to_load,
+                progress.loaded_bytes / 1024,
+                progress.total_bytes / 1024
             ),
             Anchor::center(width * 0.5, height * 0.5),
             font_size,
@@ -72,7 +83,7 @@ impl LoaderStage {
         let progress_rect = Rect::new(
             line_rect.x,
             line_rect.y,
-            line_rect.w * progress.loaded as f32 / progress.total_to_load as f32,
+            line_rect.w * progress.fraction(),
             line_rect.h,
         );
 
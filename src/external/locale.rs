@@ -0,0 +1,93 @@
+use macroquad::prelude::warn;
+
+use crate::external::cli::{args, flag_value, LANGUAGE_FLAG};
+use crate::external::settings::Settings;
+use crate::screen::translations::Language;
+
+pub const FALLBACK_LANGUAGE: Language = Language::English;
+
+/// Picks the first language available from: the `--lang` flag, the saved preference, the system
+/// locale (environment variables on native, navigator.language on wasm), and English as fallback.
+pub fn choose_initial_language(settings: &Settings) -> Language {
+    choose_language(&[
+        language_from_args(&args()),
+        settings.language,
+        system_language(),
+    ])
+}
+
+fn choose_language(candidates: &[Option<Language>]) -> Language {
+    candidates
+        .iter()
+        .find_map(|candidate| *candidate)
+        .unwrap_or(FALLBACK_LANGUAGE)
+}
+
+fn language_from_args(args: &[String]) -> Option<Language> {
+    let value = flag_value(args, LANGUAGE_FLAG)?;
+    let language = Language::from_locale(value);
+    if language.is_none() {
+        warn!("unsupported language {:?} in {}", value, LANGUAGE_FLAG);
+    }
+    language
+}
+
+/// Follows the POSIX precedence: LC_ALL overrides LC_MESSAGES, which overrides LANG.
+#[cfg(not(target_family = "wasm"))]
+fn system_language() -> Option<Language> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|locale| Language::from_locale(&locale))
+}
+
+#[cfg(target_family = "wasm")]
+fn system_language() -> Option<Language> {
+    extern "C" {
+        fn double_clicker_browser_language(out: *mut u8, out_len: usize) -> usize;
+    }
+    let mut buffer = [0_u8; 32];
+    let len = unsafe { double_clicker_browser_language(buffer.as_mut_ptr(), buffer.len()) };
+    std::str::from_utf8(&buffer[..len])
+        .ok()
+        .and_then(Language::from_locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locales() {
+        assert_eq!(Language::from_locale("es"), Some(Language::Spanish));
+        assert_eq!(
+            Language::from_locale("es_ES.UTF-8"),
+            Some(Language::Spanish)
+        );
+        assert_eq!(Language::from_locale("en-US"), Some(Language::English));
+        assert_eq!(Language::from_locale("EN"), Some(Language::English));
+        assert_eq!(Language::from_locale("C.UTF-8"), None);
+        assert_eq!(Language::from_locale(""), None);
+    }
+
+    #[test]
+    fn test_language_flag() {
+        let args = vec!["game".to_string(), "--lang=es_ES".to_string()];
+        assert_eq!(language_from_args(&args), Some(Language::Spanish));
+        let args = vec!["game".to_string(), "--lang=xx".to_string()];
+        assert_eq!(language_from_args(&args), None);
+    }
+
+    #[test]
+    fn test_language_precedence() {
+        let spanish = Some(Language::Spanish);
+        let english = Some(Language::English);
+        assert_eq!(choose_language(&[spanish, english]), Language::Spanish);
+        assert_eq!(
+            choose_language(&[None, spanish, english]),
+            Language::Spanish
+        );
+        assert_eq!(choose_language(&[None, None, None]), FALLBACK_LANGUAGE);
+    }
+}
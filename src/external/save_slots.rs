@@ -0,0 +1,220 @@
+//! The index of the save slots: their names, when they were last played, and which one is being
+//! played. The run of each slot is a `World` snapshot under its own storage key.
+
+use crate::external::backends::Seconds;
+use crate::screen::SlotId;
+use crate::world::high_scores::sanitize_name;
+
+/// So that the slots screen fits in the window.
+pub const MAX_SLOTS: usize = 6;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotInfo {
+    pub id: SlotId,
+    /// Empty until the player renames the slot.
+    pub name: String,
+    /// Unix time of the last save, or None if it was never saved.
+    pub last_played: Option<Seconds>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveSlots {
+    /// Sorted by id, which is the order they were created.
+    slots: Vec<SlotInfo>,
+    current: SlotId,
+    next_id: SlotId,
+}
+
+impl Default for SaveSlots {
+    /// A single empty slot, being played.
+    fn default() -> Self {
+        Self {
+            slots: vec![SlotInfo {
+                id: 1,
+                name: String::new(),
+                last_played: None,
+            }],
+            current: 1,
+            next_id: 2,
+        }
+    }
+}
+
+impl SaveSlots {
+    /// The storage key of the run of a slot.
+    pub fn key(slot: SlotId) -> String {
+        format!("saved_game_{}", slot)
+    }
+
+    pub fn slots(&self) -> &[SlotInfo] {
+        &self.slots
+    }
+
+    pub fn current(&self) -> SlotId {
+        self.current
+    }
+
+    pub fn get(&self, slot: SlotId) -> Option<&SlotInfo> {
+        self.slots.iter().find(|info| info.id == slot)
+    }
+
+    fn get_mut(&mut self, slot: SlotId) -> Option<&mut SlotInfo> {
+        self.slots.iter_mut().find(|info| info.id == slot)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.len() >= MAX_SLOTS
+    }
+
+    /// Returns the id of the new slot, or None if there is no room for more.
+    pub fn add(&mut self, name: &str) -> Option<SlotId> {
+        if self.is_full() {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.slots.push(SlotInfo {
+            id,
+            name: sanitize_name(name),
+            last_played: None,
+        });
+        Some(id)
+    }
+
+    /// The slot being played can't be removed.
+    pub fn remove(&mut self, slot: SlotId) -> bool {
+        let removable = slot != self.current && self.get(slot).is_some();
+        self.slots.retain(|info| !removable || info.id != slot);
+        removable
+    }
+
+    pub fn rename(&mut self, slot: SlotId, name: &str) {
+        if let Some(info) = self.get_mut(slot) {
+            info.name = sanitize_name(name);
+        }
+    }
+
+    pub fn set_current(&mut self, slot: SlotId) -> bool {
+        let exists = self.get(slot).is_some();
+        if exists {
+            self.current = slot;
+        }
+        exists
+    }
+
+    pub fn touch(&mut self, slot: SlotId, now: Seconds) {
+        if let Some(info) = self.get_mut(slot) {
+            info.last_played = Some(now);
+        }
+    }
+
+    /// `key=value` lines like the settings. Returns None if there are no valid slots, so that the
+    /// caller can start from the default.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut slots: Vec<SlotInfo> = Vec::new();
+        let mut current = None;
+        let mut next_id = 1;
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let (key, value) = (key.trim(), value.trim());
+            if key == "current" {
+                current = value.parse().ok();
+            } else if key == "next_id" {
+                next_id = value.parse().unwrap_or(next_id);
+            } else if let Some((id, field)) = key
+                .strip_prefix("slot.")
+                .and_then(|rest| rest.split_once('.'))
+            {
+                let Ok(id) = id.parse::<SlotId>() else {
+                    continue;
+                };
+                let index = match slots.iter().position(|info| info.id == id) {
+                    Some(index) => index,
+                    None => {
+                        slots.push(SlotInfo {
+                            id,
+                            name: String::new(),
+                            last_played: None,
+                        });
+                        slots.len() - 1
+                    }
+                };
+                match field {
+                    "name" => slots[index].name = sanitize_name(value),
+                    "last_played" => slots[index].last_played = value.parse().ok(),
+                    _ => {}
+                }
+            }
+        }
+        slots.sort_by_key(|info| info.id);
+        slots.truncate(MAX_SLOTS);
+        let first = slots.first()?.id;
+        let max_id = slots.iter().map(|info| info.id).max().unwrap_or_default();
+        let current = current
+            .filter(|id| slots.iter().any(|info| info.id == *id))
+            .unwrap_or(first);
+        Some(Self {
+            slots,
+            current,
+            next_id: next_id.max(max_id + 1),
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("current={}\nnext_id={}\n", self.current, self.next_id);
+        for info in &self.slots {
+            text += &format!("slot.{}.name={}\n", info.id, info.name);
+            if let Some(last_played) = info.last_played {
+                text += &format!("slot.{}.last_played={}\n", info.id, last_played);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_round_trip() {
+        let mut slots = SaveSlots::default();
+        let second = slots.add("testers").unwrap();
+        slots.touch(second, 1_700_000_000.5);
+        slots.rename(1, " family ");
+        slots.set_current(second);
+        let loaded = SaveSlots::from_text(&slots.to_text()).unwrap();
+        assert_eq!(loaded, slots);
+        assert_eq!(loaded.get(1).unwrap().name, "family");
+        assert_eq!(loaded.current(), second);
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let mut slots = SaveSlots::default();
+        let second = slots.add("").unwrap();
+        assert!(!slots.remove(1));
+        assert!(slots.remove(second));
+        assert!(!slots.remove(second));
+        let third = slots.add("").unwrap();
+        assert_ne!(third, second);
+        let mut loaded = SaveSlots::from_text(&slots.to_text()).unwrap();
+        assert_eq!(loaded.add(""), slots.add(""));
+    }
+
+    #[test]
+    fn test_max_slots() {
+        let mut slots = SaveSlots::default();
+        while slots.add("").is_some() {}
+        assert_eq!(slots.slots().len(), MAX_SLOTS);
+        assert!(slots.is_full());
+    }
+
+    #[test]
+    fn test_broken_index() {
+        assert_eq!(SaveSlots::from_text(""), None);
+        let mut slots = SaveSlots::from_text("current=9\nslot.x.name=a\nslot.4.name=b").unwrap();
+        assert_eq!(slots.current(), 4);
+        assert_eq!(slots.slots().len(), 1);
+        assert_eq!(slots.add(""), Some(5));
+    }
+}
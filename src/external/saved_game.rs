@@ -0,0 +1,178 @@
+//! The runs in progress, one per save slot, so that closing the game doesn't lose them. The stats
+//! of the runs that ended are saved next to them, so that the lifetime stats don't count the saved
+//! runs twice, and so are the high scores, which are shared by every slot.
+
+use macroquad::prelude::warn;
+
+use crate::external::backends::{now, Seconds};
+use crate::external::save_slots::SaveSlots;
+use crate::external::storage;
+use crate::screen::{SlotAction, SlotId};
+use crate::world::acts::Act;
+use crate::world::high_scores::HighScores;
+use crate::world::stats::Stats;
+use crate::world::{Cents, World};
+
+const SAVE_SLOTS_KEY: &str = "save_slots";
+/// Where the only run was saved before there were slots. Becomes the first slot.
+const LEGACY_SAVED_GAME_KEY: &str = "saved_game";
+const LIFETIME_STATS_KEY: &str = "lifetime_stats";
+/// The records were saved here before the high scores had difficulties and names, in the same
+/// format, so the ones of older versions are kept.
+const HIGH_SCORES_KEY: &str = "leaderboard";
+
+/// Also saves every this many frames of play, in case the game is closed without quitting, like
+/// closing the browser tab.
+const SAVE_PERIOD_FRAMES: i64 = 30 * 60;
+
+/// What the slots screen shows about each slot.
+pub struct SlotSummary {
+    pub id: SlotId,
+    pub name: String,
+    pub last_played: Option<Seconds>,
+    pub is_current: bool,
+    /// None if the slot has no run or it can't be loaded.
+    pub run: Option<RunSummary>,
+}
+
+pub struct RunSummary {
+    pub act: Act,
+    pub savings: Cents,
+    pub play_time: Seconds,
+}
+
+fn load_slots() -> SaveSlots {
+    if let Some(slots) = storage::load(SAVE_SLOTS_KEY).and_then(|text| SaveSlots::from_text(&text))
+    {
+        return slots;
+    }
+    let slots = SaveSlots::default();
+    if let Some(legacy) = storage::load(LEGACY_SAVED_GAME_KEY) {
+        let moved = storage::save(&SaveSlots::key(slots.current()), &legacy)
+            .and_then(|_| storage::remove(LEGACY_SAVED_GAME_KEY));
+        if let Err(e) = moved {
+            warn!("{}", e);
+        }
+    }
+    save_slots(&slots);
+    slots
+}
+
+fn save_slots(slots: &SaveSlots) {
+    storage::save_logged(SAVE_SLOTS_KEY, &slots.to_text());
+}
+
+fn load_slot(slot: SlotId) -> Option<World> {
+    let text = storage::load(&SaveSlots::key(slot))?;
+    World::from_snapshot(&text)
+        .map_err(|e| warn!("ignoring the saved game of slot {}: {}", slot, e))
+        .ok()
+}
+
+/// The run of the slot being played. None if it has no run or it can't be loaded, in which case
+/// a new game has to start.
+pub fn load() -> Option<World> {
+    load_slot(load_slots().current())
+}
+
+/// Continues the lifetime stats and the high scores of previous sessions in `world`.
+pub fn load_shared(world: &mut World) {
+    world.set_previous_runs(storage::load_or_default(
+        LIFETIME_STATS_KEY,
+        Stats::from_text,
+    ));
+    world.set_high_scores(storage::load_or_default(
+        HIGH_SCORES_KEY,
+        HighScores::from_text,
+    ));
+}
+
+pub fn save(world: &World) {
+    let mut slots = load_slots();
+    storage::save_logged(&SaveSlots::key(slots.current()), &world.to_snapshot());
+    slots.touch(slots.current(), now());
+    save_slots(&slots);
+    storage::save_logged(LIFETIME_STATS_KEY, &world.previous_runs().to_text());
+    storage::save_logged(HIGH_SCORES_KEY, &world.high_scores().to_text());
+}
+
+/// Saves when the act changes or a new run starts, when quitting, and periodically.
+pub fn save_if_needed(world: &World, previous_frame: i64, run_changed: bool, quitting: bool) {
+    let periodic = world.frame != previous_frame && world.frame % SAVE_PERIOD_FRAMES == 0;
+    if run_changed || quitting || periodic {
+        save(world);
+    }
+}
+
+pub fn list_slots() -> Vec<SlotSummary> {
+    let slots = load_slots();
+    slots
+        .slots()
+        .iter()
+        .map(|info| SlotSummary {
+            id: info.id,
+            name: info.name.clone(),
+            last_played: info.last_played,
+            is_current: info.id == slots.current(),
+            run: load_slot(info.id).map(|world| RunSummary {
+                act: world.stage(),
+                savings: world.money(),
+                play_time: world.stats().total_time(),
+            }),
+        })
+        .collect()
+}
+
+pub fn is_full() -> bool {
+    load_slots().is_full()
+}
+
+/// Loading a slot or starting a new one replaces the run of `world`, after saving it in its slot.
+pub fn apply_slot_action(world: &mut World, action: SlotAction) {
+    let mut slots = load_slots();
+    match action {
+        SlotAction::Load(slot) => {
+            if slot == slots.current() {
+                return;
+            }
+            // a slot without a run starts a new one
+            let run = load_slot(slot).unwrap_or_else(|| World::new(world.config().clone()));
+            save(world);
+            slots = load_slots();
+            if slots.set_current(slot) {
+                world.continue_run(run);
+            }
+        }
+        SlotAction::New => {
+            save(world);
+            slots = load_slots();
+            if let Some(slot) = slots.add("") {
+                slots.set_current(slot);
+                world.continue_run(World::new(world.config().clone()));
+            }
+        }
+        SlotAction::Duplicate { slot, name } => {
+            if slot == slots.current() {
+                save(world);
+                slots = load_slots();
+            }
+            let text = storage::load(&SaveSlots::key(slot));
+            if let (Some(text), Some(copy)) = (text, slots.add(&name)) {
+                storage::save_logged(&SaveSlots::key(copy), &text);
+                let last_played = slots.get(slot).and_then(|info| info.last_played);
+                if let Some(last_played) = last_played {
+                    slots.touch(copy, last_played);
+                }
+            }
+        }
+        SlotAction::Delete(slot) => {
+            if slots.remove(slot) {
+                if let Err(e) = storage::remove(&SaveSlots::key(slot)) {
+                    warn!("{}", e);
+                }
+            }
+        }
+        SlotAction::Rename { slot, name } => slots.rename(slot, &name),
+    }
+    save_slots(&slots);
+}
@@ -0,0 +1,91 @@
+use macroquad::prelude::warn;
+
+use crate::external::storage;
+use crate::screen::translations::Language;
+
+const SETTINGS_KEY: &str = "settings";
+
+/// User preferences that survive restarting the game. Stored as `key=value` lines.
+#[derive(Debug, PartialEq)]
+pub struct Settings {
+    pub language: Option<Language>,
+    /// Of every sound, from 0 to 1.
+    pub volume: f32,
+    pub muted: bool,
+    /// Whether clicks and purchases show particles.
+    pub effects: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: None,
+            volume: 1.0,
+            muted: false,
+            effects: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        storage::load_or_default(SETTINGS_KEY, Self::from_text)
+    }
+
+    pub fn save(&self) {
+        storage::save_logged(SETTINGS_KEY, &self.to_text());
+    }
+
+    fn from_text(text: &str) -> Self {
+        let mut settings = Self::default();
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let value = value.trim();
+            match key.trim() {
+                "language" => settings.language = Language::from_locale(value),
+                "volume" => match value.parse::<f32>() {
+                    Ok(volume) if (0.0..=1.0).contains(&volume) => settings.volume = volume,
+                    _ => warn!("ignoring invalid volume {:?}", value),
+                },
+                "muted" => settings.muted = value == "true",
+                "effects" => settings.effects = value != "false",
+                _ => warn!("ignoring unknown setting {:?}", key),
+            }
+        }
+        settings
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        if let Some(language) = self.language {
+            text += &format!("language={}\n", language.code());
+        }
+        text += &format!("volume={}\n", self.volume);
+        text += &format!("muted={}\n", self.muted);
+        text += &format!("effects={}\n", self.effects);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let settings = Settings {
+            language: Some(Language::Spanish),
+            volume: 0.3,
+            muted: true,
+            effects: false,
+        };
+        assert_eq!(Settings::from_text(&settings.to_text()), settings);
+    }
+
+    #[test]
+    fn test_invalid_values_keep_the_default() {
+        let settings = Settings::from_text("volume=loud\nlanguage=es\n");
+        assert_eq!(settings.volume, 1.0);
+        assert_eq!(settings.language, Some(Language::Spanish));
+        assert_eq!(Settings::from_text("volume=7").volume, 1.0);
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use macroquad::audio::{load_sound_from_bytes, Sound};
+use macroquad::prelude::{trace, warn};
+
+use crate::external::asset_manifest::{AssetManifest, SoundEntry};
+use crate::external::audio::Sounds;
+use crate::external::embedded_assets::load_asset;
+use crate::external::texture_loader::{Concurrent, LoadCounter, Progress};
+
+/// Loads the sounds of the manifest like `TextureLoader` does with textures. A sound that can't be
+/// loaded is not an error, it's just not played.
+pub struct SoundLoader {
+    entries: Vec<SoundEntry>,
+    sounds: HashMap<String, Sound>,
+    /// Each finished file gives its sound and size, or why it couldn't be loaded.
+    files: Concurrent<Result<(Sound, usize), String>>,
+    counter: LoadCounter,
+}
+
+impl SoundLoader {
+    pub fn new(manifest: &AssetManifest, max_in_flight: usize) -> Self {
+        let entries = manifest.sounds.clone();
+        Self {
+            files: Concurrent::new(entries.len(), max_in_flight),
+            counter: LoadCounter::new(entries.iter().map(|entry| entry.path.as_str())),
+            entries,
+            sounds: HashMap::new(),
+        }
+    }
+
+    pub fn get_progress(&self) -> Progress {
+        self.counter.progress()
+    }
+
+    /// returns None until all sounds were loaded or failed to load
+    pub fn get_sounds(&mut self) -> Option<Sounds> {
+        if !self.files.is_done() {
+            let entries = &self.entries;
+            let finished = self.files.poll(|index| {
+                let path = entries[index].path.clone();
+                Box::pin(async move {
+                    let bytes = load_asset(&path).await.map_err(|e| e.to_string())?;
+                    check_format(&bytes)?;
+                    let sound = load_sound_from_bytes(&bytes)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    Ok((sound, bytes.len()))
+                })
+            });
+            for (index, file) in finished {
+                let entry = &self.entries[index];
+                match file {
+                    Ok((sound, bytes)) => {
+                        self.counter.finish(index, Some(bytes));
+                        self.sounds.insert(entry.id.clone(), sound);
+                    }
+                    Err(e) => {
+                        self.counter.finish(index, None);
+                        warn!("sound {:?} won't play: {}", entry.id, e);
+                    }
+                }
+            }
+            return None;
+        }
+        trace!("finished loading sounds");
+        Some(Sounds::new(std::mem::take(&mut self.sounds)))
+    }
+}
+
+/// macroquad panics when decoding a file that it can't play, so they are rejected before. On web
+/// the browser decodes them, and only the header is checked, to catch a wrong path or file type.
+fn check_format(bytes: &[u8]) -> Result<(), String> {
+    let is_wav = bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE";
+    let is_ogg = bytes.starts_with(b"OggS");
+    if !is_wav && !is_ogg {
+        return Err("only wav and ogg files are supported".to_string());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    check_decodes(bytes)?;
+    Ok(())
+}
+
+/// Decodes the whole file like macroquad does on native, which catches truncated and corrupt
+/// files, and files with more channels than it plays.
+#[cfg(not(target_arch = "wasm32"))]
+fn check_decodes(bytes: &[u8]) -> Result<(), String> {
+    let mut reader = audrey::Reader::new(std::io::Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let channels = reader.description().channel_count();
+    if channels != 1 && channels != 2 {
+        return Err(format!(
+            "has {} channels, only mono and stereo are supported",
+            channels
+        ));
+    }
+    for sample in reader.samples::<f32>() {
+        sample.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16-bit wav header for `samples` samples of each of `channels`, followed by `data`.
+    fn wav(channels: u16, samples: u32, data: &[u8]) -> Vec<u8> {
+        let block_align = channels * 2;
+        let data_len = samples * block_align as u32;
+        let mut bytes = Vec::new();
+        bytes.extend(b"RIFF");
+        bytes.extend((36 + data_len).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16_u32.to_le_bytes());
+        bytes.extend(1_u16.to_le_bytes());
+        bytes.extend(channels.to_le_bytes());
+        bytes.extend(22050_u32.to_le_bytes());
+        bytes.extend((22050 * block_align as u32).to_le_bytes());
+        bytes.extend(block_align.to_le_bytes());
+        bytes.extend(16_u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend(data_len.to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_check_format() {
+        let wav = std::fs::read("assets/sounds/click_clean.wav").unwrap();
+        assert_eq!(check_format(&wav), Ok(()));
+        assert!(check_format(b"OggS and more").is_err());
+        assert!(check_format(b"RIFF").is_err());
+        let png = std::fs::read("assets/images/ferris.png").unwrap();
+        assert!(check_format(&png).is_err());
+        for entry in std::fs::read_dir("assets/sounds").unwrap() {
+            let path = entry.unwrap().path();
+            let bytes = std::fs::read(&path).unwrap();
+            assert_eq!(check_format(&bytes), Ok(()), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_broken_wavs_are_rejected() {
+        assert_eq!(check_format(&wav(2, 4, &[0; 16])), Ok(()));
+
+        let truncated = std::fs::read("assets/sounds/click_clean.wav").unwrap();
+        assert!(check_format(&truncated[..truncated.len() / 2]).is_err());
+        assert!(check_format(&wav(1, 100, &[0; 10])).is_err());
+
+        let six_channels = check_format(&wav(6, 4, &[0; 48]));
+        assert!(six_channels.unwrap_err().contains("6 channels"));
+    }
+}
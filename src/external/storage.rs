@@ -0,0 +1,104 @@
+//! Tiny persistence layer for settings and saves.
+//!
+//! On native, each key is a text file inside [`SAVE_FOLDER`]. On wasm, each key is an entry of the
+//! browser's localStorage, accessed through the plugin in `export_html/double_clicker.js`.
+
+use macroquad::prelude::warn;
+
+#[cfg(not(target_family = "wasm"))]
+pub const SAVE_FOLDER: &str = "saves";
+
+/// Returns None if nothing was saved under this key, or if it couldn't be read.
+pub fn load(key: &str) -> Option<String> {
+    platform::load(key)
+}
+
+pub fn save(key: &str, contents: &str) -> Result<(), String> {
+    platform::save(key, contents)
+}
+
+/// What was saved under `key`, read with `from_text`, or the default if nothing was saved.
+pub fn load_or_default<T: Default>(key: &str, from_text: impl FnOnce(&str) -> T) -> T {
+    load(key).map(|text| from_text(&text)).unwrap_or_default()
+}
+
+/// Like `save`, for what is saved again later anyway: failing is only logged.
+pub fn save_logged(key: &str, contents: &str) {
+    if let Err(e) = save(key, contents) {
+        warn!("{}", e);
+    }
+}
+
+/// Removing a key that doesn't exist is not an error.
+pub fn remove(key: &str) -> Result<(), String> {
+    platform::remove(key)
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod platform {
+    use std::path::PathBuf;
+
+    use crate::external::storage::SAVE_FOLDER;
+
+    fn path(key: &str) -> PathBuf {
+        PathBuf::from(SAVE_FOLDER).join(format!("{}.txt", key))
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        std::fs::read_to_string(path(key)).ok()
+    }
+
+    pub fn save(key: &str, contents: &str) -> Result<(), String> {
+        std::fs::create_dir_all(SAVE_FOLDER)
+            .and_then(|_| std::fs::write(path(key), contents))
+            .map_err(|e| format!("could not save {:?}: {}", path(key), e))
+    }
+
+    pub fn remove(key: &str) -> Result<(), String> {
+        match std::fs::remove_file(path(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("could not remove {:?}: {}", path(key), e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod platform {
+    extern "C" {
+        fn double_clicker_storage_len(key: *const u8, key_len: usize) -> i32;
+        fn double_clicker_storage_get(key: *const u8, key_len: usize, out: *mut u8, out_len: usize);
+        fn double_clicker_storage_set(
+            key: *const u8,
+            key_len: usize,
+            value: *const u8,
+            value_len: usize,
+        );
+        fn double_clicker_storage_remove(key: *const u8, key_len: usize);
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        let len = unsafe { double_clicker_storage_len(key.as_ptr(), key.len()) };
+        if len < 0 {
+            return None;
+        }
+        let mut buffer = vec![0_u8; len as usize];
+        unsafe {
+            double_clicker_storage_get(key.as_ptr(), key.len(), buffer.as_mut_ptr(), buffer.len())
+        };
+        String::from_utf8(buffer).ok()
+    }
+
+    pub fn save(key: &str, contents: &str) -> Result<(), String> {
+        unsafe {
+            double_clicker_storage_set(key.as_ptr(), key.len(), contents.as_ptr(), contents.len())
+        };
+        Ok(())
+    }
+
+    pub fn remove(key: &str) -> Result<(), String> {
+        unsafe { double_clicker_storage_remove(key.as_ptr(), key.len()) };
+        Ok(())
+    }
+}
@@ -1,24 +1,58 @@
+use std::rc::Rc;
+
 use macroquad::prelude::*;
 
+use crate::external::audio::{Audio, SoundEffect, VOLUME_STEP};
 use crate::external::backends::{now, Seconds};
+use crate::external::export::{export_run, unique_name};
+use crate::external::settings::Settings;
 use crate::external::texture_drawer::buttons::Buttons;
+use crate::external::texture_drawer::crowd::Crowd;
 use crate::external::texture_drawer::draw::draw_panel_border;
+use crate::external::texture_drawer::history_panel::draw_history_panel;
+use crate::external::texture_drawer::leaderboard_panel::{draw_leaderboard_panel, format_score};
+use crate::external::texture_drawer::name_entry_panel::{draw_name_entry_panel, NameEntry};
+use crate::external::texture_drawer::new_game_panel::{draw_new_game_panel, NewGame};
+use crate::external::texture_drawer::particles::Particles;
+use crate::external::texture_drawer::slots_panel::{SlotsScreen, SlotsScreenEvent};
+use crate::external::texture_drawer::stats_panel::draw_stats_panel;
 use crate::external::widgets::anchor::Anchor;
 use crate::external::widgets::button::Interaction;
 use crate::external::widgets::text::{
-    draw_text_centered, draw_text_lines, draw_tooltip_centered, wrap_or_hide_text, TextRect,
+    draw_text_centered, draw_text_lines, draw_tooltip_centered, draw_tooltip_centered_faded,
+    wrap_or_hide_text, TextRect,
 };
+use crate::external::widgets::text_input::TextInputEvent;
+use crate::external::widgets::tween::{Animations, Easing, Timeline};
 use crate::screen::drawer_trait::{Button, DrawerTrait};
-use crate::screen::textures::{Texture, Textures};
-use crate::screen::translations::{get_translation, Language, Translation};
-use crate::screen::GuiActions;
+use crate::screen::number_format::{
+    format_duration, format_money, format_money_exact, format_number,
+};
+use crate::screen::textures::{Sprite, Texture, Textures};
+use crate::screen::translation_check::TextWidthLimit;
+use crate::screen::translations::{Language, Translation, Translations};
+use crate::screen::{GuiActions, SlotAction};
 use crate::world::acts::Act;
+use crate::world::alerts::Alert;
+use crate::world::config::{ConfigField, Difficulty, GameMode, WorldConfig};
 use crate::world::heores::Hero;
-use crate::world::{accumulate_price, World};
+use crate::world::history::HistoryWindow;
+use crate::world::timeline::EventKind;
+use crate::world::{to_cents, World};
 use crate::GIT_VERSION;
 
 mod buttons;
+mod crowd;
 pub mod draw;
+mod history_panel;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod leaderboard_panel;
+mod name_entry_panel;
+mod new_game_panel;
+mod particles;
+mod slots_panel;
+mod stats_panel;
 
 pub const CLEAN_COLOR: Color = SKYBLUE;
 pub const DIRTY_COLOR: Color = PURPLE;
@@ -41,6 +75,29 @@ const BUY_PANEL_VERTICAL_PAD: f32 = 0.02;
 
 const TOOLTIP_WIDTH: f32 = 0.3;
 
+const GAME_END_PANEL_WIDTH: f32 = 0.3;
+
+const EXPORT_MESSAGE_PERSISTENCE: Seconds = 5.0;
+const MISSING_ASSETS_PERSISTENCE: Seconds = 15.0;
+/// Files listed in the missing assets warning, the rest are only counted.
+const MAX_MISSING_ASSETS_SHOWN: usize = 5;
+
+/// How long the dirtiness bar and the background take to catch up with the world.
+const BAR_SMOOTHING: Seconds = 0.3;
+const ALERT_FADE_IN: Seconds = 0.15;
+const ALERT_FADE_OUT: Seconds = 0.4;
+/// How long the alerts below one that disappears take to move up.
+const ALERT_SLIDE: Seconds = 0.2;
+
+/// Ids of the widgets in `TextureDrawer::animations`. Alerts have one each, see `alert_id`.
+const DIRTINESS_ID: &str = "dirtiness";
+const CLEAN_BUTTON_ID: &str = "button.clean";
+const DIRTY_BUTTON_ID: &str = "button.dirty";
+const ACT_OVERLAY_ID: &str = "act_overlay";
+
+/// Width divided by height of the images in assets/images/characters.
+const CHARACTER_ASPECT_RATIO: f32 = 200.0 / 250.0;
+
 pub struct TextureDrawer {
     frame: i64,
     previous_time: Seconds,
@@ -54,9 +111,66 @@ pub struct TextureDrawer {
     stage: Act,
     width: f32,
     height: f32,
-    translation: &'static Translation,
+    translations: Translations,
+    language: Language,
+    translation: Rc<Translation>,
     extra_controls: bool,
     show_debug_fps: bool,
+    show_stats: bool,
+    show_history: bool,
+    history_window: HistoryWindow,
+    export_requested: bool,
+    /// When it was shown, and the result of the last export.
+    export_message: Option<(Seconds, String)>,
+    /// When the game started, to show the warning about missing assets for a while.
+    started_at: Seconds,
+    /// The config of the world being drawn, to start the new-game screen from it.
+    config: WorldConfig,
+    /// Some while the new-game screen is open.
+    new_game: Option<NewGame>,
+    /// Some while the name-entry screen is open, which goes above the new-game screen.
+    name_entry: Option<NameEntry>,
+    /// Some while the save slots screen is open.
+    slots: Option<SlotsScreen>,
+    audio: Audio,
+    particles: Particles,
+    /// How many events of the timeline of the world already threw their coins.
+    events_seen: usize,
+    crowd: Crowd,
+    animations: Animations,
+    /// The alerts being drawn, which includes the ones fading out after the world removed them.
+    alerts: Vec<ShownAlert>,
+    #[cfg(feature = "hot-reload")]
+    hot_reload: Option<hot_reload::HotReload>,
+}
+
+struct ShownAlert {
+    id: String,
+    alert: Alert,
+    leaving: bool,
+}
+
+/// Alerts raised every frame are the same alert while they last.
+fn alert_id(time: Seconds, alert: Alert) -> String {
+    if alert.is_continuous() {
+        format!("alert.{}", alert.id())
+    } else {
+        format!("alert.{}.{}", alert.id(), time)
+    }
+}
+
+/// Shrinks and then grows past its size before settling, like a pressed spring.
+fn press_bounce() -> Timeline {
+    Timeline::new(1.0)
+        .then(0.05, 0.9, Easing::QuadOut)
+        .then(0.3, 1.0, Easing::BackOut)
+}
+
+fn act_overlay() -> Timeline {
+    Timeline::new(0.0)
+        .then(0.4, 1.0, Easing::QuadOut)
+        .hold(1.2)
+        .then(0.6, 0.0, Easing::QuadIn)
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -74,17 +188,34 @@ const AVAILABLE_ARRANGEMENTS: &[Arrangement] = &[
 ];
 
 impl TextureDrawer {
-    pub fn new(textures: Textures, translation: &'static Translation) -> Self {
-        Self::new_from_mocked(textures, screen_width(), screen_height(), translation)
+    pub fn new(textures: Textures, translations: Translations, language: Language) -> Self {
+        Self::new_from_mocked(
+            textures,
+            screen_width(),
+            screen_height(),
+            translations,
+            language,
+        )
     }
     pub fn new_from_mocked(
         textures: Textures,
         width: f32,
         height: f32,
-        translation: &'static Translation,
+        translations: Translations,
+        language: Language,
     ) -> Self {
         let font_size = Self::choose_font_size(width, height);
-        let buttons = buttons::create_buttons(font_size, width, height, &textures, translation);
+        let translation = translations.get(language);
+        let audio = Audio::default();
+        let buttons = buttons::create_buttons(
+            font_size,
+            width,
+            height,
+            &textures,
+            &translation,
+            &Self::sound_text(&audio, &translation),
+            Self::effects_text(true, &translation),
+        );
         Self {
             frame: 0,
             previous_time: now(),
@@ -98,12 +229,52 @@ impl TextureDrawer {
             stage: Act::Act1,
             width,
             height,
+            translations,
+            language,
             translation,
             extra_controls: false,
             show_debug_fps: false,
+            show_stats: false,
+            show_history: false,
+            history_window: HistoryWindow::LastMinute,
+            export_requested: false,
+            export_message: None,
+            started_at: now(),
+            config: WorldConfig::default(),
+            new_game: None,
+            name_entry: None,
+            slots: None,
+            audio,
+            particles: Particles::default(),
+            events_seen: 0,
+            crowd: Crowd::default(),
+            animations: Animations::default(),
+            alerts: Vec::new(),
+            #[cfg(feature = "hot-reload")]
+            hot_reload: None,
         }
     }
 
+    pub fn set_audio(&mut self, audio: Audio) {
+        self.audio = audio;
+        self.recreate_buttons();
+    }
+
+    pub fn set_effects(&mut self, enabled: bool) {
+        self.particles.set_enabled(enabled);
+        self.recreate_buttons();
+    }
+
+    /// Shows the new-game screen, starting with `config` selected.
+    pub fn open_new_game(&mut self, config: WorldConfig, can_cancel: bool) {
+        self.new_game = Some(NewGame { config, can_cancel });
+    }
+
+    /// Shows the name-entry screen, starting with `name` selected.
+    pub fn open_name_entry(&mut self, name: &str) {
+        self.name_entry = Some(NameEntry::new(name));
+    }
+
     fn choose_font_size(width: f32, height: f32) -> f32 {
         let min_side = width.min(height * 16.0 / 9.0);
         FONT_SIZE
@@ -115,16 +286,188 @@ impl TextureDrawer {
                 2.0
             }
     }
+    /// Where single-line texts have to fit in this layout, for the translation checks.
+    /// Buttons add a padding of one font size at each side of the text.
+    pub fn text_width_limits(width: f32, height: f32) -> Vec<TextWidthLimit> {
+        let font_size = Self::choose_font_size(width, height);
+        let character_width = height * BUY_PANEL_HEIGHT * CHARACTER_ASPECT_RATIO;
+        let pad = width * 0.01;
+        let limit = |prefix, max_width, font_size| TextWidthLimit {
+            prefix,
+            max_width,
+            font_size,
+        };
+        vec![
+            limit("buy", width * 0.08 - 2.0 * font_size, font_size),
+            limit(
+                "sell",
+                width * (BUY_PANEL_WIDTH - 0.1) - character_width - 2.0 * font_size,
+                font_size,
+            ),
+            limit(
+                "name",
+                width * BUY_PANEL_WIDTH - pad - character_width,
+                font_size * 1.25,
+            ),
+            limit("description", width * TOOLTIP_WIDTH - 2.0 * pad, font_size),
+            limit("alerts", width - 2.0 * font_size, font_size),
+            limit("missing_assets", width - 2.0 * font_size, font_size),
+            limit("cleaning_speed", width * 0.5 - font_size, font_size),
+            limit("dirtying_speed", width * 0.5 - font_size, font_size),
+            limit("over_greedy", width * GAME_END_PANEL_WIDTH, font_size),
+            limit("owned_by_dirt", width * GAME_END_PANEL_WIDTH, font_size),
+            limit("you_won", width * GAME_END_PANEL_WIDTH, font_size),
+            limit("retire", width * GAME_END_PANEL_WIDTH, font_size),
+            limit(
+                "you_can_continue_playing",
+                width * GAME_END_PANEL_WIDTH,
+                font_size,
+            ),
+            limit(
+                "stats",
+                stats_panel::label_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "new_game",
+                new_game_panel::label_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "difficulty",
+                new_game_panel::choice_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "mode",
+                new_game_panel::choice_max_width(width, font_size),
+                font_size,
+            ),
+            limit("time_up", width * GAME_END_PANEL_WIDTH, font_size),
+            limit("endless_lasted", width * GAME_END_PANEL_WIDTH, font_size),
+            limit("zen_won", width * GAME_END_PANEL_WIDTH, font_size),
+            limit("high_score", width * GAME_END_PANEL_WIDTH, font_size),
+            limit(
+                "name_entry",
+                name_entry_panel::text_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots",
+                slots_panel::text_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "act",
+                slots_panel::text_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots.load",
+                slots_panel::button_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots.duplicate",
+                slots_panel::button_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots.rename",
+                slots_panel::button_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots.done",
+                slots_panel::button_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots.delete",
+                slots_panel::button_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "slots.confirm_delete",
+                slots_panel::button_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "leaderboard.title",
+                leaderboard_panel::title_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "leaderboard.category",
+                leaderboard_panel::label_max_width(width, font_size),
+                font_size,
+            ),
+            limit(
+                "leaderboard.anonymous",
+                leaderboard_panel::label_max_width(width, font_size),
+                font_size,
+            ),
+        ]
+    }
+
     fn recreate_buttons(&mut self) {
         self.buttons = buttons::create_buttons(
             self.font_size,
             self.width,
             self.height,
             &self.textures,
-            self.translation,
+            &self.translation,
+            &Self::sound_text(&self.audio, &self.translation),
+            Self::effects_text(self.particles.is_enabled(), &self.translation),
         );
     }
 
+    fn effects_text(enabled: bool, translation: &Translation) -> &str {
+        if enabled {
+            &translation.effects.on
+        } else {
+            &translation.effects.off
+        }
+    }
+
+    fn sound_text(audio: &Audio, translation: &Translation) -> String {
+        if audio.is_muted() {
+            translation.sound.muted.clone()
+        } else {
+            let percent = (audio.volume() * 100.0).round().to_string();
+            translation.sound.volume.format(&[("volume", &percent)])
+        }
+    }
+
+    /// Also remembers the choice for the next time the game starts.
+    fn toggle_effects(&mut self) {
+        self.set_effects(!self.particles.is_enabled());
+        let mut settings = Settings::load();
+        settings.effects = self.particles.is_enabled();
+        settings.save();
+    }
+
+    /// Also remembers the volume for the next time the game starts.
+    fn change_volume(&mut self, volume: f32, muted: bool) {
+        self.audio.set_volume(volume);
+        self.audio.set_muted(muted);
+        self.recreate_buttons();
+        let mut settings = Settings::load();
+        settings.volume = self.audio.volume();
+        settings.muted = self.audio.is_muted();
+        settings.save();
+    }
+
+    /// Also remembers the choice for the next time the game starts.
+    fn change_language(&mut self, language: Language) {
+        self.language = language;
+        self.translation = self.translations.get(language);
+        self.recreate_buttons();
+        let mut settings = Settings::load();
+        settings.language = Some(language);
+        settings.save();
+    }
+
     fn resize(&mut self, width: f32, height: f32) {
         self.width = width;
         self.height = height;
@@ -140,7 +483,21 @@ impl TextureDrawer {
 impl DrawerTrait for TextureDrawer {
     fn draw(&mut self, world: &mut World) {
         self.frame += 1;
-        self.stage = world.stage();
+        #[cfg(feature = "hot-reload")]
+        self.reload_changed_assets(now());
+        let now_time = now();
+        let effects = self.audio.update(world);
+        self.spawn_particles(&effects, world);
+        self.particles.update(now_time, self.height);
+        self.crowd.sync(&world.heroes_count);
+        self.crowd.update(now_time);
+        let previous_stage = std::mem::replace(&mut self.stage, world.stage());
+        if self.stage != previous_stage && matches!(self.stage, Act::Act2 | Act::Act3) {
+            self.animations
+                .play(ACT_OVERLAY_ID, act_overlay(), now_time);
+        }
+        self.animations.remove_finished(now_time);
+        self.config = world.config().clone();
         self.dirtiness = Self::dirtiness_from_world(world);
         let width = screen_width();
         let height = screen_height();
@@ -149,23 +506,85 @@ impl DrawerTrait for TextureDrawer {
             self.font_size = Self::choose_font_size(width, height);
         }
 
-        let background_color = Self::get_background_color(self.dirtiness());
+        let shown_dirtiness = self.animations.smooth(
+            DIRTINESS_ID,
+            self.dirtiness,
+            now_time,
+            BAR_SMOOTHING,
+            Easing::QuadOut,
+        );
+        let background_color = Self::get_background_color(shown_dirtiness);
         clear_background(background_color);
         self.draw_background_pattern(width, height);
-        self.draw_bar_and_money(world, width, height, self.font_size);
-        self.draw_clean_and_dirty();
+        self.draw_bar_and_money(world, shown_dirtiness, width, height, self.font_size);
+        self.draw_clean_and_dirty(now_time);
         self.draw_buy_heroes(world, width, height, self.font_size);
+        self.crowd.draw(
+            self.crowd_area(width, height),
+            &self.textures,
+            self.font_size,
+            &world.heroes_count,
+            self.language,
+            now_time,
+        );
+        self.particles.draw();
         draw_text_bar(
             world,
             width,
             height,
             self.font_size,
             now(),
-            self.translation,
+            &self.translation,
         );
-        draw_alerts(world, width, height, self.font_size, self.translation);
+        self.update_shown_alerts(world, now_time);
+        self.draw_alerts(width, height, now_time);
         self.draw_game_over(world, width, height, self.font_size);
         self.draw_game_won(world, width, height, self.font_size);
+        self.draw_act_overlay(width, height, now_time);
+        if self.export_requested {
+            self.export_requested = false;
+            self.export(world);
+        }
+        self.draw_export_message(width, height);
+        self.draw_missing_assets(width, height);
+        if self.show_history {
+            draw_history_panel(
+                world,
+                self.history_window,
+                &mut self.buttons.history_windows,
+                width,
+                height,
+                self.font_size,
+                &self.translation,
+            );
+        }
+        if self.show_stats {
+            draw_stats_panel(world, width, height, self.font_size, &self.translation);
+        }
+        if let Some(name_entry) = &mut self.name_entry {
+            let input_rect = name_entry_panel::input_rect(width, height, self.font_size);
+            name_entry.input.set_rect(input_rect, self.font_size);
+            draw_name_entry_panel(
+                name_entry,
+                &self.buttons.confirm_name,
+                width,
+                height,
+                self.font_size,
+                &self.translation,
+            );
+        } else if let Some(slots) = &mut self.slots {
+            slots.layout(width, height, self.font_size, &self.translation);
+            slots.draw(width, height, self.font_size, &self.translation);
+        } else if let Some(new_game) = &self.new_game {
+            draw_new_game_panel(
+                new_game,
+                &mut self.buttons.new_game,
+                width,
+                height,
+                self.font_size,
+                &self.translation,
+            );
+        }
         self.buttons
             .change_language_to_spanish
             .set_color(background_color)
@@ -174,6 +593,15 @@ impl DrawerTrait for TextureDrawer {
             .change_language_to_english
             .set_color(background_color)
             .render();
+        self.buttons.history.set_color(background_color).render();
+        self.buttons.slots.set_color(background_color).render();
+        self.buttons
+            .volume_down
+            .set_color(background_color)
+            .render();
+        self.buttons.mute.set_color(background_color).render();
+        self.buttons.volume_up.set_color(background_color).render();
+        self.buttons.effects.set_color(background_color).render();
         if self.show_debug_fps {
             self.debug_fps(&world, width, height)
         }
@@ -181,6 +609,10 @@ impl DrawerTrait for TextureDrawer {
         if self.extra_controls {
             extra.show_debug_fps.set_color(background_color).render();
             extra.restart.set_color(background_color).render();
+            extra.stats.set_color(background_color).render();
+            if let Some(export) = &mut extra.export {
+                export.set_color(background_color).render();
+            }
             extra
                 .change_arrangement
                 .set_color(background_color)
@@ -194,6 +626,20 @@ impl DrawerTrait for TextureDrawer {
     }
 
     fn button(&mut self, button: Button) -> bool {
+        let settings_button = matches!(
+            button,
+            Button::ChangeLanguageToSpanish
+                | Button::ChangeLanguageToEnglish
+                | Button::VolumeDown
+                | Button::VolumeUp
+                | Button::Mute
+                | Button::Effects
+        );
+        let screen_open =
+            self.new_game.is_some() || self.name_entry.is_some() || self.slots.is_some();
+        if screen_open && !settings_button {
+            return false;
+        }
         match button {
             Button::Clean => self.buttons.clean.interact().is_clicked(),
             Button::Dirty => self.buttons.dirty.interact().is_clicked(),
@@ -206,12 +652,11 @@ impl DrawerTrait for TextureDrawer {
             Button::Restart => {
                 if self.extra_controls {
                     let button = &mut self.buttons.extra.restart;
-                    if button.interact().is_clicked() {
-                        self.restart();
-                        true
-                    } else {
-                        false
+                    let is_clicked = button.interact().is_clicked();
+                    if is_clicked {
+                        self.open_new_game(self.config.clone(), true);
                     }
+                    is_clicked
                 } else {
                     false
                 }
@@ -224,12 +669,13 @@ impl DrawerTrait for TextureDrawer {
                 }
             }
             Button::ContinueAfterGameOver => {
-                if self.stage == Act::GameOver {
-                    let interaction = &mut self.buttons.continue_after_game_over.interact();
-                    if interaction.is_clicked() {
-                        self.restart();
+                if self.stage.is_final() {
+                    let button = &mut self.buttons.continue_after_game_over;
+                    let is_clicked = button.interact().is_clicked();
+                    if is_clicked {
+                        self.open_new_game(self.config.clone(), true);
                     }
-                    interaction.is_clicked()
+                    is_clicked
                 } else {
                     false
                 }
@@ -246,8 +692,7 @@ impl DrawerTrait for TextureDrawer {
                 let button = &mut self.buttons.change_language_to_spanish;
                 let is_clicked = button.interact().is_clicked();
                 if is_clicked {
-                    self.translation = get_translation(Language::Spanish);
-                    self.recreate_buttons();
+                    self.change_language(Language::Spanish);
                 }
                 is_clicked
             }
@@ -255,8 +700,34 @@ impl DrawerTrait for TextureDrawer {
                 let button = &mut self.buttons.change_language_to_english;
                 let is_clicked = button.interact().is_clicked();
                 if is_clicked {
-                    self.translation = get_translation(Language::English);
-                    self.recreate_buttons();
+                    self.change_language(Language::English);
+                }
+                is_clicked
+            }
+            Button::VolumeDown | Button::VolumeUp => {
+                let (button, step) = if matches!(button, Button::VolumeUp) {
+                    (&mut self.buttons.volume_up, VOLUME_STEP)
+                } else {
+                    (&mut self.buttons.volume_down, -VOLUME_STEP)
+                };
+                let is_clicked = button.interact().is_clicked();
+                if is_clicked {
+                    // changing the volume is a way of unmuting
+                    self.change_volume(self.audio.volume() + step, false);
+                }
+                is_clicked
+            }
+            Button::Mute => {
+                let is_clicked = self.buttons.mute.interact().is_clicked();
+                if is_clicked {
+                    self.change_volume(self.audio.volume(), !self.audio.is_muted());
+                }
+                is_clicked
+            }
+            Button::Effects => {
+                let is_clicked = self.buttons.effects.interact().is_clicked();
+                if is_clicked {
+                    self.toggle_effects();
                 }
                 is_clicked
             }
@@ -272,6 +743,54 @@ impl DrawerTrait for TextureDrawer {
                     false
                 }
             }
+            Button::History => {
+                let is_clicked = self.buttons.history.interact().is_clicked();
+                if is_clicked {
+                    self.show_history = !self.show_history;
+                }
+                is_clicked
+            }
+            Button::SaveSlots => {
+                let is_clicked = self.buttons.slots.interact().is_clicked();
+                if is_clicked {
+                    let mut slots = SlotsScreen::new();
+                    slots.layout(self.width, self.height, self.font_size, &self.translation);
+                    self.slots = Some(slots);
+                }
+                is_clicked
+            }
+            Button::HistoryWindow(window) => {
+                if self.show_history {
+                    let button = self.buttons.history_windows.get_mut(&window).unwrap();
+                    let is_clicked = button.interact().is_clicked();
+                    if is_clicked {
+                        self.history_window = window;
+                    }
+                    is_clicked
+                } else {
+                    false
+                }
+            }
+            Button::Export => match &mut self.buttons.extra.export {
+                Some(button) if self.extra_controls => {
+                    let is_clicked = button.interact().is_clicked();
+                    self.export_requested |= is_clicked;
+                    is_clicked
+                }
+                _ => false,
+            },
+            Button::Stats => {
+                if self.extra_controls {
+                    let button = &mut self.buttons.extra.stats;
+                    let is_clicked = button.interact().is_clicked();
+                    if is_clicked {
+                        self.show_stats = !self.show_stats;
+                    }
+                    is_clicked
+                } else {
+                    false
+                }
+            }
             Button::ExtraControls => {
                 let button = &mut self.buttons.extra.show_extra_controls;
                 let is_clicked = button.interact().is_clicked();
@@ -283,15 +802,115 @@ impl DrawerTrait for TextureDrawer {
         }
     }
 
+    fn new_game(&mut self) -> Option<WorldConfig> {
+        if self.name_entry.is_some() || self.slots.is_some() {
+            return None;
+        }
+        let new_game = self.new_game.as_mut()?;
+        let buttons = &mut self.buttons.new_game;
+        for mode in GameMode::list() {
+            let button = buttons.modes.get_mut(mode).unwrap();
+            if button.interact().is_clicked() {
+                new_game.config.mode = *mode;
+            }
+        }
+        for difficulty in Difficulty::list() {
+            let button = buttons.difficulties.get_mut(difficulty).unwrap();
+            if button.interact().is_clicked() {
+                new_game.config = new_game.config.with_difficulty(*difficulty);
+            }
+        }
+        for field in ConfigField::list() {
+            for increase in [false, true] {
+                let button = buttons.steps.get_mut(&(*field, increase)).unwrap();
+                if button.interact().is_clicked() {
+                    new_game.config.step(*field, increase);
+                }
+            }
+        }
+        if new_game.can_cancel && buttons.cancel.interact().is_clicked() {
+            self.new_game = None;
+            None
+        } else if buttons.start.interact().is_clicked() {
+            let config = new_game.config.clone();
+            self.restart();
+            Some(config)
+        } else {
+            None
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.new_game.is_some() || self.name_entry.is_some() || self.slots.is_some()
+    }
+
+    fn player_name(&mut self) -> Option<String> {
+        let name_entry = self.name_entry.as_mut()?;
+        let input_rect = name_entry_panel::input_rect(self.width, self.height, self.font_size);
+        name_entry.input.set_rect(input_rect, self.font_size);
+        let submitted = name_entry.input.interact() == TextInputEvent::Submitted;
+        if submitted || self.buttons.confirm_name.interact().is_clicked() {
+            let name = name_entry.input.text().to_string();
+            self.name_entry = None;
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        let typing_name = self
+            .name_entry
+            .as_ref()
+            .is_some_and(|name_entry| name_entry.input.is_focused());
+        let typing_slot_name = self.slots.as_ref().is_some_and(SlotsScreen::is_typing);
+        typing_name || typing_slot_name
+    }
+
+    fn slot_action(&mut self) -> Option<SlotAction> {
+        if self.name_entry.is_some() {
+            return None;
+        }
+        match self.slots.as_mut()?.interact(&self.translation) {
+            SlotsScreenEvent::None => None,
+            SlotsScreenEvent::Close => {
+                self.slots = None;
+                None
+            }
+            SlotsScreenEvent::Action(action) => {
+                match action {
+                    SlotAction::Load(_) => self.slots = None,
+                    SlotAction::New => {
+                        self.slots = None;
+                        self.open_new_game(self.config.clone(), false);
+                        self.open_name_entry("");
+                    }
+                    _ => {}
+                }
+                Some(action)
+            }
+        }
+    }
+
     fn apply_gui_actions(&mut self, gui_actions: &GuiActions) {
         if gui_actions.next_arrangement {
             self.next_arrangement();
         }
         if gui_actions.clean_pressed {
             self.next_clean();
+            self.animations.play(CLEAN_BUTTON_ID, press_bounce(), now());
+            self.audio.record(SoundEffect::CleanClick);
         }
         if gui_actions.dirty_pressed {
             self.next_dirty();
+            self.animations.play(DIRTY_BUTTON_ID, press_bounce(), now());
+            self.audio.record(SoundEffect::DirtyClick);
+        }
+        if gui_actions.heroes_bought.values().any(|bought| *bought) {
+            self.audio.record(SoundEffect::Buy);
+        }
+        if gui_actions.heroes_sold.values().any(|sold| *sold) {
+            self.audio.record(SoundEffect::Sell);
         }
     }
 
@@ -310,6 +929,55 @@ impl TextureDrawer {
         );
     }
 
+    /// For what happened this frame, which are the effects that make a sound.
+    /// Coins are thrown for the purchases that went through instead.
+    fn spawn_particles(&mut self, effects: &[SoundEffect], world: &World) {
+        let height = self.height;
+        for effect in effects {
+            match effect {
+                SoundEffect::CleanClick => {
+                    let center = self.buttons.clean.rect().center();
+                    self.particles.bubbles(center, height);
+                    let earned = format_money(world.config().cleaning_per_click, self.language);
+                    let above = center - Vec2::new(0.0, self.buttons.clean.rect().h * 0.5);
+                    self.particles.floating_text(
+                        &format!("+{}", earned),
+                        above,
+                        self.font_size * 1.25,
+                        height,
+                    );
+                }
+                SoundEffect::DirtyClick => {
+                    let center = self.buttons.dirty.rect().center();
+                    self.particles.splats(center, height);
+                }
+                _ => {}
+            }
+        }
+        for hero in self.new_purchases(world) {
+            let center = self.buttons.buy[&hero].rect().center();
+            self.particles.coins(center, height);
+        }
+    }
+
+    /// The heroes bought since the last call, from the events of the world.
+    fn new_purchases(&mut self, world: &World) -> Vec<Hero> {
+        let events = world.timeline().events();
+        if events.len() < self.events_seen {
+            // another run started
+            self.events_seen = 0;
+        }
+        let purchases = events[self.events_seen..]
+            .iter()
+            .filter_map(|event| match event.kind {
+                EventKind::Bought(hero) => Some(hero),
+                _ => None,
+            })
+            .collect();
+        self.events_seen = events.len();
+        purchases
+    }
+
     fn next_clean(&mut self) {
         self.clean_index = (self.clean_index + 1) % 3;
     }
@@ -344,6 +1012,132 @@ impl TextureDrawer {
         self.previous_time = new_time;
     }
 
+    fn export(&mut self, world: &World) {
+        let message = match export_run(world, &unique_name()) {
+            Ok(paths) => {
+                info!("exported {:?}", paths);
+                let paths = paths.join(", ");
+                self.translation.exported.format(&[("path", &paths)])
+            }
+            Err(error) => {
+                warn!("{}", error);
+                self.translation.export_failed.format(&[("error", &error)])
+            }
+        };
+        self.export_message = Some((now(), message));
+    }
+
+    /// Fades in the alerts that the world raised, and fades out the ones it removed.
+    fn update_shown_alerts(&mut self, world: &World, now: Seconds) {
+        let mut raised = Vec::<(String, Alert)>::new();
+        for (time, alert) in &world.alerts {
+            let id = alert_id(*time, *alert);
+            if !raised.iter().any(|(other, _)| *other == id) {
+                raised.push((id, *alert));
+            }
+        }
+        let animations = &mut self.animations;
+        for shown in &mut self.alerts {
+            if !shown.leaving && !raised.iter().any(|(id, _)| *id == shown.id) {
+                shown.leaving = true;
+                let alpha = animations.timeline(&shown.id, now).unwrap_or(1.0);
+                let fade_out = Timeline::new(alpha).then(ALERT_FADE_OUT, 0.0, Easing::QuadIn);
+                animations.play(&shown.id, fade_out, now);
+            }
+        }
+        self.alerts.retain(|shown| {
+            let gone = shown.leaving && !animations.is_playing(&shown.id, now);
+            if gone {
+                animations.forget(&shown.id);
+                animations.forget(&format!("{}.y", shown.id));
+            }
+            !gone
+        });
+        for (id, alert) in raised {
+            if !self.alerts.iter().any(|shown| shown.id == id) {
+                let fade_in = Timeline::new(0.0).then(ALERT_FADE_IN, 1.0, Easing::QuadOut);
+                animations.play(&id, fade_in, now);
+                self.alerts.push(ShownAlert {
+                    id,
+                    alert,
+                    leaving: false,
+                });
+            }
+        }
+    }
+
+    fn draw_alerts(&mut self, width: f32, height: f32, now: Seconds) {
+        for (i, shown) in self.alerts.iter().enumerate() {
+            let alpha = self.animations.timeline(&shown.id, now).unwrap_or(1.0);
+            let y = self.animations.smooth(
+                &format!("{}.y", shown.id),
+                height * 0.5 + i as f32 * 2.0 * self.font_size,
+                now,
+                ALERT_SLIDE,
+                Easing::QuadOut,
+            );
+            draw_tooltip_centered_faded(
+                shown.alert.to_string(&self.translation),
+                Vec2::new(width * 0.5, y),
+                self.font_size,
+                alpha,
+            );
+        }
+    }
+
+    /// The name of the act that just started, over a darkened screen.
+    fn draw_act_overlay(&self, width: f32, height: f32, now: Seconds) {
+        let Some(alpha) = self.animations.timeline(ACT_OVERLAY_ID, now) else {
+            return;
+        };
+        draw_rectangle(0.0, 0.0, width, height, with_alpha(BLACK, 0.4 * alpha));
+        let text = TextRect::new(
+            self.translation.slots.act(self.stage),
+            Anchor::center(width * 0.5, height * 0.5),
+            self.font_size * 3.0,
+        );
+        text.render_text(with_alpha(WHITE, alpha));
+    }
+
+    fn draw_export_message(&mut self, width: f32, height: f32) {
+        if let Some((shown_at, message)) = &self.export_message {
+            if now() - shown_at > EXPORT_MESSAGE_PERSISTENCE {
+                self.export_message = None;
+            } else {
+                draw_tooltip_centered(
+                    message,
+                    Vec2::new(width * 0.5, height - self.font_size * 4.0),
+                    self.font_size,
+                );
+            }
+        }
+    }
+
+    /// The paths of the textures that show a placeholder, so that the player knows why.
+    fn draw_missing_assets(&self, width: f32, height: f32) {
+        let missing = self.textures.missing();
+        if missing.is_empty() || now() - self.started_at > MISSING_ASSETS_PERSISTENCE {
+            return;
+        }
+        let mut lines = vec![self
+            .translation
+            .missing_assets
+            .format_count(missing.len() as i64, &[])];
+        lines.extend(missing.iter().take(MAX_MISSING_ASSETS_SHOWN).cloned());
+        if missing.len() > MAX_MISSING_ASSETS_SHOWN {
+            let more = (missing.len() - MAX_MISSING_ASSETS_SHOWN).to_string();
+            lines.push(
+                self.translation
+                    .missing_assets_more
+                    .format(&[("count", &more)]),
+            );
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let y = height * 0.2 + i as f32 * 2.0 * self.font_size;
+            draw_tooltip_centered(line, Vec2::new(width * 0.5, y), self.font_size);
+        }
+    }
+
     fn restart(&mut self) {
         self.restart_mocked(screen_width(), screen_height())
     }
@@ -354,7 +1148,25 @@ impl TextureDrawer {
         // my guess is that it's because the assignment to *self happens after taking self.textures,
         // during which self is incomplete/invalid. Workaround:
         let textures = std::mem::take(&mut self.textures);
-        *self = Self::new_from_mocked(textures, width, height, self.translation);
+        let started_at = self.started_at;
+        let audio = std::mem::take(&mut self.audio);
+        let effects = self.particles.is_enabled();
+        #[cfg(feature = "hot-reload")]
+        let hot_reload = self.hot_reload.take();
+        *self = Self::new_from_mocked(
+            textures,
+            width,
+            height,
+            self.translations.clone(),
+            self.language,
+        );
+        self.started_at = started_at;
+        self.set_audio(audio);
+        self.set_effects(effects);
+        #[cfg(feature = "hot-reload")]
+        {
+            self.hot_reload = hot_reload;
+        }
     }
 
     pub fn get_background_color(dirtiness: f32) -> Color {
@@ -389,42 +1201,33 @@ impl TextureDrawer {
             for i_width in 0..((width / texture_size.x).ceil() as i32) {
                 let x = texture_size.x * i_width as f32 + offset_x;
                 let y = texture_size.y * i_height as f32 + offset_y;
-                draw_texture_ex(
-                    pattern_texture,
-                    x,
-                    y,
-                    Color::new(1.0, 1.0, 1.0, 0.25),
-                    DrawTextureParams {
-                        dest_size: Some(texture_size),
-                        source: None,
-                        rotation: 0.0,
-                        flip_x: false,
-                        flip_y: false,
-                        pivot: None,
-                    },
-                );
+                pattern_texture.draw(x, y, Color::new(1.0, 1.0, 1.0, 0.25), texture_size);
             }
         }
 
-        draw_texture_ex(
-            self.textures.get(Texture::BackgroundMargin),
+        self.textures.get(Texture::BackgroundMargin).draw(
             0.0,
             0.0,
             WHITE,
-            DrawTextureParams {
-                dest_size: Some(Vec2::new(width, height)),
-                ..Default::default()
-            },
+            Vec2::new(width, height),
         );
     }
 
-    fn draw_bar_and_money(&self, world: &World, width: f32, height: f32, font_size: f32) {
+    /// `dirtiness` is the one shown in the bar, which may be behind the one of the world.
+    fn draw_bar_and_money(
+        &self,
+        world: &World,
+        dirtiness: f32,
+        width: f32,
+        height: f32,
+        font_size: f32,
+    ) {
         let Arrangement {
             overlapping,
             transparency,
         } = AVAILABLE_ARRANGEMENTS[self.arrangement_index];
 
-        draw_bar(world, width, height, overlapping);
+        draw_bar(world, dirtiness, width, height, overlapping);
         // draw_salary(world, width, height, overlapping);
         draw_savings(
             world,
@@ -433,7 +1236,7 @@ impl TextureDrawer {
             overlapping,
             transparency,
             font_size,
-            self.translation,
+            &self.translation,
         );
         draw_speeds(
             world,
@@ -442,7 +1245,15 @@ impl TextureDrawer {
             overlapping,
             transparency,
             font_size,
-            self.translation,
+            &self.translation,
+        );
+        draw_time_left(
+            world,
+            width,
+            height,
+            overlapping,
+            font_size,
+            &self.translation,
         );
         // draw_dirtiness(
         //     world,
@@ -454,9 +1265,10 @@ impl TextureDrawer {
         // );
     }
 
-    fn draw_clean_and_dirty(&self) {
+    fn draw_clean_and_dirty(&self, now: Seconds) {
         use Texture::*;
-        self.buttons.clean.render(
+        let scale = |id| self.animations.timeline(id, now).unwrap_or(1.0);
+        self.buttons.clean.render_scaled(
             vec![
                 self.textures.get(CleanBackground),
                 self.textures.get(self.clean_texture()),
@@ -465,8 +1277,9 @@ impl TextureDrawer {
                 self.textures.get(CleanBackgroundOff),
                 self.textures.get(self.clean_texture()),
             ]),
+            scale(CLEAN_BUTTON_ID),
         );
-        self.buttons.dirty.render(
+        self.buttons.dirty.render_scaled(
             vec![
                 self.textures.get(DirtyBackground),
                 self.textures.get(self.dirty_texture()),
@@ -475,9 +1288,19 @@ impl TextureDrawer {
                 self.textures.get(DirtyBackgroundOff),
                 self.textures.get(self.dirty_texture()),
             ]),
+            scale(DIRTY_BUTTON_ID),
         );
     }
 
+    /// Between the buy panels, from below the clean and dirty buttons to the text bar.
+    fn crowd_area(&self, width: f32, height: f32) -> Rect {
+        let pad = height * BUY_PANEL_VERTICAL_PAD;
+        let left = width * (BUY_PANEL_HORIZONTAL_PAD + BUY_PANEL_WIDTH + 0.01);
+        let top = self.buttons.clean.rect().bottom() + pad;
+        let bottom = height * text_bar_height() - pad;
+        Rect::new(left, top, width - 2.0 * left, (bottom - top).max(0.0))
+    }
+
     fn draw_buy_heroes(&mut self, world: &World, width: f32, height: f32, font_size: f32) {
         let start_height = BUY_PANEL_START_HEIGHT;
         let panel_width = width * BUY_PANEL_WIDTH;
@@ -510,30 +1333,16 @@ impl TextureDrawer {
                 let x = (width * (tooltip_x_coef + pad_coef)).round();
                 let y = height * (tooltip_y_coef + pad_coef); // rounded later
 
-                let (production, kind) = if i % 2 == 0 {
-                    (
-                        hero.production_clean() * world.heroes_count[hero],
-                        self.translation.cleanings,
-                    )
-                } else {
-                    (
-                        hero.production_dirty() * world.heroes_count[hero],
-                        self.translation.dirtyings,
-                    )
-                };
-
-                let invested = format!(
-                    "{} {} {} {} €",
-                    self.translation.you_hired,
-                    world.heroes_count[hero],
-                    self.translation.investing,
-                    accumulate_price(world.heroes_count[hero]) * hero.base_price() as f32
-                );
-                let speed = format!(
-                    "{} {} {} {}",
-                    self.translation.producing, production, kind, self.translation.per_second
-                );
-                let lines = vec![hero.short_description(self.translation), &invested, &speed];
+                let count = world.heroes_count[hero];
+                let invested = world.invested(hero);
+                let invested = self
+                    .translation
+                    .you_hired
+                    .format_count(count, &[("money", &format_money(invested, self.language))]);
+                let speed = hero
+                    .production_message(&self.translation.producing)
+                    .format_count(hero.production().saturating_mul(count), &[]);
+                let lines = vec![hero.short_description(&self.translation), invested, speed];
 
                 let line_height_coef = 1.1;
                 for (i, line) in lines.iter().enumerate() {
@@ -557,7 +1366,7 @@ impl TextureDrawer {
                     BLACK,
                 );
                 let description = wrap_or_hide_text(
-                    hero.long_description(self.translation),
+                    hero.long_description(&self.translation),
                     font_size,
                     font_size * line_height_coef,
                     width * (TOOLTIP_WIDTH - 2.0 * pad_coef),
@@ -606,7 +1415,7 @@ impl TextureDrawer {
             //     1.0,
             //     BLACK,
             // );
-            let character_texture = self.textures.get(hero.texture_index());
+            let character_texture = self.textures.get(hero.texture());
             let texture_size = Vec2::new(
                 panel_rect.h * character_texture.width() / character_texture.height(),
                 panel_rect.h,
@@ -617,40 +1426,41 @@ impl TextureDrawer {
 
             let title_size = font_size * 1.25;
             draw_text(
-                &hero.name(self.translation),
+                hero.name(&self.translation),
                 (text_pos_x).round(),
                 (height * (start_height + 0.01 + vertical_offset) + title_size).round(),
                 title_size,
                 BLACK,
             );
             draw_text(
-                &hero.name(self.translation),
+                hero.name(&self.translation),
                 (text_pos_x + 0.5).round(),
                 (height * (start_height + 0.01 + vertical_offset) + title_size + 0.5).round(),
                 title_size,
                 with_alpha(BLACK, 0.3),
             );
             draw_text(
-                &format!("{}: {} €", self.translation.price, world.price(hero)),
+                &self.translation.price.format(&[(
+                    "price",
+                    &format_money(to_cents(world.price(hero)), self.language),
+                )]),
                 text_pos_x,
                 (height * (start_height + 0.01 + vertical_offset) + font_size * 3.0).round(),
                 font_size,
                 BLACK,
             );
             draw_text(
-                &format!(
-                    "{}: {} x {}",
-                    if i % 2 == 0 {
-                        self.translation.cleaning
-                    } else {
-                        self.translation.dirtying
-                    },
-                    if i % 2 == 0 {
-                        hero.production_clean()
-                    } else {
-                        hero.production_dirty()
-                    },
-                    world.heroes_count[&hero],
+                &if hero.is_hero() {
+                    &self.translation.cleaning
+                } else {
+                    &self.translation.dirtying
+                }
+                .format_count(
+                    world.heroes_count[hero],
+                    &[(
+                        "production",
+                        &format_number(hero.production(), self.language),
+                    )],
                 ),
                 text_pos_x,
                 (height * (start_height + 0.01 + vertical_offset) + font_size * 4.2).round(),
@@ -687,7 +1497,7 @@ impl TextureDrawer {
         hero_index: usize,
         width: f32,
         height: f32,
-        character_texture: Texture2D,
+        character_texture: Sprite,
     ) -> f32 {
         let texture_offset = if hero_index % 2 == 0 {
             0.0
@@ -720,97 +1530,119 @@ impl TextureDrawer {
     }
 
     fn draw_game_over(&mut self, world: &mut World, width: f32, height: f32, font_size: f32) {
-        if world.stage() == Act::GameOver {
-            let text_rect = Rect::new(
-                (width * 0.35).round(),
-                (height * 0.5).round(),
-                (width * 0.3).round(),
-                (height * 0.25).round(),
-            );
-            draw_rectangle(
-                text_rect.x,
-                text_rect.y,
-                text_rect.w,
-                text_rect.h,
-                Color::new(0.7, 0.7, 0.7, 1.00),
-            );
-            draw_rectangle_lines(
-                text_rect.x,
-                text_rect.y,
-                text_rect.w,
-                text_rect.h,
-                2.0,
-                BLACK,
-            );
-            draw_text_centered("GAME OVER", Vec2::new(0.5, 0.55), width, height, font_size);
-            draw_text_centered(
-                self.translation.over_greedy,
-                Vec2::new(0.5, 0.61),
-                width,
-                height,
-                font_size,
-            );
-            draw_text_centered(
-                self.translation.owned_by_dirt,
-                Vec2::new(0.5, 0.64),
-                width,
-                height,
-                font_size,
-            );
-            self.buttons.continue_after_game_over.render();
-        }
+        let language = self.language;
+        let translation = &self.translation;
+        let (title, lines) = match world.stage() {
+            Act::GameOver if world.config().mode == GameMode::Endless => {
+                let time = format_duration(world.stats().total_time());
+                let lasted = translation.endless_lasted.format(&[("time", &time)]);
+                ("GAME OVER", vec![lasted, translation.owned_by_dirt.clone()])
+            }
+            Act::GameOver => (
+                "GAME OVER",
+                vec![
+                    translation.over_greedy.clone(),
+                    translation.owned_by_dirt.clone(),
+                ],
+            ),
+            Act::TimeUp => {
+                let money = format_money(world.money(), language);
+                let saved = translation.time_up_savings.format(&[("money", &money)]);
+                (translation.time_up.as_str(), vec![saved])
+            }
+            _ => return,
+        };
+        let score = score_text(world, translation);
+        draw_game_end_panel(title, score, &lines, width, height, font_size);
+        self.buttons.continue_after_game_over.render();
     }
 
     fn draw_game_won(&self, world: &mut World, width: f32, height: f32, font_size: f32) {
         if world.stage() == Act::GameWon {
-            let text_rect = Rect::new(
-                (width * 0.35).round(),
-                (height * 0.5).round(),
-                (width * 0.3).round(),
-                (height * 0.25).round(),
-            );
-            draw_rectangle(
-                text_rect.x,
-                text_rect.y,
-                text_rect.w,
-                text_rect.h,
-                Color::new(0.7, 0.7, 0.7, 1.00),
-            );
-            draw_rectangle_lines(
-                text_rect.x,
-                text_rect.y,
-                text_rect.w,
-                text_rect.h,
-                2.0,
-                BLACK,
-            );
-            draw_text_centered(
-                self.translation.you_won,
-                Vec2::new(0.5, 0.55),
-                width,
-                height,
-                font_size,
-            );
-            draw_text_centered(
-                self.translation.retire,
-                Vec2::new(0.5, 0.61),
-                width,
-                height,
-                font_size,
-            );
-            draw_text_centered(
-                self.translation.you_can_continue_playing,
-                Vec2::new(0.5, 0.64),
-                width,
-                height,
-                font_size,
-            );
+            let translation = &self.translation;
+            let (title, lines) = if world.config().mode == GameMode::Zen {
+                (
+                    &translation.zen_won,
+                    vec![translation.you_can_continue_playing.clone()],
+                )
+            } else {
+                (
+                    &translation.you_won,
+                    vec![
+                        translation.retire.clone(),
+                        translation.you_can_continue_playing.clone(),
+                    ],
+                )
+            };
+            let score = score_text(world, translation);
+            draw_game_end_panel(title, score, &lines, width, height, font_size);
+            draw_leaderboard_panel(world, width, height * 0.5, font_size, translation);
             self.buttons.continue_playing.render()
         }
     }
 }
 
-fn draw_bar(world: &World, width: f32, height: f32, overlapping: bool) {
+/// The panel of the screens that end a run, with the title, the high score of the mode if this run
+/// was scored, and some more lines.
+fn draw_game_end_panel(
+    title: &str,
+    score: Option<String>,
+    lines: &[String],
+    width: f32,
+    height: f32,
+    font_size: f32,
+) {
+    let text_rect = Rect::new(
+        (width * (0.5 - GAME_END_PANEL_WIDTH * 0.5)).round(),
+        (height * 0.5).round(),
+        (width * GAME_END_PANEL_WIDTH).round(),
+        (height * 0.25).round(),
+    );
+    draw_rectangle(
+        text_rect.x,
+        text_rect.y,
+        text_rect.w,
+        text_rect.h,
+        Color::new(0.7, 0.7, 0.7, 1.00),
+    );
+    draw_rectangle_lines(
+        text_rect.x,
+        text_rect.y,
+        text_rect.w,
+        text_rect.h,
+        2.0,
+        BLACK,
+    );
+    draw_text_centered(title, Vec2::new(0.5, 0.55), width, height, font_size);
+    if let Some(score) = score {
+        draw_text_centered(&score, Vec2::new(0.5, 0.58), width, height, font_size);
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let y = 0.61 + 0.03 * i as f32;
+        draw_text_centered(line, Vec2::new(0.5, y), width, height, font_size);
+    }
+}
+
+/// "New record" with the score of this run, or the best score of the mode and difficulty, in the
+/// main category of the mode.
+fn score_text(world: &World, translation: &Translation) -> Option<String> {
+    let run_score = world.run_score()?;
+    let messages = &translation.high_score;
+    let format = |score| format_score(score, translation);
+    let config = world.config();
+    let category = config.mode.main_category();
+    if world.new_records().contains(&category) {
+        let score = format(run_score);
+        Some(messages.new_record.format(&[("score", &score)]))
+    } else {
+        let best = world
+            .high_scores()
+            .record(config.mode, config.difficulty, category)?;
+        Some(messages.best.format(&[("score", &format(best.score))]))
+    }
+}
+
+fn draw_bar(world: &World, dirtiness_coef: f32, width: f32, height: f32, overlapping: bool) {
     let bar_width = 1.0 - BAR_HORIZONTAL_PAD * 2.0;
     let bar_height = get_bar_height(overlapping);
 
@@ -821,7 +1653,6 @@ fn draw_bar(world: &World, width: f32, height: f32, overlapping: bool) {
         height * bar_height,
         CLEAN_COLOR,
     );
-    let dirtiness_coef = world.dirtiness_units() as f32 / world.max_dirtiness_units() as f32;
     draw_rectangle(
         width * (1.0 - BAR_HORIZONTAL_PAD - bar_width * dirtiness_coef),
         height * BAR_VERTICAL_PAD,
@@ -829,6 +1660,18 @@ fn draw_bar(world: &World, width: f32, height: f32, overlapping: bool) {
         height * bar_height,
         DIRTY_COLOR,
     );
+    // where the TooDirty alert starts, which moves with the capacity
+    let threshold_coef =
+        world.too_dirty_threshold() as f32 / to_cents(world.max_dirtiness_units()) as f32;
+    let threshold_x = (width * (1.0 - BAR_HORIZONTAL_PAD - bar_width * threshold_coef)).round();
+    draw_line(
+        threshold_x,
+        height * BAR_VERTICAL_PAD,
+        threshold_x,
+        height * (BAR_VERTICAL_PAD + bar_height),
+        2.0,
+        with_alpha(BLACK, 0.5),
+    );
     draw_panel_border(
         Rect::new(
             width * BAR_HORIZONTAL_PAD,
@@ -863,7 +1706,7 @@ fn draw_savings(
         height * (BAR_VERTICAL_PAD * 3.0)
     };
     let savings_font_size = font_size * 2.0;
-    let money_text = format!("{} €", world.money_euros());
+    let money_text = format_money(world.money(), translation.language);
     let money_rect = TextRect::new(
         &money_text,
         Anchor::center((width * 0.5).round(), vertical_offset.round()),
@@ -889,9 +1732,12 @@ fn draw_savings(
 
     let (mouse_x, mouse_y) = mouse_position();
     if money_rect.rect.contains(Vec2::new(mouse_x, mouse_y)) {
-        let tooltip_text = translation.savings;
+        let tooltip_text = translation.savings.format(&[(
+            "money",
+            &format_money_exact(world.money(), translation.language),
+        )]);
         let tooltip = TextRect::new(
-            tooltip_text,
+            &tooltip_text,
             Anchor::bottom_left(mouse_x, mouse_y),
             font_size,
         );
@@ -913,6 +1759,33 @@ fn draw_savings(
         tooltip.render_text(BLACK)
     }
 }
+/// Countdown below the savings, in timed runs.
+fn draw_time_left(
+    world: &World,
+    width: f32,
+    height: f32,
+    overlapping: bool,
+    font_size: f32,
+    translation: &Translation,
+) {
+    if let Some(time_left) = world.time_left() {
+        let savings_y = if overlapping {
+            height * (BAR_VERTICAL_PAD * 2.0)
+        } else {
+            height * (BAR_VERTICAL_PAD * 3.0)
+        };
+        let text = translation
+            .time_left
+            .format(&[("time", &format_duration(time_left.ceil()))]);
+        let text_rect = TextRect::new(
+            &text,
+            Anchor::center((width * 0.5).round(), (savings_y + font_size * 2.0).round()),
+            font_size,
+        );
+        text_rect.render_text(BLACK);
+    }
+}
+
 fn draw_speeds(
     world: &World,
     width: f32,
@@ -930,11 +1803,10 @@ fn draw_speeds(
     // let text_color = if overlapping { BLACK } else { WHITE };
     let text_color = BLACK;
 
-    let mut speed = 0;
-    for hero in [Hero::Hero1, Hero::Hero2, Hero::Hero3] {
-        speed += hero.production_clean() * world.heroes_count[&hero];
-    }
-    let cleaning_text = format!("{}: {}", translation.cleaning_speed, speed);
+    let speed = world.cleaning_speed();
+    let cleaning_text = translation
+        .cleaning_speed
+        .format(&[("speed", &format_number(speed, translation.language))]);
     let text_pos = Anchor::top_left(
         (width * (BAR_HORIZONTAL_PAD)).round(),
         (vertical_offset).round(),
@@ -955,11 +1827,10 @@ fn draw_speeds(
     }
     text_rect.render_text(text_color);
 
-    let mut speed = 0;
-    for hero in [Hero::Villain1, Hero::Villain2, Hero::Villain3] {
-        speed += hero.production_dirty() * world.heroes_count[&hero];
-    }
-    let dirtiying_text = format!("{}: {}", translation.dirtying_speed, speed);
+    let speed = world.dirtying_speed();
+    let dirtiying_text = translation
+        .dirtying_speed
+        .format(&[("speed", &format_number(speed, translation.language))]);
     let text_pos = Anchor::top_right(
         (width * (1.0 - BAR_HORIZONTAL_PAD)).round(),
         (vertical_offset).round(),
@@ -996,12 +1867,16 @@ fn draw_dirtiness(
     translation: &Translation,
 ) {
     let vertical_offset = if overlapping { -0.025 } else { 0.05 };
-    let dirtied_str = format!(
-        "{}: {}/{}",
-        translation.dirts,
-        world.dirtiness_units(),
-        world.max_dirtiness_units()
-    );
+    let dirtied_str = translation.dirts.format(&[
+        (
+            "dirtiness",
+            &format_number(world.dirtiness_units(), translation.language),
+        ),
+        (
+            "max_dirtiness",
+            &format_number(world.max_dirtiness_units(), translation.language),
+        ),
+    ]);
     let text_size = measure_text(&dirtied_str, None, font_size as u16, 1.0);
     draw_text(
         &dirtied_str,
@@ -1012,6 +1887,11 @@ fn draw_dirtiness(
     );
 }
 
+/// Where the text bar starts, as a fraction of the screen height.
+fn text_bar_height() -> f32 {
+    BUY_PANEL_START_HEIGHT + 3.0 * (BUY_PANEL_HEIGHT + BUY_PANEL_VERTICAL_PAD)
+}
+
 fn draw_text_bar(
     world: &World,
     width: f32,
@@ -1020,7 +1900,7 @@ fn draw_text_bar(
     now: Seconds,
     translation: &Translation,
 ) {
-    let bar_height = BUY_PANEL_START_HEIGHT + 3.0 * (BUY_PANEL_HEIGHT + BUY_PANEL_VERTICAL_PAD);
+    let bar_height = text_bar_height();
     let dirtiness_coef = world.dirtiness_units() as f32 / world.max_dirtiness_units() as f32;
     draw_rectangle(
         width * 0.0,
@@ -1053,14 +1933,14 @@ fn draw_text_bar(
 
 fn choose_text_lore(stage: Act, now: Seconds, translation: &Translation) -> &str {
     let lore_sentences = match stage {
-        Act::Act1 => translation.lore.act_1,
-        Act::Act2 => translation.lore.act_2,
-        Act::Act3 => translation.lore.act_3,
-        Act::GameOver => translation.lore.game_over,
-        Act::GameWon => translation.lore.game_won,
-        Act::ContinuePlayingAfterWinning => translation.lore.act_3,
+        Act::Act1 => &translation.lore.act_1,
+        Act::Act2 => &translation.lore.act_2,
+        Act::Act3 => &translation.lore.act_3,
+        Act::GameOver | Act::TimeUp => &translation.lore.game_over,
+        Act::GameWon => &translation.lore.game_won,
+        Act::ContinuePlayingAfterWinning => &translation.lore.act_3,
     };
-    *choose_pseudo_random(lore_sentences, now)
+    choose_pseudo_random(lore_sentences, now).as_str()
 }
 
 fn choose_pseudo_random<T>(collection: &[T], now: Seconds) -> &T {
@@ -1083,35 +1963,75 @@ fn draw_version(_width: f32, height: f32, font_size: f32) {
     text_rect.render_text(BLACK);
 }
 
-fn draw_alerts(world: &World, width: f32, height: f32, font_size: f32, translation: &Translation) {
-    for (i, (_, alert)) in world.alerts.iter().enumerate() {
-        draw_tooltip_centered(
-            &alert.to_string(translation),
-            Vec2::new(width * 0.5, height * 0.5 + (i as f32 * 2.0 * font_size)),
-            font_size,
-        );
-    }
-}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
-    #[test]
-    fn test_restart() {
-        let mut textures = Vec::new();
-        for _ in 0..=(Texture::Villain3 as usize) {
+    fn mocked_drawer() -> TextureDrawer {
+        let mut textures = HashMap::new();
+        for texture_id in Texture::list() {
             let mut texture = miniquad::Texture::empty();
             texture.width = 100;
             texture.height = 200;
-            textures.push(Texture2D::from_miniquad_texture(texture))
+            let texture = Texture2D::from_miniquad_texture(texture);
+            textures.insert(texture_id.id().to_string(), Sprite::whole(texture));
         }
-        let mut drawer = TextureDrawer::new_from_mocked(
-            Textures::new(textures.clone()),
+        TextureDrawer::new_from_mocked(
+            Textures::new(textures, Vec::new()),
             2000.0,
             1000.0,
-            get_translation(Language::Spanish),
-        );
+            Translations::compiled_in(),
+            Language::Spanish,
+        )
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut drawer = mocked_drawer();
         drawer.restart_mocked(2000.0, 1000.0);
         drawer.restart_mocked(2000.0, 1000.0);
     }
+
+    #[test]
+    fn test_coins_only_for_purchases_that_went_through() {
+        let mut drawer = mocked_drawer();
+        let mut world = World::new(WorldConfig::default());
+        while world.money_euros() < world.price(&Hero::Hero1) {
+            world.update(GuiActions {
+                dirty_pressed: true,
+                clean_pressed: true,
+                ..GuiActions::default()
+            });
+        }
+        assert!(world.money_euros() < world.price(&Hero::Villain3));
+        let mut buy_both = GuiActions::default();
+        buy_both.heroes_bought.insert(Hero::Hero1, true);
+        buy_both.heroes_bought.insert(Hero::Villain3, true);
+        world.update(buy_both);
+        assert_eq!(drawer.new_purchases(&world), [Hero::Hero1]);
+        assert_eq!(drawer.new_purchases(&world), []);
+
+        let world = World::new(WorldConfig::default());
+        assert_eq!(drawer.new_purchases(&world), []);
+        assert_eq!(drawer.events_seen, 0);
+    }
+
+    #[test]
+    fn test_alerts_fade_out() {
+        let mut drawer = mocked_drawer();
+        let mut world = World::new(WorldConfig::default());
+        world.alerts.push((1.0, Alert::CannotSell));
+        world.alerts.push((1.0, Alert::TooDirty));
+        drawer.update_shown_alerts(&world, 1.0);
+        world.alerts.retain(|(_, alert)| alert.is_continuous());
+        world.alerts.push((1.1, Alert::TooDirty));
+        drawer.update_shown_alerts(&world, 1.1);
+        let leaving = drawer.alerts.iter().map(|shown| shown.leaving);
+        assert_eq!(leaving.collect::<Vec<_>>(), [true, false]);
+
+        drawer.update_shown_alerts(&world, 1.2 + ALERT_FADE_OUT);
+        assert_eq!(drawer.alerts.len(), 1);
+        assert_eq!(drawer.alerts[0].alert, Alert::TooDirty);
+    }
 }
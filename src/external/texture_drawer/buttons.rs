@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use macroquad::prelude::*;
 
+use crate::external::texture_drawer::new_game_panel::NewGameButtons;
+use crate::external::texture_drawer::{history_panel, name_entry_panel, new_game_panel};
 use crate::external::texture_drawer::{
     TextureDrawer, BUY_PANEL_HORIZONTAL_PAD, BUY_PANEL_START_HEIGHT, CLEAN_BACKGROUND_COLOR,
     DIRTY_BACKGROUND_COLOR,
@@ -13,6 +15,7 @@ use crate::external::widgets::texture_button::TextureButton;
 use crate::screen::textures::Textures;
 use crate::screen::translations::Translation;
 use crate::world::heores::Hero;
+use crate::world::history::HistoryWindow;
 
 const BUTTON_PAD: Pixels = 2.0;
 pub struct Buttons {
@@ -24,6 +27,17 @@ pub struct Buttons {
     pub continue_after_game_over: Button,
     pub change_language_to_spanish: Button,
     pub change_language_to_english: Button,
+    pub history: Button,
+    pub slots: Button,
+    pub volume_down: Button,
+    /// Mutes and unmutes, showing the volume.
+    pub mute: Button,
+    pub volume_up: Button,
+    /// Enables and disables the particles.
+    pub effects: Button,
+    pub history_windows: HashMap<HistoryWindow, Button>,
+    pub new_game: NewGameButtons,
+    pub confirm_name: Button,
     pub extra: ExtraControls,
 }
 
@@ -32,6 +46,9 @@ pub struct ExtraControls {
     pub show_debug_fps: Button,
     pub change_arrangement: Button,
     pub restart: Button,
+    pub stats: Button,
+    /// Only on native, where files can be written.
+    pub export: Option<Button>,
 }
 
 pub fn create_buttons(
@@ -40,6 +57,8 @@ pub fn create_buttons(
     height: f32,
     textures: &Textures,
     translation: &Translation,
+    sound_text: &str,
+    effects_text: &str,
 ) -> Buttons {
     let spanish = Button::new(
         "Español",
@@ -54,24 +73,77 @@ pub fn create_buttons(
         ),
         font_size,
     );
+    let history = Button::new(
+        &translation.history.title,
+        Anchor::top_left(
+            english.rect().x - (english.rect().w + BUTTON_PAD),
+            english.rect().y,
+        ),
+        font_size,
+    );
+    let slots = Button::new(
+        &translation.slots.open,
+        Anchor::top_right(history.rect().x - BUTTON_PAD, history.rect().y),
+        font_size,
+    );
+    // a row above the language, so that the bottom row doesn't reach the extra controls
+    let volume_up = Button::new(
+        "+",
+        Anchor::bottom_right(width - BUTTON_PAD, spanish.rect().y - BUTTON_PAD),
+        font_size,
+    );
+    let mute = Button::new(
+        sound_text,
+        Anchor::top_right(volume_up.rect().x - BUTTON_PAD, volume_up.rect().y),
+        font_size,
+    );
+    let volume_down = Button::new(
+        "-",
+        Anchor::top_right(mute.rect().x - BUTTON_PAD, mute.rect().y),
+        font_size,
+    );
+    let effects = Button::new(
+        effects_text,
+        Anchor::top_right(volume_down.rect().x - BUTTON_PAD, volume_down.rect().y),
+        font_size,
+    );
     let (clean, dirty) = create_clean_and_dirty_buttons(width, height);
     Buttons {
         clean,
         dirty,
         continue_after_game_over: Button::new(
-            translation.restart,
+            &translation.restart,
             Anchor::center(width * 0.5, height * 0.7),
             font_size,
         ),
         buy: create_buy_hero_buttons(font_size, width, height, textures, translation),
         sell: create_sell_hero_buttons(font_size, width, height, textures, translation),
         continue_playing: Button::new(
-            translation.continue_playing,
+            &translation.continue_playing,
             Anchor::center(width * 0.5, height * 0.7),
             font_size,
         ),
         change_language_to_spanish: spanish,
         change_language_to_english: english,
+        history,
+        slots,
+        volume_down,
+        mute,
+        volume_up,
+        effects,
+        history_windows: history_panel::create_window_buttons(
+            font_size,
+            width,
+            height,
+            translation,
+        ),
+        new_game: new_game_panel::create_buttons(font_size, width, height, translation),
+        confirm_name: name_entry_panel::create_confirm_button(
+            font_size,
+            width,
+            height,
+            translation,
+        ),
         extra: create_extra_buttons(font_size, width, height, translation),
     }
 }
@@ -100,7 +172,7 @@ fn create_buy_hero_buttons(
     textures: &Textures,
     translation: &Translation,
 ) -> HashMap<Hero, Button> {
-    create_buy_or_sell_hero_buttons(font_size, width, height, textures, translation.buy, 0.02)
+    create_buy_or_sell_hero_buttons(font_size, width, height, textures, &translation.buy, 0.02)
 }
 
 fn create_sell_hero_buttons(
@@ -110,7 +182,7 @@ fn create_sell_hero_buttons(
     textures: &Textures,
     translation: &Translation,
 ) -> HashMap<Hero, Button> {
-    create_buy_or_sell_hero_buttons(font_size, width, height, textures, translation.sell, 0.1)
+    create_buy_or_sell_hero_buttons(font_size, width, height, textures, &translation.sell, 0.1)
 }
 
 fn create_buy_or_sell_hero_buttons(
@@ -129,7 +201,7 @@ fn create_buy_or_sell_hero_buttons(
             hero.index(),
             width,
             height,
-            textures.get(hero.texture_index()),
+            textures.get(hero.texture()),
         );
         let x_coef =
             BUY_PANEL_HORIZONTAL_PAD + extra_horizontal_offset + horizontal_offset + texture_offset;
@@ -159,7 +231,7 @@ fn create_extra_buttons(
     translation: &Translation,
 ) -> ExtraControls {
     let show_extra_controls = Button::new(
-        translation.extra_controls,
+        &translation.extra_controls,
         Anchor::bottom_left(0.0, height - BUTTON_PAD),
         font_size,
     );
@@ -177,12 +249,19 @@ fn create_extra_buttons(
     };
     let prev_rect = show_extra_controls.rect();
     let (show_debug_fps, prev_rect) = next_button("Debug FPS", prev_rect);
-    let (restart, prev_rect) = next_button(translation.restart, prev_rect);
-    let (change_arrangement, _prev_rect) = next_button(translation.change_style, prev_rect);
+    let (restart, prev_rect) = next_button(&translation.restart, prev_rect);
+    let (change_arrangement, prev_rect) = next_button(&translation.change_style, prev_rect);
+    let (stats, _prev_rect) = next_button(&translation.stats.title, prev_rect);
+    #[cfg(not(target_family = "wasm"))]
+    let export = Some(next_button(&translation.export, _prev_rect).0);
+    #[cfg(target_family = "wasm")]
+    let export = None;
     ExtraControls {
         show_extra_controls,
         show_debug_fps,
         restart,
         change_arrangement,
+        stats,
+        export,
     }
 }
@@ -0,0 +1,248 @@
+//! The hired heroes and villains, walking around the centre of the screen and stopping to clean
+//! or dirty. Heroes stay on the clean half and villains on the dirty half. Only a few of each kind
+//! are shown, so that hiring hundreds doesn't fill the screen; the rest are counted in a label.
+
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::external::backends::Seconds;
+use crate::external::texture_drawer::{
+    with_alpha, CHARACTER_ASPECT_RATIO, CLEAN_COLOR, DIRTY_COLOR,
+};
+use crate::screen::number_format::format_number;
+use crate::screen::textures::Textures;
+use crate::screen::translations::Language;
+use crate::world::heores::Hero;
+
+/// Hiring more than this doesn't show more characters of that kind.
+const MAX_SHOWN_PER_HERO: usize = 4;
+/// In widths of the area per second.
+const WALK_SPEED: f32 = 0.12;
+/// How far from its home each character wanders, as a fraction of the area.
+const WANDER_RADIUS: f32 = 0.12;
+/// How long a character works before walking again.
+const MIN_WORK_TIME: f32 = 1.0;
+const MAX_WORK_TIME: f32 = 3.0;
+/// As a fraction of the height of the area.
+const CHARACTER_HEIGHT: f32 = 0.3;
+/// Keeps the characters away from the sides of their half of the area.
+const SIDE_MARGIN: f32 = 0.06;
+/// A frame longer than this, e.g. after the window was hidden, doesn't make characters jump.
+const MAX_STEP: f32 = 0.1;
+
+struct Walker {
+    hero: Hero,
+    /// Of its feet, as fractions of the area.
+    position: Vec2,
+    target: Vec2,
+    /// Seconds of work left. Walks towards `target` when it's 0.
+    working: f32,
+    facing_left: bool,
+    /// So that characters don't bob and wobble in sync.
+    phase: f32,
+}
+
+#[derive(Default)]
+pub struct Crowd {
+    walkers: Vec<Walker>,
+    last_update: Option<Seconds>,
+}
+
+impl Crowd {
+    /// Adds or removes characters to match how many of each kind are hired.
+    pub fn sync(&mut self, heroes_count: &HashMap<Hero, i64>) {
+        for hero in Hero::list() {
+            let count = heroes_count.get(hero).copied().unwrap_or(0);
+            let shown = count.clamp(0, MAX_SHOWN_PER_HERO as i64) as usize;
+            let mut kept = 0;
+            self.walkers.retain(|walker| {
+                if walker.hero == *hero {
+                    kept += 1;
+                    kept <= shown
+                } else {
+                    true
+                }
+            });
+            for _ in kept..shown {
+                let position = wander_target(*hero);
+                self.walkers.push(Walker {
+                    hero: *hero,
+                    position,
+                    target: position,
+                    working: rand::gen_range(0.0, MAX_WORK_TIME),
+                    facing_left: !hero.is_hero(),
+                    phase: rand::gen_range(0.0, std::f32::consts::TAU),
+                });
+            }
+        }
+    }
+
+    /// Moves the characters to where they are at `now`.
+    pub fn update(&mut self, now: Seconds) {
+        let step = match self.last_update {
+            Some(last_update) => ((now - last_update) as f32).clamp(0.0, MAX_STEP),
+            None => 0.0,
+        };
+        self.last_update = Some(now);
+        for walker in &mut self.walkers {
+            if walker.working > 0.0 {
+                walker.working -= step;
+                if walker.working <= 0.0 {
+                    walker.working = 0.0;
+                    walker.target = wander_target(walker.hero);
+                }
+                continue;
+            }
+            let to_target = walker.target - walker.position;
+            let distance = to_target.length();
+            let advance = WALK_SPEED * step;
+            if distance <= advance {
+                walker.position = walker.target;
+                walker.working = rand::gen_range(MIN_WORK_TIME, MAX_WORK_TIME);
+            } else {
+                walker.position += to_target / distance * advance;
+                walker.facing_left = to_target.x < 0.0;
+            }
+        }
+    }
+
+    /// Draws the characters inside `area`, the ones in front last. Kinds with more hired than
+    /// shown get a label with how many there are.
+    pub fn draw(
+        &self,
+        area: Rect,
+        textures: &Textures,
+        font_size: f32,
+        heroes_count: &HashMap<Hero, i64>,
+        language: Language,
+        now: Seconds,
+    ) {
+        let height = area.h * CHARACTER_HEIGHT;
+        let size = vec2(height * CHARACTER_ASPECT_RATIO, height);
+        let mut walkers = self.walkers.iter().collect::<Vec<_>>();
+        walkers.sort_by(|a, b| a.position.y.total_cmp(&b.position.y));
+        let now = now as f32;
+        for walker in walkers {
+            let feet = area.point() + walker.position * area.size();
+            let (bob, rotation) = if walker.working > 0.0 {
+                (0.0, (now * 8.0 + walker.phase).sin() * 0.15)
+            } else {
+                ((now * 10.0 + walker.phase).sin().abs() * height * 0.05, 0.0)
+            };
+            if walker.working > 0.0 {
+                let color = if walker.hero.is_hero() {
+                    CLEAN_COLOR
+                } else {
+                    DIRTY_COLOR
+                };
+                let pulse = 0.5 + 0.5 * (now * 6.0 + walker.phase).sin();
+                let radius = size.x * 0.3;
+                draw_circle(feet.x, feet.y, radius, with_alpha(color, 0.3 * pulse));
+            }
+            textures.get(walker.hero.texture()).draw_ex(
+                feet.x - size.x * 0.5,
+                feet.y - size.y - bob,
+                WHITE,
+                size,
+                rotation,
+                walker.facing_left,
+            );
+        }
+        for hero in Hero::list() {
+            let count = heroes_count.get(hero).copied().unwrap_or(0);
+            if count <= MAX_SHOWN_PER_HERO as i64 {
+                continue;
+            }
+            let Some(first) = self.walkers.iter().find(|walker| walker.hero == *hero) else {
+                continue;
+            };
+            let text = format!("×{}", format_number(count, language));
+            let feet = area.point() + first.position * area.size();
+            let dimensions = measure_text(&text, None, font_size as u16, 1.0);
+            let x = (feet.x - dimensions.width * 0.5).round();
+            let y = (feet.y - size.y - font_size * 0.5).round();
+            draw_text(&text, x, y, font_size, BLACK);
+        }
+    }
+}
+
+/// A random place near the home of `hero`, inside its half of the area.
+fn wander_target(hero: Hero) -> Vec2 {
+    let home = home(hero);
+    let offset = vec2(
+        rand::gen_range(-WANDER_RADIUS, WANDER_RADIUS),
+        rand::gen_range(-WANDER_RADIUS, WANDER_RADIUS),
+    );
+    let (min_x, max_x) = if hero.is_hero() {
+        (SIDE_MARGIN, 0.5 - SIDE_MARGIN)
+    } else {
+        (0.5 + SIDE_MARGIN, 1.0 - SIDE_MARGIN)
+    };
+    let position = home + offset;
+    vec2(
+        position.x.clamp(min_x, max_x),
+        position.y.clamp(CHARACTER_HEIGHT, 1.0),
+    )
+}
+
+/// Heroes on the left and villains on the right, the stronger ones closer to the front, like in
+/// the buy panels.
+fn home(hero: Hero) -> Vec2 {
+    let x = if hero.is_hero() { 0.25 } else { 0.75 };
+    let y = 0.4 + (hero.index() / 2) as f32 * 0.27;
+    vec2(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(crowd: &Crowd, hero: Hero) -> usize {
+        crowd.walkers.iter().filter(|w| w.hero == hero).count()
+    }
+
+    #[test]
+    fn test_sync_caps_each_kind() {
+        let mut crowd = Crowd::default();
+        let mut heroes_count = HashMap::from([(Hero::Hero1, 2), (Hero::Villain2, 1000)]);
+        crowd.sync(&heroes_count);
+        assert_eq!(shown(&crowd, Hero::Hero1), 2);
+        assert_eq!(shown(&crowd, Hero::Villain2), MAX_SHOWN_PER_HERO);
+        assert_eq!(shown(&crowd, Hero::Hero3), 0);
+
+        heroes_count.insert(Hero::Hero1, 1);
+        heroes_count.insert(Hero::Villain2, 999);
+        crowd.sync(&heroes_count);
+        assert_eq!(shown(&crowd, Hero::Hero1), 1);
+        assert_eq!(shown(&crowd, Hero::Villain2), MAX_SHOWN_PER_HERO);
+
+        crowd.sync(&HashMap::new());
+        assert!(crowd.walkers.is_empty());
+    }
+
+    #[test]
+    fn test_walkers_stay_in_their_half() {
+        let mut crowd = Crowd::default();
+        let heroes_count = Hero::list().iter().map(|hero| (*hero, 10)).collect();
+        crowd.sync(&heroes_count);
+        let mut walked = false;
+        for i in 0..600 {
+            crowd.update(i as f64 * 0.05);
+            for walker in &crowd.walkers {
+                // walking between two places inside the bounds may round a bit outside
+                let inside =
+                    |value: f32, min: f32, max: f32| value >= min - 1e-4 && value <= max + 1e-4;
+                let Vec2 { x, y } = walker.position;
+                if walker.hero.is_hero() {
+                    assert!(inside(x, SIDE_MARGIN, 0.5 - SIDE_MARGIN), "{}", x);
+                } else {
+                    assert!(inside(x, 0.5 + SIDE_MARGIN, 1.0 - SIDE_MARGIN), "{}", x);
+                }
+                assert!(inside(y, CHARACTER_HEIGHT, 1.0), "{}", y);
+                walked |= walker.position != walker.target;
+            }
+        }
+        assert!(walked);
+    }
+}
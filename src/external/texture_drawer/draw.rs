@@ -1,10 +1,11 @@
 use macroquad::prelude::{
-    draw_line, draw_texture_ex, is_mouse_button_down, is_mouse_button_released, mouse_position,
-    Color, DrawTextureParams, MouseButton, Rect, Texture2D, DARKGRAY, WHITE,
+    draw_line, is_mouse_button_down, is_mouse_button_released, mouse_position, Color, MouseButton,
+    Rect, DARKGRAY, WHITE,
 };
 
 use crate::external::backends::Vec2;
 use crate::external::widgets::button::Interaction;
+use crate::screen::textures::Sprite;
 
 /// Returns if the texture was clicked this frame.
 ///
@@ -12,8 +13,8 @@ use crate::external::widgets::button::Interaction;
 /// The rect is in pixels.
 pub fn is_texture_clicked(
     rect_pixels: Rect,
-    texture: Texture2D,
-    texture_highlighted: Option<Texture2D>,
+    texture: Sprite,
+    texture_highlighted: Option<Sprite>,
 ) -> bool {
     let hovered = rect_pixels.contains(Vec2::from(mouse_position()));
     let clicking = rect_pixels.contains(Vec2::from(mouse_position()))
@@ -25,16 +26,7 @@ pub fn is_texture_clicked(
             chosen_texture = tp
         }
     }
-    draw_texture_ex(
-        chosen_texture,
-        rect_pixels.x,
-        rect_pixels.y,
-        WHITE,
-        DrawTextureParams {
-            dest_size: Some(rect_pixels.size()),
-            ..Default::default()
-        },
-    );
+    chosen_texture.draw(rect_pixels.x, rect_pixels.y, WHITE, rect_pixels.size());
     return rect_pixels.contains(Vec2::from(mouse_position()))
         && is_mouse_button_released(MouseButton::Left);
 }
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::external::texture_drawer::{CLEAN_COLOR, DIRTY_COLOR};
+use crate::external::widgets::anchor::Anchor;
+use crate::external::widgets::button::Button;
+use crate::external::widgets::chart::{Chart, Series};
+use crate::screen::number_format::{format_money, format_number};
+use crate::screen::translations::Translation;
+use crate::world::history::{HistoryWindow, Sample};
+use crate::world::World;
+
+const HISTORY_PANEL_WIDTH: f32 = 0.6;
+const HISTORY_PANEL_TOP: f32 = 0.2;
+const HISTORY_PANEL_HEIGHT: f32 = 0.6;
+const MONEY_COLOR: Color = DARKGREEN;
+
+pub fn panel_rect(width: f32, height: f32) -> Rect {
+    Rect::new(
+        (width * (1.0 - HISTORY_PANEL_WIDTH) * 0.5).round(),
+        (height * HISTORY_PANEL_TOP).round(),
+        (width * HISTORY_PANEL_WIDTH).round(),
+        (height * HISTORY_PANEL_HEIGHT).round(),
+    )
+}
+
+/// One button per time window, in a row at the top of the panel.
+pub fn create_window_buttons(
+    font_size: f32,
+    width: f32,
+    height: f32,
+    translation: &Translation,
+) -> HashMap<HistoryWindow, Button> {
+    let panel = panel_rect(width, height);
+    let mut x = panel.x + font_size * 0.5;
+    let mut buttons = HashMap::new();
+    for window in HistoryWindow::list() {
+        let button = Button::new(
+            translation.history.window(*window),
+            Anchor::top_left(x, panel.y + font_size * 0.5),
+            font_size,
+        );
+        x += button.rect().w + font_size * 0.5;
+        buttons.insert(*window, button);
+    }
+    buttons
+}
+
+/// Draws the panel with money, dirtiness and both speeds, in the chosen window of time.
+pub fn draw_history_panel(
+    world: &World,
+    window: HistoryWindow,
+    buttons: &mut HashMap<HistoryWindow, Button>,
+    width: f32,
+    height: f32,
+    font_size: f32,
+    translation: &Translation,
+) {
+    let panel = panel_rect(width, height);
+    draw_rectangle(
+        panel.x,
+        panel.y,
+        panel.w,
+        panel.h,
+        Color::new(0.7, 0.7, 0.7, 1.00),
+    );
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, BLACK);
+    for (button_window, button) in buttons.iter_mut() {
+        let color = if *button_window == window {
+            LIGHTGRAY
+        } else {
+            GRAY
+        };
+        button.set_color(color).render();
+    }
+
+    let samples = world.history().samples(window);
+    let series = |value: fn(&Sample) -> i64, color| Series {
+        points: samples
+            .iter()
+            .map(|sample| (sample.time, value(sample) as f64))
+            .collect(),
+        color,
+    };
+    let language = translation.language;
+    let money_label = |cents: f64| format_money(cents as i64, language);
+    let units_label = |cents: f64| format_number(cents as i64 / 100, language);
+    let speed_label = |speed: f64| format_number(speed as i64, language);
+    let charts = [
+        Chart {
+            title: &translation.history.money,
+            series: vec![series(|s| s.money, MONEY_COLOR)],
+            y_label: &money_label,
+        },
+        Chart {
+            title: &translation.history.dirtiness,
+            series: vec![series(|s| s.dirtiness, DIRTY_COLOR)],
+            y_label: &units_label,
+        },
+        Chart {
+            title: &translation.history.speeds,
+            series: vec![
+                series(|s| s.cleaning_speed, CLEAN_COLOR),
+                series(|s| s.dirtying_speed, DIRTY_COLOR),
+            ],
+            y_label: &speed_label,
+        },
+    ];
+
+    let pad = font_size * 0.5;
+    let top = panel.y + font_size * 2.5;
+    let chart_height = (panel.y + panel.h - top) / charts.len() as f32 - pad;
+    for (i, chart) in charts.iter().enumerate() {
+        let rect = Rect::new(
+            panel.x + pad,
+            (top + (chart_height + pad) * i as f32).round(),
+            panel.w - pad * 2.0,
+            chart_height.round(),
+        );
+        chart.render(rect, font_size);
+    }
+}
@@ -0,0 +1,171 @@
+//! With the `hot-reload` feature, the files of the asset manifest and the translation files are
+//! polled for changes while the game runs, and the drawer swaps in the new versions. The `World`
+//! is not touched, so artists and translators can see their changes in the run they are playing.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use macroquad::prelude::{info, warn, FilterMode, Image, Texture2D};
+
+use crate::external::asset_manifest::AssetManifest;
+use crate::external::backends::Seconds;
+use crate::external::texture_drawer::TextureDrawer;
+use crate::external::texture_loader::decode_image;
+use crate::screen::textures::Sprite;
+use crate::screen::translations::{Language, Translations};
+
+/// Checking the modification time of every file each frame would be wasteful.
+const POLL_PERIOD: Seconds = 0.5;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Asset {
+    Texture { id: String, filter: FilterMode },
+    Translation(Language),
+}
+
+struct WatchedFile {
+    path: PathBuf,
+    asset: Asset,
+    /// None if the file couldn't be read, so that it's reloaded when it appears.
+    modified: Option<SystemTime>,
+}
+
+pub struct HotReload {
+    files: Vec<WatchedFile>,
+    last_poll: Seconds,
+}
+
+impl HotReload {
+    pub fn new(manifest: &AssetManifest) -> Self {
+        let textures = manifest.textures.iter().map(|entry| {
+            let texture = Asset::Texture {
+                id: entry.id.clone(),
+                filter: entry.filter,
+            };
+            (PathBuf::from(&entry.path), texture)
+        });
+        let translations = Language::list().iter().map(|language| {
+            let path = PathBuf::from(language.translation_path());
+            (path, Asset::Translation(*language))
+        });
+        let files = textures
+            .chain(translations)
+            .map(|(path, asset)| WatchedFile {
+                modified: modified(&path),
+                path,
+                asset,
+            })
+            .collect();
+        Self {
+            files,
+            last_poll: 0.0,
+        }
+    }
+
+    /// The files that changed since the last time they were checked.
+    fn poll(&mut self, now: Seconds) -> Vec<(PathBuf, Asset)> {
+        if now - self.last_poll < POLL_PERIOD {
+            return Vec::new();
+        }
+        self.last_poll = now;
+        let mut changed = Vec::new();
+        for file in &mut self.files {
+            let modified = modified(&file.path);
+            if modified.is_some() && modified != file.modified {
+                changed.push((file.path.clone(), file.asset.clone()));
+            }
+            file.modified = modified;
+        }
+        changed
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The file may be half written, so it must not panic.
+fn read_image(path: &PathBuf) -> Result<Image, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    decode_image(&bytes)
+}
+
+impl TextureDrawer {
+    /// Starts watching the files of `manifest` and the translation files.
+    pub fn watch_assets(&mut self, manifest: &AssetManifest) {
+        self.hot_reload = Some(HotReload::new(manifest));
+    }
+
+    pub(super) fn reload_changed_assets(&mut self, now: Seconds) {
+        let Some(hot_reload) = &mut self.hot_reload else {
+            return;
+        };
+        let mut sizes_changed = false;
+        let mut translations_changed = false;
+        for (path, asset) in hot_reload.poll(now) {
+            info!("reloading {}", path.display());
+            match asset {
+                Asset::Texture { id, filter } => match read_image(&path) {
+                    Ok(image) => sizes_changed |= self.swap_texture(&id, &image, filter),
+                    Err(e) => warn!("can't reload {}: {}", path.display(), e),
+                },
+                Asset::Translation(_) => translations_changed = true,
+            }
+        }
+        if translations_changed {
+            let mut loaded_files = Vec::new();
+            for language in Language::list() {
+                match std::fs::read_to_string(language.translation_path()) {
+                    Ok(text) => loaded_files.push((*language, text)),
+                    Err(e) => warn!("using compiled-in translation for {:?}: {}", language, e),
+                }
+            }
+            self.translations = Translations::new(loaded_files);
+            self.translation = self.translations.get(self.language);
+        }
+        if sizes_changed || translations_changed {
+            self.recreate_buttons();
+        }
+    }
+
+    /// The new image gets a texture of its own instead of going into the atlas. Returns whether
+    /// the size changed, in which case the buttons placed around it have to be created again.
+    fn swap_texture(&mut self, id: &str, image: &Image, filter: FilterMode) -> bool {
+        let texture = Texture2D::from_image(image);
+        texture.set_filter(filter);
+        let sprite = Sprite::whole(texture);
+        let previous = self.textures.get_by_id(id);
+        self.textures.insert(id, sprite);
+        previous.is_none_or(|previous| {
+            previous.width() != sprite.width() || previous.height() != sprite.height()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_detects_changes() {
+        let dir = std::env::temp_dir().join(format!("hot_reload_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hero.png");
+        std::fs::write(&path, "old").unwrap();
+        let manifest =
+            AssetManifest::parse(&format!("texture.hero.path = {}", path.display())).unwrap();
+        let mut hot_reload = HotReload::new(&manifest);
+        assert_eq!(hot_reload.poll(1.0), Vec::new());
+
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(hot_reload.poll(1.1), Vec::new(), "polled too soon");
+        let changed = hot_reload.poll(2.0);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].0, path);
+        assert!(matches!(&changed[0].1, Asset::Texture { id, .. } if id == "hero"));
+        assert_eq!(hot_reload.poll(3.0), Vec::new());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
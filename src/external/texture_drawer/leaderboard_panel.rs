@@ -0,0 +1,123 @@
+use macroquad::prelude::*;
+
+use crate::screen::number_format::{format_duration, format_money, format_number};
+use crate::screen::translations::Translation;
+use crate::world::high_scores::{Category, Score};
+use crate::world::World;
+
+/// Fraction of the screen width.
+const LEADERBOARD_PANEL_WIDTH: f32 = 0.4;
+/// Fraction of the panel width where the label column ends.
+const LEADERBOARD_LABEL_COLUMN_WIDTH: f32 = 0.45;
+const LEADERBOARD_LINE_HEIGHT: f32 = 1.3;
+/// The panel ends this many font sizes above `bottom`.
+const LEADERBOARD_MARGIN: f32 = 0.5;
+const NEW_RECORD_COLOR: Color = Color::new(0.95, 0.85, 0.4, 1.0);
+
+/// The records of the mode and difficulty of the run, above `bottom`, highlighting the ones that
+/// this run beat.
+pub fn draw_leaderboard_panel(
+    world: &World,
+    width: f32,
+    bottom: f32,
+    font_size: f32,
+    translation: &Translation,
+) {
+    let messages = &translation.leaderboard;
+    let config = world.config();
+    let mode = translation.new_game.mode(config.mode);
+    let difficulty = translation.new_game.difficulty(config.difficulty);
+    let title = messages
+        .title
+        .format(&[("mode", &mode), ("difficulty", &difficulty)]);
+
+    let pad = font_size;
+    let line_height = (font_size * LEADERBOARD_LINE_HEIGHT).round();
+    let categories = Category::list()
+        .iter()
+        .filter(|category| category.applies_to(config.mode))
+        .collect::<Vec<_>>();
+    let rows = 1 + categories.len();
+    let panel_width = (width * LEADERBOARD_PANEL_WIDTH).round();
+    let panel_height = line_height * rows as f32 + pad * 2.0;
+    let panel = Rect::new(
+        ((width - panel_width) * 0.5).round(),
+        (bottom - font_size * LEADERBOARD_MARGIN - panel_height)
+            .max(0.0)
+            .round(),
+        panel_width,
+        panel_height,
+    );
+    draw_rectangle(
+        panel.x,
+        panel.y,
+        panel.w,
+        panel.h,
+        Color::new(0.7, 0.7, 0.7, 1.00),
+    );
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, BLACK);
+
+    let label_x = panel.x + pad;
+    let right = panel.x + panel.w - pad;
+    let row_y = |row: usize| (panel.y + pad + line_height * row as f32).round();
+    draw_text(&title, label_x, row_y(0) + font_size, font_size, BLACK);
+    for (i, category) in categories.into_iter().enumerate() {
+        let y = row_y(i + 1);
+        if world.new_records().contains(category) {
+            draw_rectangle(
+                panel.x + 2.0,
+                y,
+                panel.w - 4.0,
+                line_height,
+                NEW_RECORD_COLOR,
+            );
+        }
+        let text_y = y + font_size;
+        draw_text(
+            messages.category(*category),
+            label_x,
+            text_y,
+            font_size,
+            BLACK,
+        );
+        let record = world
+            .high_scores()
+            .record(config.mode, config.difficulty, *category);
+        let value = match record {
+            Some(record) => {
+                let name = if record.name.is_empty() {
+                    &messages.anonymous
+                } else {
+                    &record.name
+                };
+                format!("{}  {}", format_score(record.score, translation), name)
+            }
+            None => "-".to_string(),
+        };
+        let value_width = measure_text(&value, None, font_size as u16, 1.0).width;
+        draw_text(
+            &value,
+            (right - value_width).round(),
+            text_y,
+            font_size,
+            BLACK,
+        );
+    }
+}
+
+pub fn format_score(score: Score, translation: &Translation) -> String {
+    match score {
+        Score::Time(time) => format_duration(time),
+        Score::Money(money) => format_money(money, translation.language),
+        Score::Clicks(clicks) => format_number(clicks, translation.language),
+    }
+}
+
+/// Labels have to fit in the first column, and the title in the whole panel.
+pub fn label_max_width(width: f32, font_size: f32) -> f32 {
+    width * LEADERBOARD_PANEL_WIDTH * LEADERBOARD_LABEL_COLUMN_WIDTH - font_size
+}
+
+pub fn title_max_width(width: f32, font_size: f32) -> f32 {
+    width * LEADERBOARD_PANEL_WIDTH - font_size * 2.0
+}
@@ -0,0 +1,108 @@
+use macroquad::prelude::*;
+
+use crate::external::widgets::anchor::Anchor;
+use crate::external::widgets::button::Button;
+use crate::external::widgets::text_input::TextInput;
+use crate::screen::translations::Translation;
+use crate::world::high_scores::MAX_NAME_LENGTH;
+
+/// Fraction of the screen width.
+const NAME_ENTRY_PANEL_WIDTH: f32 = 0.4;
+/// In font sizes.
+const NAME_ENTRY_LINE_HEIGHT: f32 = 2.2;
+/// Title, hint, text input and confirm button.
+const NAME_ENTRY_ROWS: usize = 4;
+
+/// The name being typed while the name-entry screen is open.
+pub struct NameEntry {
+    pub input: TextInput,
+}
+
+impl NameEntry {
+    pub fn new(name: &str) -> Self {
+        let mut input = TextInput::new(name, MAX_NAME_LENGTH);
+        input.set_focused(true);
+        input.select_all();
+        Self { input }
+    }
+}
+
+fn panel_rect(width: f32, height: f32, font_size: f32) -> Rect {
+    let pad = font_size;
+    let panel_width = (width * NAME_ENTRY_PANEL_WIDTH).round();
+    let panel_height = font_size * NAME_ENTRY_LINE_HEIGHT * NAME_ENTRY_ROWS as f32 + pad * 2.0;
+    Rect::new(
+        ((width - panel_width) * 0.5).round(),
+        ((height - panel_height) * 0.5).max(0.0).round(),
+        panel_width,
+        panel_height.round(),
+    )
+}
+
+fn row_y(panel: Rect, font_size: f32, row: usize) -> f32 {
+    (panel.y + font_size + font_size * NAME_ENTRY_LINE_HEIGHT * row as f32).round()
+}
+
+/// Where the text input goes, which the caller keeps up to date because it outlives the buttons.
+pub fn input_rect(width: f32, height: f32, font_size: f32) -> Rect {
+    let panel = panel_rect(width, height, font_size);
+    let pad = font_size;
+    Rect::new(
+        panel.x + pad,
+        row_y(panel, font_size, 2),
+        panel.w - pad * 2.0,
+        (font_size * 1.8).round(),
+    )
+}
+
+pub fn create_confirm_button(
+    font_size: f32,
+    width: f32,
+    height: f32,
+    translation: &Translation,
+) -> Button {
+    let panel = panel_rect(width, height, font_size);
+    Button::new(
+        &translation.name_entry.confirm,
+        Anchor::top_left(panel.x + font_size, row_y(panel, font_size, 3)),
+        font_size,
+    )
+}
+
+pub fn draw_name_entry_panel(
+    name_entry: &NameEntry,
+    confirm: &Button,
+    width: f32,
+    height: f32,
+    font_size: f32,
+    translation: &Translation,
+) {
+    let panel = panel_rect(width, height, font_size);
+    draw_rectangle(
+        panel.x,
+        panel.y,
+        panel.w,
+        panel.h,
+        Color::new(0.7, 0.7, 0.7, 1.00),
+    );
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, BLACK);
+
+    let pad = font_size;
+    let messages = &translation.name_entry;
+    let text_y = |row| row_y(panel, font_size, row) + font_size;
+    draw_text(
+        &messages.title,
+        panel.x + pad,
+        text_y(0),
+        font_size * 1.25,
+        BLACK,
+    );
+    draw_text(&messages.hint, panel.x + pad, text_y(1), font_size, BLACK);
+    name_entry.input.render();
+    confirm.render();
+}
+
+/// Texts go in a single line across the panel.
+pub fn text_max_width(width: f32, font_size: f32) -> f32 {
+    width * NAME_ENTRY_PANEL_WIDTH - font_size * 2.0
+}
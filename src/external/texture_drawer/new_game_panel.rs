@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::external::widgets::anchor::Anchor;
+use crate::external::widgets::button::Button;
+use crate::screen::number_format::{format_money, format_number};
+use crate::screen::translations::{Language, Translation};
+use crate::world::config::{ConfigField, Difficulty, GameMode, WorldConfig};
+use crate::world::to_cents;
+
+/// Fraction of the screen width.
+const NEW_GAME_PANEL_WIDTH: f32 = 0.5;
+/// Fraction of the panel width where the labels of the config end.
+const NEW_GAME_LABEL_COLUMN_WIDTH: f32 = 0.55;
+/// In font sizes.
+const NEW_GAME_LINE_HEIGHT: f32 = 2.2;
+/// Title, modes, difficulties, one row per field, and start and cancel.
+const NEW_GAME_ROWS: usize = 4 + ConfigField::list().len();
+const FIRST_FIELD_ROW: usize = 3;
+const BUTTON_PAD: f32 = 2.0;
+
+/// What the player is choosing while the new-game screen is open.
+pub struct NewGame {
+    pub config: WorldConfig,
+    /// False on the first launch, when there is no game to go back to.
+    pub can_cancel: bool,
+}
+
+pub struct NewGameButtons {
+    pub modes: HashMap<GameMode, Button>,
+    pub difficulties: HashMap<Difficulty, Button>,
+    /// The buttons that halve (false) and double (true) each field.
+    pub steps: HashMap<(ConfigField, bool), Button>,
+    pub start: Button,
+    pub cancel: Button,
+}
+
+fn panel_rect(width: f32, height: f32, font_size: f32) -> Rect {
+    let pad = font_size;
+    let panel_width = (width * NEW_GAME_PANEL_WIDTH).round();
+    let panel_height = font_size * NEW_GAME_LINE_HEIGHT * NEW_GAME_ROWS as f32 + pad * 2.0;
+    Rect::new(
+        ((width - panel_width) * 0.5).round(),
+        ((height - panel_height) * 0.5).max(0.0).round(),
+        panel_width,
+        panel_height.round(),
+    )
+}
+
+fn row_y(panel: Rect, font_size: f32, row: usize) -> f32 {
+    (panel.y + font_size + font_size * NEW_GAME_LINE_HEIGHT * row as f32).round()
+}
+
+pub fn create_buttons(
+    font_size: f32,
+    width: f32,
+    height: f32,
+    translation: &Translation,
+) -> NewGameButtons {
+    let panel = panel_rect(width, height, font_size);
+    let pad = font_size;
+
+    let row_of_choices = |texts: Vec<&str>, row| {
+        let mut x = panel.x + pad;
+        let mut buttons = Vec::new();
+        for text in texts {
+            let button = Button::new(
+                text,
+                Anchor::top_left(x, row_y(panel, font_size, row)),
+                font_size,
+            );
+            x += button.rect().w + BUTTON_PAD;
+            buttons.push(button);
+        }
+        buttons
+    };
+    let modes = GameMode::list().iter().copied().zip(row_of_choices(
+        GameMode::list()
+            .iter()
+            .map(|mode| translation.new_game.mode(*mode))
+            .collect(),
+        1,
+    ));
+    let difficulties = Difficulty::list().iter().copied().zip(row_of_choices(
+        Difficulty::list()
+            .iter()
+            .map(|difficulty| translation.new_game.difficulty(*difficulty))
+            .collect(),
+        2,
+    ));
+
+    let mut steps = HashMap::new();
+    for (i, field) in ConfigField::list().iter().enumerate() {
+        let y = row_y(panel, font_size, FIRST_FIELD_ROW + i);
+        let double = Button::new("x2", Anchor::top_right(panel.right() - pad, y), font_size);
+        let halve = Button::new(
+            "/2",
+            Anchor::top_right(double.rect().x - BUTTON_PAD, y),
+            font_size,
+        );
+        steps.insert((*field, true), double);
+        steps.insert((*field, false), halve);
+    }
+
+    let last_row = row_y(panel, font_size, NEW_GAME_ROWS - 1);
+    let start = Button::new(
+        &translation.new_game.start,
+        Anchor::top_left(panel.x + pad, last_row),
+        font_size,
+    );
+    let cancel = Button::new(
+        &translation.new_game.cancel,
+        Anchor::top_left(start.rect().right() + BUTTON_PAD, last_row),
+        font_size,
+    );
+    NewGameButtons {
+        modes: modes.collect(),
+        difficulties: difficulties.collect(),
+        steps,
+        start,
+        cancel,
+    }
+}
+
+/// The modes, the difficulties, the value of each field with buttons to change it, and start and cancel.
+pub fn draw_new_game_panel(
+    new_game: &NewGame,
+    buttons: &mut NewGameButtons,
+    width: f32,
+    height: f32,
+    font_size: f32,
+    translation: &Translation,
+) {
+    let panel = panel_rect(width, height, font_size);
+    draw_rectangle(
+        panel.x,
+        panel.y,
+        panel.w,
+        panel.h,
+        Color::new(0.7, 0.7, 0.7, 1.00),
+    );
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, BLACK);
+
+    let pad = font_size;
+    let text_y = |row| row_y(panel, font_size, row) + font_size;
+    draw_text(
+        &translation.new_game.title,
+        panel.x + pad,
+        text_y(0),
+        font_size * 1.25,
+        BLACK,
+    );
+    let selected_color = |selected| if selected { LIGHTGRAY } else { GRAY };
+    for (mode, button) in buttons.modes.iter_mut() {
+        let selected = *mode == new_game.config.mode;
+        button.set_color(selected_color(selected)).render();
+    }
+    for (difficulty, button) in buttons.difficulties.iter_mut() {
+        let selected = *difficulty == new_game.config.difficulty;
+        button.set_color(selected_color(selected)).render();
+    }
+    for (i, field) in ConfigField::list().iter().enumerate() {
+        let y = text_y(FIRST_FIELD_ROW + i);
+        draw_text(
+            translation.new_game.field(*field),
+            panel.x + pad,
+            y,
+            font_size,
+            BLACK,
+        );
+        let value = format_field(&new_game.config, *field, translation.language);
+        let value_right = buttons.steps[&(*field, false)].rect().x - pad;
+        let value_width = measure_text(&value, None, font_size as u16, 1.0).width;
+        draw_text(
+            &value,
+            (value_right - value_width).round(),
+            y,
+            font_size,
+            BLACK,
+        );
+    }
+    for button in buttons.steps.values() {
+        button.render();
+    }
+    buttons.start.render();
+    if new_game.can_cancel {
+        buttons.cancel.render();
+    }
+}
+
+fn format_field(config: &WorldConfig, field: ConfigField, language: Language) -> String {
+    let value = config.get(field);
+    match field {
+        ConfigField::MaxDirtiness => format_number(value, language),
+        ConfigField::TargetSavings => format_money(to_cents(value), language),
+        ConfigField::DirtyingPerClick => format_number(value / 100, language),
+        ConfigField::CleaningPerClick => format_money(value, language),
+        ConfigField::CapacityGrowthPercent => format!("{}%", format_number(value, language)),
+        ConfigField::CapacityGrowthSqrt => format!("x{}", format_number(value, language)),
+    }
+}
+
+/// Labels of the fields have to fit before their values.
+pub fn label_max_width(width: f32, font_size: f32) -> f32 {
+    width * NEW_GAME_PANEL_WIDTH * NEW_GAME_LABEL_COLUMN_WIDTH - font_size
+}
+
+/// The buttons of the modes and of the difficulties share a row each, with a padding of one font
+/// size at each side of the text.
+pub fn choice_max_width(width: f32, font_size: f32) -> f32 {
+    let choices = Difficulty::list().len().max(GameMode::list().len());
+    (width * NEW_GAME_PANEL_WIDTH - font_size * 2.0) / choices as f32 - font_size * 2.0 - BUTTON_PAD
+}
@@ -0,0 +1,273 @@
+//! Short-lived decorations that react to what the player does: bubbles when cleaning, splats when
+//! dirtying, the money earned floating up, and coins when hiring. They live in a pool of fixed
+//! size, so that clicking fast doesn't allocate nor slow down the frame.
+
+use macroquad::prelude::*;
+
+use crate::external::backends::Seconds;
+use crate::external::texture_drawer::{with_alpha, CLEAN_COLOR, DIRTY_COLOR};
+
+/// More than this are not spawned until some disappear.
+const MAX_PARTICLES: usize = 256;
+/// A frame longer than this, e.g. after the window was hidden, doesn't make particles jump.
+const MAX_STEP: f32 = 0.1;
+
+const BUBBLES_PER_CLICK: usize = 6;
+const SPLATS_PER_CLICK: usize = 5;
+const COINS_PER_PURCHASE: usize = 12;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Kind {
+    Bubble,
+    Splat,
+    Coin,
+    Text,
+}
+
+impl Kind {
+    /// Downwards acceleration, in screen heights per second squared. Bubbles float up.
+    fn gravity(&self) -> f32 {
+        match self {
+            Kind::Bubble => -0.3,
+            Kind::Splat => 1.5,
+            Kind::Coin => 2.5,
+            Kind::Text => 0.0,
+        }
+    }
+}
+
+struct Particle {
+    kind: Kind,
+    alive: bool,
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    /// Radius in pixels, or the font size for texts.
+    size: f32,
+    /// Only for texts. Reused along with the particle, so it keeps its allocation.
+    text: String,
+}
+
+pub struct Particles {
+    pool: Vec<Particle>,
+    /// Indexes of the dead particles of `pool`, to reuse them.
+    free: Vec<usize>,
+    enabled: bool,
+    last_update: Option<Seconds>,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl Particles {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            pool: Vec::new(),
+            free: Vec::new(),
+            enabled,
+            last_update: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Disabling removes the particles that are flying.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.free = (0..self.pool.len()).collect();
+            for particle in &mut self.pool {
+                particle.alive = false;
+            }
+        }
+    }
+
+    /// Returns None if disabled or if there are too many particles already.
+    fn spawn(
+        &mut self,
+        kind: Kind,
+        position: Vec2,
+        velocity: Vec2,
+        lifetime: f32,
+        size: f32,
+    ) -> Option<&mut Particle> {
+        if !self.enabled {
+            return None;
+        }
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if self.pool.len() < MAX_PARTICLES => {
+                self.pool.push(Particle {
+                    kind,
+                    alive: false,
+                    position,
+                    velocity,
+                    age: 0.0,
+                    lifetime,
+                    size,
+                    text: String::new(),
+                });
+                self.pool.len() - 1
+            }
+            None => return None,
+        };
+        let particle = &mut self.pool[index];
+        particle.kind = kind;
+        particle.alive = true;
+        particle.position = position;
+        particle.velocity = velocity;
+        particle.age = 0.0;
+        particle.lifetime = lifetime;
+        particle.size = size;
+        particle.text.clear();
+        Some(particle)
+    }
+
+    /// Around `center`, for a screen `height` pixels tall.
+    pub fn bubbles(&mut self, center: Vec2, height: f32) {
+        for _ in 0..BUBBLES_PER_CLICK {
+            let offset = random_direction() * height * rand::gen_range(0.0, 0.05);
+            let velocity = vec2(rand::gen_range(-0.05, 0.05), rand::gen_range(-0.2, -0.05));
+            let size = height * rand::gen_range(0.006, 0.015);
+            let lifetime = rand::gen_range(0.6, 1.2);
+            self.spawn(
+                Kind::Bubble,
+                center + offset,
+                velocity * height,
+                lifetime,
+                size,
+            );
+        }
+    }
+
+    pub fn splats(&mut self, center: Vec2, height: f32) {
+        for _ in 0..SPLATS_PER_CLICK {
+            let velocity = random_direction() * rand::gen_range(0.2, 0.5) - vec2(0.0, 0.3);
+            let size = height * rand::gen_range(0.005, 0.012);
+            let lifetime = rand::gen_range(0.4, 0.8);
+            self.spawn(Kind::Splat, center, velocity * height, lifetime, size);
+        }
+    }
+
+    pub fn coins(&mut self, center: Vec2, height: f32) {
+        for _ in 0..COINS_PER_PURCHASE {
+            let velocity = vec2(rand::gen_range(-0.3, 0.3), rand::gen_range(-0.9, -0.4));
+            let size = height * 0.008;
+            let lifetime = rand::gen_range(0.7, 1.0);
+            self.spawn(Kind::Coin, center, velocity * height, lifetime, size);
+        }
+    }
+
+    /// `text` rises from `position` and fades out.
+    pub fn floating_text(&mut self, text: &str, position: Vec2, font_size: f32, height: f32) {
+        let velocity = vec2(0.0, -0.1 * height);
+        if let Some(particle) = self.spawn(Kind::Text, position, velocity, 1.0, font_size) {
+            particle.text.push_str(text);
+        }
+    }
+
+    /// Moves the particles to where they are at `now`, for a screen `height` pixels tall.
+    pub fn update(&mut self, now: Seconds, height: f32) {
+        let step = match self.last_update {
+            Some(last_update) => ((now - last_update) as f32).clamp(0.0, MAX_STEP),
+            None => 0.0,
+        };
+        self.last_update = Some(now);
+        for (index, particle) in self.pool.iter_mut().enumerate() {
+            if !particle.alive {
+                continue;
+            }
+            particle.age += step;
+            if particle.age >= particle.lifetime {
+                particle.alive = false;
+                self.free.push(index);
+                continue;
+            }
+            particle.velocity.y += particle.kind.gravity() * height * step;
+            particle.position += particle.velocity * step;
+        }
+    }
+
+    pub fn draw(&self) {
+        for particle in self.pool.iter().filter(|particle| particle.alive) {
+            let alpha = 1.0 - particle.age / particle.lifetime;
+            let Vec2 { x, y } = particle.position;
+            let size = particle.size;
+            match particle.kind {
+                Kind::Bubble => {
+                    draw_circle(x, y, size, Color::new(1.0, 1.0, 1.0, 0.4 * alpha));
+                    draw_circle_lines(x, y, size, 1.5, with_alpha(CLEAN_COLOR, alpha));
+                }
+                Kind::Splat => draw_circle(x, y, size, with_alpha(DIRTY_COLOR, alpha)),
+                Kind::Coin => {
+                    draw_circle(x, y, size, with_alpha(GOLD, alpha));
+                    draw_circle_lines(x, y, size, 1.0, with_alpha(ORANGE, alpha));
+                }
+                Kind::Text => {
+                    let dimensions = measure_text(&particle.text, None, size as u16, 1.0);
+                    let x = (x - dimensions.width * 0.5).round();
+                    draw_text(&particle.text, x, y.round(), size, with_alpha(BLACK, alpha));
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn alive(&self) -> usize {
+        self.pool.iter().filter(|particle| particle.alive).count()
+    }
+}
+
+fn random_direction() -> Vec2 {
+    let angle = rand::gen_range(0.0, std::f32::consts::TAU);
+    vec2(angle.cos(), angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_is_reused() {
+        let mut particles = Particles::new(true);
+        particles.update(0.0, 100.0);
+        particles.bubbles(Vec2::ZERO, 100.0);
+        assert_eq!(particles.alive(), BUBBLES_PER_CLICK);
+
+        // every bubble is gone after its lifetime
+        for i in 1..=20 {
+            particles.update(i as f64 * 0.1, 100.0);
+        }
+        assert_eq!(particles.alive(), 0);
+        particles.splats(Vec2::ZERO, 100.0);
+        assert_eq!(particles.alive(), SPLATS_PER_CLICK);
+        assert_eq!(particles.pool.len(), BUBBLES_PER_CLICK);
+    }
+
+    #[test]
+    fn test_pool_has_a_limit() {
+        let mut particles = Particles::new(true);
+        for _ in 0..MAX_PARTICLES {
+            particles.coins(Vec2::ZERO, 100.0);
+        }
+        assert_eq!(particles.alive(), MAX_PARTICLES);
+        assert_eq!(particles.pool.len(), MAX_PARTICLES);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut particles = Particles::new(true);
+        particles.floating_text("+1 €", Vec2::ZERO, 16.0, 100.0);
+        assert_eq!(particles.pool[0].text, "+1 €");
+        particles.set_enabled(false);
+        assert_eq!(particles.alive(), 0);
+        particles.bubbles(Vec2::ZERO, 100.0);
+        assert_eq!(particles.alive(), 0);
+    }
+}
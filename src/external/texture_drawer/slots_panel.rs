@@ -0,0 +1,338 @@
+use macroquad::prelude::*;
+
+use crate::external::save_slots::MAX_SLOTS;
+use crate::external::saved_game::{self, SlotSummary};
+use crate::external::widgets::anchor::Anchor;
+use crate::external::widgets::button::Button;
+use crate::external::widgets::text_input::{TextInput, TextInputEvent};
+use crate::screen::number_format::{format_date, format_duration, format_money};
+use crate::screen::translations::Translation;
+use crate::screen::{SlotAction, SlotId};
+use crate::world::high_scores::MAX_NAME_LENGTH;
+
+/// Fraction of the screen width.
+const SLOTS_PANEL_WIDTH: f32 = 0.7;
+/// Fraction of the panel width where the buttons of each slot start.
+const SLOTS_TEXT_COLUMN_WIDTH: f32 = 0.5;
+const SLOTS_LINE_HEIGHT: f32 = 1.3;
+/// Name, summary and last played date.
+const LINES_PER_SLOT: usize = 3;
+const BUTTON_PAD: f32 = 2.0;
+const CURRENT_SLOT_COLOR: Color = Color::new(0.8, 0.8, 0.8, 1.0);
+
+/// What the slots screen shows while it's open. The slots are read from storage when opening it,
+/// and again after every action.
+pub struct SlotsScreen {
+    slots: Vec<SlotSummary>,
+    is_full: bool,
+    renaming: Option<(SlotId, TextInput)>,
+    confirming_delete: Option<SlotId>,
+    needs_reload: bool,
+    buttons: SlotsButtons,
+}
+
+struct SlotRowButtons {
+    /// None for the slot being played, which can't be loaded again nor deleted.
+    load: Option<Button>,
+    duplicate: Option<Button>,
+    rename: Button,
+    delete: Option<Button>,
+}
+
+struct SlotsButtons {
+    rows: Vec<(SlotId, SlotRowButtons)>,
+    new: Option<Button>,
+    close: Button,
+}
+
+/// What happened in the slots screen this frame.
+pub enum SlotsScreenEvent {
+    None,
+    Close,
+    Action(SlotAction),
+}
+
+impl SlotsScreen {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            is_full: false,
+            renaming: None,
+            confirming_delete: None,
+            needs_reload: true,
+            buttons: SlotsButtons {
+                rows: Vec::new(),
+                new: None,
+                close: Button::new("", Anchor::top_left(0.0, 0.0), 0.0),
+            },
+        }
+    }
+
+    /// Rereads the slots if an action changed them, and places the buttons for this window size.
+    pub fn layout(&mut self, width: f32, height: f32, font_size: f32, translation: &Translation) {
+        if self.needs_reload {
+            self.slots = saved_game::list_slots();
+            self.is_full = saved_game::is_full();
+            self.needs_reload = false;
+        }
+        let panel = panel_rect(width, height, font_size);
+        let messages = &translation.slots;
+        let pad = font_size;
+        let mut rows = Vec::new();
+        for (i, slot) in self.slots.iter().enumerate() {
+            let y = slot_y(panel, font_size, i) + line_height(font_size);
+            let mut right = panel.right() - pad;
+            let mut button = |text: &str| {
+                let button = Button::new(text, Anchor::top_right(right, y), font_size);
+                right = button.rect().x - BUTTON_PAD;
+                button
+            };
+            let delete = (!slot.is_current).then(|| {
+                if self.confirming_delete == Some(slot.id) {
+                    button(&messages.confirm_delete)
+                } else {
+                    button(&messages.delete)
+                }
+            });
+            let renaming = matches!(&self.renaming, Some((id, _)) if *id == slot.id);
+            let rename = button(if renaming {
+                &messages.done
+            } else {
+                &messages.rename
+            });
+            let duplicate = (!self.is_full).then(|| button(&messages.duplicate));
+            let load = (!slot.is_current).then(|| button(&messages.load));
+            rows.push((
+                slot.id,
+                SlotRowButtons {
+                    load,
+                    duplicate,
+                    rename,
+                    delete,
+                },
+            ));
+        }
+        let last_row = slot_y(panel, font_size, self.slots.len());
+        let close = Button::new(
+            &messages.close,
+            Anchor::top_right(panel.right() - pad, last_row),
+            font_size,
+        );
+        let new = (!self.is_full).then(|| {
+            Button::new(
+                &messages.new,
+                Anchor::top_left(panel.x + pad, last_row),
+                font_size,
+            )
+        });
+        self.buttons = SlotsButtons { rows, new, close };
+        if let Some((id, input)) = &mut self.renaming {
+            if let Some(i) = self.slots.iter().position(|slot| slot.id == *id) {
+                let rect = Rect::new(
+                    panel.x + pad,
+                    slot_y(panel, font_size, i),
+                    panel.w * SLOTS_TEXT_COLUMN_WIDTH - pad,
+                    line_height(font_size),
+                );
+                input.set_rect(rect, font_size);
+            }
+        }
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.renaming
+            .as_ref()
+            .is_some_and(|(_, input)| input.is_focused())
+    }
+
+    pub fn interact(&mut self, translation: &Translation) -> SlotsScreenEvent {
+        let mut renamed = false;
+        if let Some((_, input)) = &mut self.renaming {
+            renamed = input.interact() == TextInputEvent::Submitted;
+        }
+        let mut event = SlotsScreenEvent::None;
+        for (slot, buttons) in &mut self.buttons.rows {
+            let slot = *slot;
+            if let Some(load) = &mut buttons.load {
+                if load.interact().is_clicked() {
+                    event = SlotsScreenEvent::Action(SlotAction::Load(slot));
+                }
+            }
+            if let Some(duplicate) = &mut buttons.duplicate {
+                if duplicate.interact().is_clicked() {
+                    let name = self
+                        .slots
+                        .iter()
+                        .find(|summary| summary.id == slot)
+                        .map(|summary| display_name(summary, translation))
+                        .unwrap_or_default();
+                    let name = translation.slots.copy_name.format(&[("name", &name)]);
+                    event = SlotsScreenEvent::Action(SlotAction::Duplicate { slot, name });
+                }
+            }
+            let renaming_this = matches!(&self.renaming, Some((id, _)) if *id == slot);
+            if buttons.rename.interact().is_clicked() || (renaming_this && renamed) {
+                if renaming_this {
+                    let (_, input) = self.renaming.take().unwrap();
+                    let name = input.text().to_string();
+                    event = SlotsScreenEvent::Action(SlotAction::Rename { slot, name });
+                } else {
+                    let name = self
+                        .slots
+                        .iter()
+                        .find(|summary| summary.id == slot)
+                        .map(|summary| summary.name.clone())
+                        .unwrap_or_default();
+                    let mut input = TextInput::new(&name, MAX_NAME_LENGTH);
+                    input.set_focused(true);
+                    input.select_all();
+                    self.renaming = Some((slot, input));
+                }
+            }
+            if let Some(delete) = &mut buttons.delete {
+                if delete.interact().is_clicked() {
+                    if self.confirming_delete == Some(slot) {
+                        self.confirming_delete = None;
+                        event = SlotsScreenEvent::Action(SlotAction::Delete(slot));
+                    } else {
+                        self.confirming_delete = Some(slot);
+                    }
+                }
+            }
+        }
+        if let Some(new) = &mut self.buttons.new {
+            if new.interact().is_clicked() {
+                event = SlotsScreenEvent::Action(SlotAction::New);
+            }
+        }
+        if self.buttons.close.interact().is_clicked() {
+            event = SlotsScreenEvent::Close;
+        }
+        if let SlotsScreenEvent::Action(_) = event {
+            self.needs_reload = true;
+        }
+        event
+    }
+
+    pub fn draw(&self, width: f32, height: f32, font_size: f32, translation: &Translation) {
+        let panel = panel_rect(width, height, font_size);
+        draw_rectangle(
+            panel.x,
+            panel.y,
+            panel.w,
+            panel.h,
+            Color::new(0.7, 0.7, 0.7, 1.00),
+        );
+        draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, BLACK);
+
+        let messages = &translation.slots;
+        let pad = font_size;
+        let line_height = line_height(font_size);
+        draw_text(
+            &messages.title,
+            panel.x + pad,
+            panel.y + pad + font_size,
+            font_size * 1.25,
+            BLACK,
+        );
+        for (i, slot) in self.slots.iter().enumerate() {
+            let y = slot_y(panel, font_size, i);
+            if slot.is_current {
+                draw_rectangle(
+                    panel.x + 2.0,
+                    y - pad * 0.25,
+                    panel.w - 4.0,
+                    line_height * LINES_PER_SLOT as f32 + pad * 0.5,
+                    CURRENT_SLOT_COLOR,
+                );
+            }
+            let renaming = self.renaming.as_ref().filter(|(id, _)| *id == slot.id);
+            match renaming {
+                Some((_, input)) => input.render(),
+                None => {
+                    let mut name = display_name(slot, translation);
+                    if slot.is_current {
+                        name = format!("{} ({})", name, messages.current);
+                    }
+                    draw_text(&name, panel.x + pad, y + font_size, font_size, BLACK);
+                }
+            }
+            let summary = match &slot.run {
+                Some(run) => messages.summary.format(&[
+                    ("act", &translation.slots.act(run.act)),
+                    ("savings", &format_money(run.savings, translation.language)),
+                    ("time", &format_duration(run.play_time)),
+                ]),
+                None => messages.empty.clone(),
+            };
+            let text_y = y + line_height + font_size;
+            draw_text(&summary, panel.x + pad, text_y, font_size, BLACK);
+            if let Some(last_played) = slot.last_played {
+                let date = format_date(last_played);
+                let text = messages.last_played.format(&[("date", &date)]);
+                let text_y = text_y + line_height;
+                draw_text(&text, panel.x + pad, text_y, font_size, DARKGRAY);
+            }
+        }
+        for (_, buttons) in &self.buttons.rows {
+            for button in [&buttons.load, &buttons.duplicate, &buttons.delete]
+                .into_iter()
+                .flatten()
+            {
+                button.render();
+            }
+            buttons.rename.render();
+        }
+        if let Some(new) = &self.buttons.new {
+            new.render();
+        }
+        self.buttons.close.render();
+    }
+}
+
+fn display_name(slot: &SlotSummary, translation: &Translation) -> String {
+    if slot.name.is_empty() {
+        let number = slot.id.to_string();
+        translation.slots.unnamed.format(&[("number", &number)])
+    } else {
+        slot.name.clone()
+    }
+}
+
+fn line_height(font_size: f32) -> f32 {
+    (font_size * SLOTS_LINE_HEIGHT).round()
+}
+
+/// Sized for the most slots there can be, so that it doesn't jump when adding or removing slots.
+fn panel_rect(width: f32, height: f32, font_size: f32) -> Rect {
+    let pad = font_size;
+    let slot_height = line_height(font_size) * LINES_PER_SLOT as f32 + pad;
+    let panel_width = (width * SLOTS_PANEL_WIDTH).round();
+    let panel_height =
+        font_size * 2.0 + slot_height * MAX_SLOTS as f32 + font_size * 2.0 + pad * 2.0;
+    Rect::new(
+        ((width - panel_width) * 0.5).round(),
+        ((height - panel_height) * 0.5).max(0.0).round(),
+        panel_width,
+        panel_height.round(),
+    )
+}
+
+/// Top of the `index`th slot. The one after the last is where the bottom buttons go.
+fn slot_y(panel: Rect, font_size: f32, index: usize) -> f32 {
+    let pad = font_size;
+    let slot_height = line_height(font_size) * LINES_PER_SLOT as f32 + pad;
+    (panel.y + pad + font_size * 2.0 + slot_height * index as f32).round()
+}
+
+/// Texts of each slot go in the left column, and the buttons share the right one.
+pub fn text_max_width(width: f32, font_size: f32) -> f32 {
+    width * SLOTS_PANEL_WIDTH * SLOTS_TEXT_COLUMN_WIDTH - font_size
+}
+
+pub fn button_max_width(width: f32, font_size: f32) -> f32 {
+    let buttons = 4.0;
+    (width * SLOTS_PANEL_WIDTH * (1.0 - SLOTS_TEXT_COLUMN_WIDTH) - font_size) / buttons
+        - font_size * 2.0
+        - BUTTON_PAD
+}
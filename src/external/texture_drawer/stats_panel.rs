@@ -0,0 +1,114 @@
+use macroquad::prelude::*;
+
+use crate::screen::number_format::{format_duration, format_money, format_number};
+use crate::screen::translations::Translation;
+use crate::world::config::Difficulty;
+use crate::world::heores::Hero;
+use crate::world::stats::Stats;
+use crate::world::World;
+
+/// Fraction of the screen width.
+pub const STATS_PANEL_WIDTH: f32 = 0.6;
+/// Fraction of the panel width where the label column ends.
+pub const STATS_LABEL_COLUMN_WIDTH: f32 = 0.55;
+const STATS_LINE_HEIGHT: f32 = 1.3;
+
+/// Lists the stats of the current run next to the ones of every run.
+pub fn draw_stats_panel(
+    world: &World,
+    width: f32,
+    height: f32,
+    font_size: f32,
+    translation: &Translation,
+) {
+    let messages = &translation.stats;
+    let mut rows = vec![(
+        messages.title.clone(),
+        messages.current_run.clone(),
+        messages.lifetime.clone(),
+    )];
+    let current_run = stats_values(world.stats(), translation);
+    let lifetime = stats_values(&world.lifetime_stats(), translation);
+    for ((label, current_run), (_, lifetime)) in current_run.into_iter().zip(lifetime) {
+        rows.push((label, current_run, lifetime));
+    }
+
+    let pad = font_size;
+    let line_height = (font_size * STATS_LINE_HEIGHT).round();
+    let panel_width = (width * STATS_PANEL_WIDTH).round();
+    let panel_height = line_height * rows.len() as f32 + pad * 2.0;
+    let panel = Rect::new(
+        ((width - panel_width) * 0.5).round(),
+        ((height - panel_height) * 0.5).max(0.0).round(),
+        panel_width,
+        panel_height,
+    );
+    draw_rectangle(
+        panel.x,
+        panel.y,
+        panel.w,
+        panel.h,
+        Color::new(0.7, 0.7, 0.7, 1.00),
+    );
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, BLACK);
+
+    let label_x = panel.x + pad;
+    let current_run_right = panel.x + panel.w * (STATS_LABEL_COLUMN_WIDTH + 0.2);
+    let lifetime_right = panel.x + panel.w - pad;
+    for (i, (label, current_run, lifetime)) in rows.iter().enumerate() {
+        let y = (panel.y + pad + line_height * i as f32 + font_size).round();
+        draw_text(label, label_x, y, font_size, BLACK);
+        for (text, right) in [(current_run, current_run_right), (lifetime, lifetime_right)] {
+            let text_width = measure_text(text, None, font_size as u16, 1.0).width;
+            draw_text(text, (right - text_width).round(), y, font_size, BLACK);
+        }
+        if i == 0 {
+            let line_y = y + (line_height - font_size) * 0.5 + 2.0;
+            draw_line(label_x, line_y, lifetime_right, line_y, 1.0, BLACK);
+        }
+    }
+}
+
+fn stats_values(stats: &Stats, translation: &Translation) -> Vec<(String, String)> {
+    let messages = &translation.stats;
+    let language = translation.language;
+    let number = |number: i64| format_number(number, language);
+    let mut values = vec![
+        (messages.clean_clicks.clone(), number(stats.clean_clicks)),
+        (messages.dirty_clicks.clone(), number(stats.dirty_clicks)),
+    ];
+    for hero in Hero::list() {
+        let label = messages
+            .hired_and_fired
+            .format(&[("name", &hero.name(translation))]);
+        let value = format!(
+            "{} / {}",
+            number(stats.units_bought[hero]),
+            number(stats.units_sold[hero])
+        );
+        values.push((label, value));
+    }
+    values.push((
+        messages.money_spent.clone(),
+        format_money(stats.money_spent, language),
+    ));
+    values.push((
+        messages.peak_dirtiness.clone(),
+        number(stats.peak_dirtiness / 100),
+    ));
+    for (act, label) in &messages.time_in_act {
+        values.push((label.clone(), format_duration(stats.time_in_act[act])));
+    }
+    values.push((messages.game_overs.clone(), number(stats.game_overs)));
+    for difficulty in Difficulty::list() {
+        let name = translation.new_game.difficulty(*difficulty);
+        let label = messages.games.format(&[("difficulty", &name)]);
+        values.push((label, number(stats.games[difficulty])));
+    }
+    values
+}
+
+/// Labels have to fit in the first column.
+pub fn label_max_width(width: f32, font_size: f32) -> f32 {
+    width * STATS_PANEL_WIDTH * STATS_LABEL_COLUMN_WIDTH - font_size * 2.0
+}
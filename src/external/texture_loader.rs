@@ -1,75 +1,321 @@
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Poll, RawWaker, RawWakerVTable, Waker};
 
-use macroquad::prelude::{load_texture, trace, FileError, Texture2D};
+use macroquad::prelude::{
+    trace, warn, Color, FileError, FilterMode, Image, Texture2D, BLACK, MAGENTA,
+};
+
+use crate::external::asset_manifest::{AssetError, AssetManifest, TextureEntry};
+use crate::external::atlas::build_atlases;
+use crate::external::embedded_assets::{expected_size, load_asset};
+use crate::screen::textures::{Sprite, Textures};
+
+/// In squares of the checkerboard, which are a pixel each.
+const PLACEHOLDER_SIDE: u16 = 8;
+const PLACEHOLDER_DARK: Color = BLACK;
+const PLACEHOLDER_LIGHT: Color = MAGENTA;
+
+/// A future that can be polled with `resume()`.
+pub type Task<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// A file being loaded.
+pub type Loading<T> = Task<Result<T, FileError>>;
 
 pub struct TextureLoader {
-    texture_paths: &'static [&'static str], // if I make these non-static, it doesn't compile because the struct must outlive the in_progress pin ???
-    textures: Vec<Texture2D>,
-    in_progress: Option<Pin<Box<dyn Future<Output = Result<Texture2D, FileError>>>>>,
+    entries: Vec<TextureEntry>,
+    /// The result of each entry, in the order of the manifest, once its file finished loading.
+    results: Vec<Option<Result<Image, String>>>,
+    files: Concurrent<Result<Vec<u8>, FileError>>,
+    counter: LoadCounter,
 }
 
+#[derive(Copy, Clone)]
 pub struct Progress {
     pub loaded: usize,
     pub total_to_load: usize,
+    /// Of the files that finished loading.
+    pub loaded_bytes: usize,
+    /// Of every file, as far as it's known before loading them.
+    pub total_bytes: usize,
 }
 
-impl TextureLoader {
-    pub fn new(texture_paths: &'static [&'static str]) -> Self {
+impl Progress {
+    /// From 0 to 1, by bytes, or by files if the sizes are not known.
+    pub fn fraction(&self) -> f32 {
+        let (done, total) = if self.total_bytes > 0 {
+            (self.loaded_bytes, self.total_bytes)
+        } else {
+            (self.loaded, self.total_to_load)
+        };
+        if total == 0 {
+            1.0
+        } else {
+            (done as f32 / total as f32).min(1.0)
+        }
+    }
+}
+
+impl std::ops::Add for Progress {
+    type Output = Progress;
+
+    fn add(self, other: Progress) -> Progress {
+        Progress {
+            loaded: self.loaded + other.loaded,
+            total_to_load: self.total_to_load + other.total_to_load,
+            loaded_bytes: self.loaded_bytes + other.loaded_bytes,
+            total_bytes: self.total_bytes + other.total_bytes,
+        }
+    }
+}
+
+/// Counts the files of a loader as they finish. The total of bytes starts with the sizes that the
+/// files had when the game was built, and each file replaces its size with the real one when it
+/// finishes, so that files changed since then only make the total inexact until they are loaded.
+pub struct LoadCounter {
+    expected_bytes: Vec<usize>,
+    loaded: usize,
+    loaded_bytes: usize,
+    /// Expected of the files that didn't finish yet.
+    pending_bytes: usize,
+}
+
+impl LoadCounter {
+    pub fn new(paths: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let expected_bytes = paths
+            .into_iter()
+            .map(|path| expected_size(path.as_ref()).unwrap_or(0))
+            .collect::<Vec<_>>();
         Self {
-            texture_paths,
-            textures: Vec::new(),
-            in_progress: None,
+            pending_bytes: expected_bytes.iter().sum(),
+            expected_bytes,
+            loaded: 0,
+            loaded_bytes: 0,
         }
     }
 
-    pub fn get_progress(&self) -> Progress {
+    /// The file `index` of the paths finished, with `bytes` if it could be read.
+    pub fn finish(&mut self, index: usize, bytes: Option<usize>) {
+        self.loaded += 1;
+        self.loaded_bytes += bytes.unwrap_or(0);
+        self.pending_bytes = self
+            .pending_bytes
+            .saturating_sub(self.expected_bytes[index]);
+    }
+
+    pub fn progress(&self) -> Progress {
         Progress {
-            loaded: self.textures.len(),
-            total_to_load: self.texture_paths.len(),
+            loaded: self.loaded,
+            total_to_load: self.expected_bytes.len(),
+            loaded_bytes: self.loaded_bytes,
+            total_bytes: self.loaded_bytes + self.pending_bytes,
         }
     }
+}
+
+impl TextureLoader {
+    /// Loads up to `max_in_flight` files at the same time, which on web are separate requests.
+    pub fn new(manifest: &AssetManifest, max_in_flight: usize) -> Self {
+        let entries = manifest.textures.clone();
+        Self {
+            results: vec![None; entries.len()],
+            files: Concurrent::new(entries.len(), max_in_flight),
+            counter: LoadCounter::new(entries.iter().map(|entry| entry.path.as_str())),
+            entries,
+        }
+    }
+
+    pub fn get_progress(&self) -> Progress {
+        self.counter.progress()
+    }
 
     /// returns Ok(None) until all textures are loaded, and then returns Ok(Some(textures))
-    /// returns Err() if a file couldn't be read for any reason
-    pub fn get_textures(&mut self) -> Result<Option<Vec<Texture2D>>, FileError> {
-        if self.textures.len() < self.texture_paths.len() {
-            // more textures to load
-            let next_unloaded_index = self.textures.len();
-            if let Some(in_progress) = &mut self.in_progress {
-                // the loading of some texture was started
-                match resume(in_progress) {
-                    Some(texture_res) => {
-                        // the texture finished loading
-                        let texture = texture_res?;
-                        self.textures.push(texture);
-                        self.in_progress = None;
-                    }
-                    None => {
-                        // the texture is still loading
-                    }
+    /// Textures that couldn't be loaded use their fallback, or a placeholder if they have none.
+    /// returns Err() only if some of those were critical.
+    pub fn get_textures(&mut self) -> Result<Option<Textures>, AssetError> {
+        if !self.files.is_done() {
+            let entries = &self.entries;
+            let finished = self.files.poll(|index| {
+                let path = entries[index].path.clone();
+                Box::pin(async move { load_asset(&path).await })
+            });
+            for (index, file) in finished {
+                self.finish_file(index, file);
+            }
+            return Ok(None);
+        }
+        trace!("finished loading textures");
+        let mut images = Vec::new();
+        for (entry, result) in self.entries.iter().zip(&mut self.results) {
+            // the errors stay, to look for fallbacks
+            if let Some(Ok(_)) = result {
+                if let Some(Ok(image)) = result.take() {
+                    images.push((entry.id.clone(), image, entry.filter));
                 }
-            } else {
-                // no texture is loading
-                let texture_fut = load_texture(&self.texture_paths[next_unloaded_index]);
-                let texture_pin = Box::pin(texture_fut);
-                self.in_progress = Some(texture_pin);
             }
-            Ok(None)
-        } else {
-            trace!("finished loading textures");
-            let mut textures = Vec::new();
-            std::mem::swap(&mut textures, &mut self.textures);
-            Ok(Some(textures))
         }
+        let mut textures = build_atlases(images);
+        let missing = replace_failed(&self.entries, &self.results, &mut textures, || {
+            let texture = Texture2D::from_image(&checkerboard());
+            texture.set_filter(FilterMode::Nearest);
+            Sprite::whole(texture)
+        })?;
+        Ok(Some(Textures::new(textures, missing)))
     }
 }
 
+impl TextureLoader {
+    /// Keeps the decoded image of the file of entry `index`, or why it couldn't be loaded. A file
+    /// that can't be decoded counts as loaded, like one that can't be read.
+    fn finish_file(&mut self, index: usize, file: Result<Vec<u8>, FileError>) {
+        let entry = &self.entries[index];
+        let result = match file {
+            Ok(bytes) => {
+                self.counter.finish(index, Some(bytes.len()));
+                decode_image(&bytes)
+            }
+            Err(e) => {
+                self.counter.finish(index, None);
+                Err(e.to_string())
+            }
+        };
+        if let Err(e) = &result {
+            warn!("texture {:?}: {}", entry.id, e);
+        }
+        self.results[index] = Some(result);
+    }
+}
+
+/// Decodes a png without panicking, unlike macroquad, so that a corrupt file is just missing.
+pub fn decode_image(bytes: &[u8]) -> Result<Image, String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+    Ok(Image {
+        width: image.width() as u16,
+        height: image.height() as u16,
+        bytes: image.into_raw(),
+    })
+}
+
+/// Gives each texture whose file failed its fallback, or the placeholder that `placeholder`
+/// creates the first time it's needed. Returns the paths that got the placeholder, or the errors
+/// of the critical textures that got nothing.
+fn replace_failed<T: Copy, I>(
+    entries: &[TextureEntry],
+    results: &[Option<Result<I, String>>],
+    textures: &mut HashMap<String, T>,
+    mut placeholder: impl FnMut() -> T,
+) -> Result<Vec<String>, AssetError> {
+    let mut missing = Vec::new();
+    let mut critical = Vec::new();
+    let mut created = None;
+    for (entry, result) in entries.iter().zip(results) {
+        let Some(Err(error)) = result else {
+            continue;
+        };
+        let texture = match resolve_fallback(entries, textures, &entry.id) {
+            Some(texture) => *texture,
+            None if entry.critical => {
+                critical.push(error.clone());
+                continue;
+            }
+            None => {
+                missing.push(entry.path.clone());
+                *created.get_or_insert_with(&mut placeholder)
+            }
+        };
+        textures.insert(entry.id.clone(), texture);
+    }
+    if !critical.is_empty() {
+        return Err(AssetError::CriticalMissing(critical));
+    }
+    Ok(missing)
+}
+
+/// Keeps up to `limit` futures running, started in order, and collects what each one returned.
+pub struct Concurrent<T> {
+    total: usize,
+    limit: usize,
+    started: usize,
+    in_flight: Vec<(usize, Task<T>)>,
+}
+
+impl<T> Concurrent<T> {
+    pub fn new(total: usize, limit: usize) -> Self {
+        Self {
+            total,
+            limit: limit.max(1),
+            started: 0,
+            in_flight: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.started == self.total && self.in_flight.is_empty()
+    }
+
+    /// Starts the next futures with `start(index)` until there are `limit` running, and polls
+    /// each of them once. Returns the ones that finished, with their index.
+    pub fn poll<F>(&mut self, mut start: F) -> Vec<(usize, T)>
+    where
+        F: FnMut(usize) -> Task<T>,
+    {
+        while self.in_flight.len() < self.limit && self.started < self.total {
+            self.in_flight.push((self.started, start(self.started)));
+            self.started += 1;
+        }
+        let mut finished = Vec::new();
+        self.in_flight
+            .retain_mut(|(index, future)| match resume(future) {
+                Some(output) => {
+                    finished.push((*index, output));
+                    false
+                }
+                None => true,
+            });
+        finished
+    }
+}
+
+/// Follows the fallbacks of `id` until one that was loaded.
+fn resolve_fallback<'a, T>(
+    entries: &[TextureEntry],
+    loaded: &'a HashMap<String, T>,
+    id: &str,
+) -> Option<&'a T> {
+    let mut current = id;
+    // a chain longer than the manifest has a loop
+    for _ in 0..entries.len() {
+        let entry = entries.iter().find(|entry| entry.id == current)?;
+        let fallback = entry.fallback.as_deref()?;
+        match loaded.get(fallback) {
+            Some(texture) => return Some(texture),
+            None => current = fallback,
+        }
+    }
+    None
+}
+
+/// What a texture that couldn't be loaded looks like, so that it's obvious but the game is still
+/// playable.
+pub fn checkerboard() -> Image {
+    let mut image = Image::gen_image_color(PLACEHOLDER_SIDE, PLACEHOLDER_SIDE, PLACEHOLDER_DARK);
+    for y in 0..PLACEHOLDER_SIDE as u32 {
+        for x in 0..PLACEHOLDER_SIDE as u32 {
+            if (x + y) % 2 == 0 {
+                image.set_pixel(x, y, PLACEHOLDER_LIGHT);
+            }
+        }
+    }
+    image
+}
+
 // resume() and waker() taken from macroquad::exec. I don't understand why they are private
 
 /// returns Some(T) if future is done, None if it would block
-fn resume<T>(future: &mut Pin<Box<dyn Future<Output = T>>>) -> Option<T> {
+pub fn resume<T>(future: &mut Task<T>) -> Option<T> {
     let waker = waker();
     let mut futures_context = std::task::Context::from_waker(&waker);
     match future.as_mut().poll(&mut futures_context) {
@@ -97,3 +343,145 @@ fn waker() -> Waker {
     let raw_waker = RawWaker::new(std::ptr::null(), &VTABLE);
     unsafe { Waker::from_raw(raw_waker) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::asset_manifest::{AssetManifest, MANIFEST_PATH};
+
+    #[test]
+    fn test_fallback_chain() {
+        let manifest = AssetManifest::parse(
+            "texture.a.path = a.png\n\
+             texture.a.fallback = b\n\
+             texture.b.path = b.png\n\
+             texture.b.fallback = c\n\
+             texture.c.path = c.png\n\
+             texture.d.path = d.png\n\
+             texture.d.fallback = d\n",
+        )
+        .unwrap();
+        let entries = &manifest.textures;
+        let loaded = HashMap::from([("c".to_string(), 3)]);
+        assert_eq!(resolve_fallback(entries, &loaded, "a"), Some(&3));
+        assert_eq!(resolve_fallback(entries, &loaded, "c"), None);
+        assert_eq!(resolve_fallback(entries, &loaded, "d"), None);
+    }
+
+    /// Finishes after being polled `polls_left` times.
+    struct Delayed {
+        polls_left: usize,
+        output: usize,
+    }
+
+    impl Future for Delayed {
+        type Output = usize;
+
+        fn poll(mut self: Pin<&mut Self>, _: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+            if self.polls_left <= 1 {
+                Poll::Ready(self.output)
+            } else {
+                self.polls_left -= 1;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_concurrent_limit_and_order() {
+        let polls_needed = [3, 1, 1, 1, 2];
+        let mut concurrent = Concurrent::new(polls_needed.len(), 2);
+        let mut finished = Vec::new();
+        let mut rounds = 0;
+        while !concurrent.is_done() {
+            finished.extend(concurrent.poll(|index| {
+                Box::pin(Delayed {
+                    polls_left: polls_needed[index],
+                    output: index * 10,
+                })
+            }));
+            assert!(concurrent.in_flight.len() <= 2);
+            rounds += 1;
+        }
+        // the first one takes long, so the rest go through the other place meanwhile
+        assert_eq!(finished, [(1, 10), (2, 20), (0, 0), (3, 30), (4, 40)]);
+        assert_eq!(rounds, 5);
+    }
+
+    #[test]
+    fn test_undecodable_file_gets_placeholder() {
+        let manifest = AssetManifest::parse(
+            "texture.hero.path = hero.png\n\
+             texture.ferris.path = assets/images/ferris.png\n",
+        )
+        .unwrap();
+        let ferris = std::fs::read("assets/images/ferris.png").unwrap();
+        let results = vec![
+            Some(decode_image(b"\x89PNG garbage")),
+            Some(decode_image(&ferris)),
+        ];
+        assert!(matches!(results[0], Some(Err(_))));
+        assert!(matches!(results[1], Some(Ok(_))));
+
+        let mut textures = HashMap::from([("ferris".to_string(), 1)]);
+        let missing = replace_failed(&manifest.textures, &results, &mut textures, || 0).unwrap();
+        assert_eq!(textures["hero"], 0);
+        assert_eq!(textures["ferris"], 1);
+        assert_eq!(missing, ["hero.png"]);
+    }
+
+    #[test]
+    fn test_failed_files_count_as_loaded() {
+        let manifest = AssetManifest::parse(
+            "texture.a.path = a.png\n\
+             texture.b.path = b.png\n\
+             texture.c.path = c.png\n",
+        )
+        .unwrap();
+        let mut loader = TextureLoader::new(&manifest, 2);
+        loader.finish_file(1, Ok(b"not a png".to_vec()));
+        loader.finish_file(
+            2,
+            Err(FileError::new(
+                macroquad::miniquad::fs::Error::DownloadFailed,
+                "c.png",
+            )),
+        );
+        let progress = loader.get_progress();
+        assert_eq!(progress.loaded, 2);
+        assert_eq!(progress.total_to_load, 3);
+        assert_eq!(progress.loaded_bytes, 9);
+        assert!(loader.results[0].is_none());
+        assert!(matches!(loader.results[1], Some(Err(_))));
+        assert!(matches!(loader.results[2], Some(Err(_))));
+    }
+
+    #[test]
+    fn test_bytes_progress() {
+        let manifest_size = std::fs::metadata(MANIFEST_PATH).unwrap().len() as usize;
+        let mut counter = LoadCounter::new([MANIFEST_PATH, "not_built.png"]);
+        let progress = counter.progress();
+        assert_eq!(progress.total_bytes, manifest_size);
+        assert_eq!(progress.fraction(), 0.0);
+
+        counter.finish(1, Some(10));
+        let progress = counter.progress();
+        assert_eq!(progress.loaded_bytes, 10);
+        assert_eq!(progress.total_bytes, manifest_size + 10);
+
+        // changed since the game was built
+        counter.finish(0, Some(manifest_size * 2));
+        let progress = counter.progress();
+        assert_eq!(progress.loaded, 2);
+        assert_eq!(progress.total_bytes, progress.loaded_bytes);
+        assert_eq!(progress.fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_checkerboard() {
+        let image = checkerboard();
+        assert_eq!(image.get_pixel(0, 0), PLACEHOLDER_LIGHT);
+        assert_eq!(image.get_pixel(1, 0), PLACEHOLDER_DARK);
+        assert_eq!(image.get_pixel(1, 1), PLACEHOLDER_LIGHT);
+    }
+}
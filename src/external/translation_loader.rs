@@ -0,0 +1,89 @@
+use macroquad::prelude::{load_string, measure_text, trace, warn, FileError};
+
+use crate::external::texture_drawer::TextureDrawer;
+use crate::external::texture_loader::{resume, LoadCounter, Loading, Progress};
+use crate::screen::translation_check::{check_translations, TranslationIssue};
+use crate::screen::translations::{parse_messages, Language, Translations};
+
+/// Loads the translation file of each language, one per call to `get_translations`, like
+/// `TextureLoader` does with textures. Unlike textures, a file that can't be loaded is not an error:
+/// that language will use the translation that was compiled in.
+pub struct TranslationLoader {
+    loaded_files: Vec<(Language, String)>,
+    attempted: usize,
+    counter: LoadCounter,
+    in_progress: Option<Loading<String>>,
+}
+
+impl TranslationLoader {
+    pub fn new() -> Self {
+        Self {
+            loaded_files: Vec::new(),
+            attempted: 0,
+            counter: LoadCounter::new(
+                Language::list()
+                    .iter()
+                    .map(|language| language.translation_path()),
+            ),
+            in_progress: None,
+        }
+    }
+
+    pub fn get_progress(&self) -> Progress {
+        self.counter.progress()
+    }
+
+    /// returns None until all translation files were loaded or failed to load
+    pub fn get_translations(&mut self) -> Option<Translations> {
+        let languages = Language::list();
+        if self.attempted < languages.len() {
+            let language = languages[self.attempted];
+            if let Some(in_progress) = &mut self.in_progress {
+                if let Some(text_res) = resume(in_progress) {
+                    match text_res {
+                        Ok(text) => {
+                            self.counter.finish(self.attempted, Some(text.len()));
+                            self.loaded_files.push((language, text));
+                        }
+                        Err(e) => {
+                            self.counter.finish(self.attempted, None);
+                            warn!("using compiled-in translation for {:?}: {}", language, e);
+                        }
+                    }
+                    self.attempted += 1;
+                    self.in_progress = None;
+                }
+            } else {
+                let path = language.translation_path();
+                self.in_progress = Some(Box::pin(async move { load_string(&path).await }));
+            }
+            None
+        } else {
+            trace!("finished loading translations");
+            Some(Translations::new(std::mem::take(&mut self.loaded_files)))
+        }
+    }
+}
+
+impl Default for TranslationLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the translation files from disk and checks them against each other and against the layout
+/// of a window of this size. Used by the `--check-translations` flag.
+pub async fn check_translation_files(
+    width: f32,
+    height: f32,
+) -> Result<Vec<TranslationIssue>, FileError> {
+    let mut translations = Vec::new();
+    for language in Language::list() {
+        let text = load_string(&language.translation_path()).await?;
+        translations.push((*language, parse_messages(&text)));
+    }
+    let limits = TextureDrawer::text_width_limits(width, height);
+    let measure =
+        |text: &str, font_size: f32| measure_text(text, None, font_size as u16, 1.0).width;
+    Ok(check_translations(&translations, &limits, &measure))
+}
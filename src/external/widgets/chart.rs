@@ -0,0 +1,133 @@
+use macroquad::prelude::{
+    draw_line, draw_rectangle, draw_rectangle_lines, draw_text, measure_text, Color, Rect, BLACK,
+    WHITE,
+};
+
+use crate::external::backends::Vec2;
+
+/// A line of the chart. Points are (x, y) in the units of the data, e.g. (seconds, cents).
+pub struct Series {
+    pub points: Vec<(f64, f64)>,
+    pub color: Color,
+}
+
+/// Line chart where every series shares the same axes. The x axis spans from the first to the
+/// last point of all series, and the y axis from 0 to the highest point, labelled with `y_label`.
+pub struct Chart<'a> {
+    pub title: &'a str,
+    pub series: Vec<Series>,
+    pub y_label: &'a dyn Fn(f64) -> String,
+}
+
+impl<'a> Chart<'a> {
+    pub fn render(&self, rect: Rect, font_size: f32) {
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, WHITE);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, BLACK);
+        let (x_range, y_max) = self.ranges();
+        let text_y = (rect.y + font_size).round();
+        draw_text(self.title, (rect.x + 4.0).round(), text_y, font_size, BLACK);
+        let y_text = (self.y_label)(y_max);
+        let y_text_x =
+            (rect.x + rect.w - 4.0 - measure_text(&y_text, None, font_size as u16, 1.0).width)
+                .round();
+        draw_text(&y_text, y_text_x, text_y, font_size, BLACK);
+
+        let plot = Rect::new(
+            rect.x + 2.0,
+            rect.y + font_size * 1.25,
+            rect.w - 4.0,
+            rect.h - font_size * 1.25 - 2.0,
+        );
+        for series in &self.series {
+            let pixels = to_pixels(&series.points, plot, x_range, y_max);
+            for pair in pixels.windows(2) {
+                draw_line(
+                    pair[0].x,
+                    pair[0].y,
+                    pair[1].x,
+                    pair[1].y,
+                    2.0,
+                    series.color,
+                );
+            }
+        }
+    }
+
+    /// Returns the range of x and the max of y. Never empty, so that the chart can be divided by
+    /// them.
+    fn ranges(&self) -> ((f64, f64), f64) {
+        let points = || self.series.iter().flat_map(|series| series.points.iter());
+        let x_min = points().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let x_max = points().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        let y_max = points().map(|p| p.1).fold(0.0, f64::max);
+        let x_range = if x_min < x_max {
+            (x_min, x_max)
+        } else if x_min.is_finite() {
+            (x_min, x_min + 1.0)
+        } else {
+            (0.0, 1.0)
+        };
+        (x_range, if y_max > 0.0 { y_max } else { 1.0 })
+    }
+}
+
+/// Maps data points into the rect, with y growing upwards.
+fn to_pixels(points: &[(f64, f64)], rect: Rect, x_range: (f64, f64), y_max: f64) -> Vec<Vec2> {
+    points
+        .iter()
+        .map(|(x, y)| {
+            let x_coef = ((x - x_range.0) / (x_range.1 - x_range.0)) as f32;
+            let y_coef = (y / y_max).clamp(0.0, 1.0) as f32;
+            Vec2::new(rect.x + x_coef * rect.w, rect.y + (1.0 - y_coef) * rect.h)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        let y_label = |y: f64| y.to_string();
+        let series = |points: Vec<(f64, f64)>| Series {
+            points,
+            color: BLACK,
+        };
+        let chart = Chart {
+            title: "",
+            series: vec![
+                series(vec![(2.0, 5.0), (4.0, 1.0)]),
+                series(vec![(3.0, 7.0)]),
+            ],
+            y_label: &y_label,
+        };
+        assert_eq!(chart.ranges(), ((2.0, 4.0), 7.0));
+
+        let empty = Chart {
+            title: "",
+            series: vec![series(vec![])],
+            y_label: &y_label,
+        };
+        assert_eq!(empty.ranges(), ((0.0, 1.0), 1.0));
+    }
+
+    #[test]
+    fn test_to_pixels() {
+        let rect = Rect::new(10.0, 20.0, 100.0, 50.0);
+        let pixels = to_pixels(
+            &[(0.0, 0.0), (5.0, 10.0), (10.0, 20.0)],
+            rect,
+            (0.0, 10.0),
+            10.0,
+        );
+        assert_eq!(
+            pixels,
+            vec![
+                Vec2::new(10.0, 70.0),
+                Vec2::new(60.0, 20.0),
+                Vec2::new(110.0, 20.0)
+            ]
+        );
+    }
+}
@@ -212,11 +212,17 @@ impl Clone for TextRect {
 }
 
 pub fn draw_tooltip_centered(text: &str, position: Vec2, font_size: f32) {
+    draw_tooltip_centered_faded(text, position, font_size, 1.0);
+}
+
+/// Like `draw_tooltip_centered`, with an `alpha` from 0 (invisible) to 1.
+pub fn draw_tooltip_centered_faded(text: &str, position: Vec2, font_size: f32, alpha: f32) {
+    let faded = |color: Color| Color { a: alpha, ..color };
     let text_rect = TextRect::new(text, Anchor::center_v(position), font_size);
     let rect = &text_rect.rect;
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, ALERT_COLOR);
-    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, BLACK);
-    text_rect.render_text(BLACK);
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, faded(ALERT_COLOR));
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, faded(BLACK));
+    text_rect.render_text(faded(BLACK));
 }
 
 pub fn draw_text_centered(text: &str, mut position: Vec2, width: f32, height: f32, font_size: f32) {
@@ -0,0 +1,347 @@
+//! A single-line text field. Characters come from macroquad's char queue, so that keyboard
+//! layouts and input methods produce the characters that the player sees.
+
+use macroquad::prelude::{
+    draw_line, draw_rectangle, draw_rectangle_lines, draw_text, get_char_pressed, get_time,
+    is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, measure_text,
+    mouse_position, Color, KeyCode, MouseButton, Rect, BLACK, GRAY, WHITE,
+};
+
+use crate::external::backends::Vec2;
+
+const SELECTION_COLOR: Color = Color::new(0.6, 0.75, 1.0, 1.0);
+/// Times per second that the cursor appears and disappears.
+const CURSOR_BLINK_FREQUENCY: f64 = 1.0;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum TextInputEvent {
+    None,
+    Changed,
+    /// Enter was pressed while focused.
+    Submitted,
+}
+
+pub struct TextInput {
+    text: String,
+    /// In characters, not bytes.
+    cursor: usize,
+    /// Where the selection started, the cursor being the other end.
+    selection_anchor: Option<usize>,
+    /// In characters.
+    max_length: usize,
+    focused: bool,
+    /// Whether the mouse is selecting text since it was pressed inside.
+    dragging: bool,
+    rect: Rect,
+    font_size: f32,
+}
+
+impl TextInput {
+    /// Starts without focus and with the cursor at the end of `text`.
+    pub fn new(text: &str, max_length: usize) -> Self {
+        let mut input = Self {
+            text: String::new(),
+            cursor: 0,
+            selection_anchor: None,
+            max_length,
+            focused: false,
+            dragging: false,
+            rect: Rect::new(0.0, 0.0, 0.0, 0.0),
+            font_size: 0.0,
+        };
+        input.insert(text);
+        input
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Where to draw it. Can change every frame, e.g. when the window is resized.
+    pub fn set_rect(&mut self, rect: Rect, font_size: f32) {
+        self.rect = rect;
+        self.font_size = font_size;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        if focused && !self.focused {
+            // the queue keeps what was typed while nothing was listening
+            while get_char_pressed().is_some() {}
+        }
+        self.focused = focused;
+        self.dragging = false;
+    }
+
+    /// Takes the focus when clicked and loses it when clicking elsewhere. While focused, edits the
+    /// text with the keyboard.
+    pub fn interact(&mut self) -> TextInputEvent {
+        let mouse = Vec2::from(mouse_position());
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let inside = self.rect.contains(mouse);
+            self.set_focused(inside);
+            if inside {
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                self.move_cursor(self.index_at(mouse.x), shift);
+                self.dragging = true;
+            }
+        } else if self.dragging {
+            if is_mouse_button_down(MouseButton::Left) {
+                self.move_cursor(self.index_at(mouse.x), true);
+            } else {
+                self.dragging = false;
+            }
+        }
+        if !self.focused {
+            return TextInputEvent::None;
+        }
+
+        let before = self.text.clone();
+        let control = is_key_down(KeyCode::LeftControl)
+            || is_key_down(KeyCode::RightControl)
+            || is_key_down(KeyCode::LeftSuper)
+            || is_key_down(KeyCode::RightSuper);
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let mut typed = Vec::new();
+        while let Some(character) = get_char_pressed() {
+            typed.push(character);
+        }
+        // the queue is a stack, so the last typed character comes first
+        typed.reverse();
+        if !control {
+            self.insert(&typed.into_iter().collect::<String>());
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.backspace();
+        }
+        if is_key_pressed(KeyCode::Delete) {
+            self.delete();
+        }
+        if is_key_pressed(KeyCode::Left) {
+            self.move_cursor(self.cursor.saturating_sub(1), shift);
+        }
+        if is_key_pressed(KeyCode::Right) {
+            self.move_cursor(self.cursor + 1, shift);
+        }
+        if is_key_pressed(KeyCode::Home) {
+            self.move_cursor(0, shift);
+        }
+        if is_key_pressed(KeyCode::End) {
+            self.move_cursor(self.len(), shift);
+        }
+        if control && is_key_pressed(KeyCode::A) {
+            self.select_all();
+        }
+        if control && is_key_pressed(KeyCode::V) {
+            if let Some(pasted) = clipboard() {
+                self.insert(&pasted);
+            }
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.set_focused(false);
+        }
+
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            TextInputEvent::Submitted
+        } else if self.text != before {
+            TextInputEvent::Changed
+        } else {
+            TextInputEvent::None
+        }
+    }
+
+    pub fn render(&self) {
+        let rect = self.rect;
+        let pad = (self.font_size * 0.5).round();
+        let text_x = rect.x + pad;
+        let baseline = (rect.y + (rect.h + self.font_size * 0.6) * 0.5).round();
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, WHITE);
+        if let Some((start, end)) = self.selection() {
+            let start_x = text_x + self.width_until(start);
+            let end_x = text_x + self.width_until(end);
+            draw_rectangle(
+                start_x,
+                rect.y + 2.0,
+                end_x - start_x,
+                rect.h - 4.0,
+                SELECTION_COLOR,
+            );
+        }
+        draw_text(&self.text, text_x, baseline, self.font_size, BLACK);
+        let blink_on = (get_time() * CURSOR_BLINK_FREQUENCY * 2.0) as i64 % 2 == 0;
+        if self.focused && blink_on {
+            let cursor_x = (text_x + self.width_until(self.cursor)).round();
+            draw_line(
+                cursor_x,
+                rect.y + pad * 0.5,
+                cursor_x,
+                rect.y + rect.h - pad * 0.5,
+                1.0,
+                BLACK,
+            );
+        }
+        let border_color = if self.focused { BLACK } else { GRAY };
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, border_color);
+    }
+
+    /// Replaces the selection, if any. Control characters are dropped, and so is whatever doesn't
+    /// fit in the max length.
+    pub fn insert(&mut self, text: &str) {
+        if !text.chars().any(|c| !c.is_control()) {
+            return;
+        }
+        self.delete_selection();
+        let room = self.max_length.saturating_sub(self.len());
+        let inserted = text
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(room)
+            .collect::<String>();
+        let at = self.byte_index(self.cursor);
+        self.text.insert_str(at, &inserted);
+        self.cursor += inserted.chars().count();
+    }
+
+    pub fn backspace(&mut self) {
+        if !self.delete_selection() && self.cursor > 0 {
+            self.cursor -= 1;
+            self.text.remove(self.byte_index(self.cursor));
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if !self.delete_selection() && self.cursor < self.len() {
+            self.text.remove(self.byte_index(self.cursor));
+        }
+    }
+
+    /// Extends the selection if `selecting`, otherwise clears it.
+    pub fn move_cursor(&mut self, to: usize, selecting: bool) {
+        if selecting {
+            self.selection_anchor.get_or_insert(self.cursor);
+        } else {
+            self.selection_anchor = None;
+        }
+        self.cursor = to.min(self.len());
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection_anchor = Some(0);
+        self.cursor = self.len();
+    }
+
+    /// The selected characters, from the first to the one after the last.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.selection_anchor?;
+        (anchor != self.cursor).then(|| (anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// Returns whether there was something selected.
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.selection_anchor = None;
+        match selection {
+            Some((start, end)) => {
+                let range = self.byte_index(start)..self.byte_index(end);
+                self.text.replace_range(range, "");
+                self.cursor = start;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    fn width_until(&self, char_index: usize) -> f32 {
+        let prefix = &self.text[..self.byte_index(char_index)];
+        measure_text(prefix, None, self.font_size as u16, 1.0).width
+    }
+
+    /// The position between characters closest to `x`.
+    fn index_at(&self, x: f32) -> usize {
+        let relative_x = x - self.rect.x - (self.font_size * 0.5).round();
+        (0..=self.len())
+            .min_by(|a, b| {
+                let distance = |i| (self.width_until(i) - relative_x).abs();
+                distance(*a).total_cmp(&distance(*b))
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn clipboard() -> Option<String> {
+    let gl = unsafe { macroquad::window::get_internal_gl() };
+    gl.quad_context.clipboard_get()
+}
+
+/// Browsers only give the clipboard to paste events, which don't reach the game.
+#[cfg(target_arch = "wasm32")]
+fn clipboard() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typing_and_deleting() {
+        let mut input = TextInput::new("ana", 10);
+        input.backspace();
+        input.insert("ñé");
+        assert_eq!(input.text(), "anñé");
+        input.move_cursor(0, false);
+        input.delete();
+        input.insert("\tJ");
+        assert_eq!(input.text(), "Jnñé");
+        input.backspace();
+        input.backspace();
+        assert_eq!(input.text(), "nñé");
+    }
+
+    #[test]
+    fn test_selection_is_replaced() {
+        let mut input = TextInput::new("hello world", 20);
+        input.move_cursor(0, false);
+        input.move_cursor(5, true);
+        assert_eq!(input.selection(), Some((0, 5)));
+        input.insert("bye");
+        assert_eq!(input.text(), "bye world");
+        assert_eq!(input.selection(), None);
+
+        input.select_all();
+        input.insert("");
+        assert_eq!(input.selection(), Some((0, 9)));
+        input.backspace();
+        assert_eq!(input.text(), "");
+        input.move_cursor(7, true);
+        assert_eq!(input.selection(), None);
+    }
+
+    #[test]
+    fn test_max_length() {
+        let mut input = TextInput::new("abcdefgh", 5);
+        assert_eq!(input.text(), "abcde");
+        input.insert("x");
+        assert_eq!(input.text(), "abcde");
+        input.move_cursor(1, false);
+        input.move_cursor(3, true);
+        input.insert("xyz");
+        assert_eq!(input.text(), "axyde");
+    }
+}
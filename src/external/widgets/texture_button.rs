@@ -1,12 +1,11 @@
 use macroquad::prelude::{
-    draw_texture_ex, is_mouse_button_down, is_mouse_button_released, mouse_position, MouseButton,
-    Rect, WHITE,
+    is_mouse_button_down, is_mouse_button_released, mouse_position, MouseButton, Rect, WHITE,
 };
-use macroquad::prelude::{DrawTextureParams, Texture2D};
 
 use crate::external::backends::Vec2;
 use crate::external::widgets::anchor::Anchor;
 use crate::external::widgets::button::Interaction;
+use crate::screen::textures::Sprite;
 
 pub struct TextureButton {
     rect: Rect,
@@ -29,6 +28,10 @@ impl TextureButton {
         }
     }
 
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
     pub fn interact(&mut self) -> Interaction {
         self.interaction = if self.rect.contains(Vec2::from(mouse_position())) {
             if is_mouse_button_down(MouseButton::Left) {
@@ -43,7 +46,19 @@ impl TextureButton {
         };
         self.interaction
     }
-    pub fn render(&self, textures: Vec<Texture2D>, textures_highlighted: Option<Vec<Texture2D>>) {
+    pub fn render(&self, textures: Vec<Sprite>, textures_highlighted: Option<Vec<Sprite>>) {
+        self.render_scaled(textures, textures_highlighted, 1.0);
+    }
+
+    /// Like `render`, but `scale` times bigger around the center, for animations.
+    pub fn render_scaled(
+        &self,
+        textures: Vec<Sprite>,
+        textures_highlighted: Option<Vec<Sprite>>,
+        scale: f32,
+    ) {
+        let size = self.rect.size() * scale;
+        let top_left = self.rect.center() - size * 0.5;
         let chosen_textures = match self.interaction {
             Interaction::Clicked | Interaction::Pressing => textures,
             Interaction::Hovered => textures_highlighted.unwrap_or(textures),
@@ -51,16 +66,7 @@ impl TextureButton {
         };
 
         for chosen_texture in chosen_textures {
-            draw_texture_ex(
-                chosen_texture,
-                self.rect.x,
-                self.rect.y,
-                WHITE,
-                DrawTextureParams {
-                    dest_size: Some(self.rect.size()),
-                    ..Default::default()
-                },
-            );
+            chosen_texture.draw(top_left.x, top_left.y, WHITE, size);
         }
     }
 }
@@ -0,0 +1,274 @@
+//! Values that change smoothly over time instead of jumping, for animating the widgets. Drawing
+//! code asks `Animations` for the current value of a widget by its id, so it doesn't need to keep
+//! the tweens itself.
+
+use std::collections::HashMap;
+
+use crate::external::backends::Seconds;
+
+/// How the progress of a tween, from 0 to 1, maps to the progress of its value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Starts slow.
+    QuadIn,
+    /// Ends slow.
+    QuadOut,
+    QuadInOut,
+    /// Goes a bit past the end and comes back, like a bounce.
+    BackOut,
+}
+
+impl Easing {
+    /// `t` is clamped between 0 and 1. Returns 0 for 0 and 1 for 1.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+                }
+            }
+            Easing::BackOut => {
+                const OVERSHOOT: f32 = 1.70158;
+                let t = t - 1.0;
+                1.0 + (OVERSHOOT + 1.0) * t * t * t + OVERSHOOT * t * t
+            }
+        }
+    }
+}
+
+/// A value that goes from `from` to `to` in `duration` seconds since `start`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tween {
+    pub from: f32,
+    pub to: f32,
+    pub start: Seconds,
+    pub duration: Seconds,
+    pub easing: Easing,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, start: Seconds, duration: Seconds, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            start,
+            duration,
+            easing,
+        }
+    }
+
+    pub fn value(&self, now: Seconds) -> f32 {
+        let t = if self.duration > 0.0 {
+            ((now - self.start) / self.duration) as f32
+        } else {
+            1.0
+        };
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    pub fn is_done(&self, now: Seconds) -> bool {
+        now - self.start >= self.duration
+    }
+
+    /// Goes to `to` from wherever it is at `now`, so that the value doesn't jump.
+    pub fn retarget(&mut self, to: f32, now: Seconds) {
+        self.from = self.value(now);
+        self.to = to;
+        self.start = now;
+    }
+}
+
+/// Tweens one after the other, each starting from where the previous one ended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline {
+    start: Seconds,
+    from: f32,
+    /// The duration, target value and easing of each step.
+    steps: Vec<(Seconds, f32, Easing)>,
+}
+
+impl Timeline {
+    pub fn new(from: f32) -> Self {
+        Self {
+            start: 0.0,
+            from,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds a step that goes to `to` in `duration` seconds.
+    pub fn then(mut self, duration: Seconds, to: f32, easing: Easing) -> Self {
+        self.steps.push((duration, to, easing));
+        self
+    }
+
+    /// Keeps the value of the previous step for `duration` seconds.
+    pub fn hold(self, duration: Seconds) -> Self {
+        let last = self.last_value();
+        self.then(duration, last, Easing::Linear)
+    }
+
+    pub fn duration(&self) -> Seconds {
+        self.steps.iter().map(|(duration, _, _)| duration).sum()
+    }
+
+    fn last_value(&self) -> f32 {
+        self.steps.last().map_or(self.from, |(_, to, _)| *to)
+    }
+
+    pub fn value(&self, now: Seconds) -> f32 {
+        let mut step_start = self.start;
+        let mut from = self.from;
+        for (duration, to, easing) in &self.steps {
+            if now < step_start + duration {
+                return Tween::new(from, *to, step_start, *duration, *easing).value(now);
+            }
+            step_start += duration;
+            from = *to;
+        }
+        from
+    }
+
+    pub fn is_done(&self, now: Seconds) -> bool {
+        now - self.start >= self.duration()
+    }
+}
+
+/// The animated values of the widgets, by widget id.
+#[derive(Default)]
+pub struct Animations {
+    tweens: HashMap<String, Tween>,
+    timelines: HashMap<String, Timeline>,
+}
+
+impl Animations {
+    /// Moves the value of `id` towards `target`, taking `duration` seconds since the target last
+    /// changed. The first time, it starts at the target.
+    pub fn smooth(
+        &mut self,
+        id: &str,
+        target: f32,
+        now: Seconds,
+        duration: Seconds,
+        easing: Easing,
+    ) -> f32 {
+        match self.tweens.get_mut(id) {
+            Some(tween) => {
+                if tween.to != target {
+                    tween.retarget(target, now);
+                    tween.duration = duration;
+                    tween.easing = easing;
+                }
+                tween.value(now)
+            }
+            None => {
+                let tween = Tween::new(target, target, now, duration, easing);
+                self.tweens.insert(id.to_string(), tween);
+                target
+            }
+        }
+    }
+
+    /// Starts `timeline` for `id` at `now`, replacing the one it had.
+    pub fn play(&mut self, id: &str, mut timeline: Timeline, now: Seconds) {
+        timeline.start = now;
+        self.timelines.insert(id.to_string(), timeline);
+    }
+
+    /// The value of the timeline of `id`, or None if it has none or it finished.
+    pub fn timeline(&self, id: &str, now: Seconds) -> Option<f32> {
+        let timeline = self.timelines.get(id)?;
+        (!timeline.is_done(now)).then(|| timeline.value(now))
+    }
+
+    pub fn is_playing(&self, id: &str, now: Seconds) -> bool {
+        self.timeline(id, now).is_some()
+    }
+
+    /// Forgets the timelines that finished.
+    pub fn remove_finished(&mut self, now: Seconds) {
+        self.timelines.retain(|_, timeline| !timeline.is_done(now));
+    }
+
+    /// Forgets everything about `id`, for widgets that are gone.
+    pub fn forget(&mut self, id: &str) {
+        self.tweens.remove(id);
+        self.timelines.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easings_start_and_end() {
+        for easing in [
+            Easing::Linear,
+            Easing::QuadIn,
+            Easing::QuadOut,
+            Easing::QuadInOut,
+            Easing::BackOut,
+        ] {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+        assert!(Easing::QuadOut.apply(0.5) > 0.5);
+        assert!(Easing::QuadIn.apply(0.5) < 0.5);
+        assert!(Easing::BackOut.apply(0.8) > 1.0);
+    }
+
+    #[test]
+    fn test_retarget_doesnt_jump() {
+        let mut tween = Tween::new(0.0, 10.0, 1.0, 2.0, Easing::Linear);
+        assert_eq!(tween.value(2.0), 5.0);
+        tween.retarget(0.0, 2.0);
+        assert_eq!(tween.value(2.0), 5.0);
+        assert_eq!(tween.value(3.0), 2.5);
+        assert!(!tween.is_done(3.0));
+        assert_eq!(tween.value(5.0), 0.0);
+        assert!(tween.is_done(5.0));
+    }
+
+    #[test]
+    fn test_timeline() {
+        let timeline = Timeline::new(0.0)
+            .then(1.0, 1.0, Easing::Linear)
+            .hold(2.0)
+            .then(1.0, 0.0, Easing::Linear);
+        assert_eq!(timeline.duration(), 4.0);
+        assert_eq!(timeline.value(0.5), 0.5);
+        assert_eq!(timeline.value(2.0), 1.0);
+        assert_eq!(timeline.value(3.5), 0.5);
+        assert_eq!(timeline.value(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_animations_by_id() {
+        let mut animations = Animations::default();
+        assert_eq!(animations.smooth("bar", 1.0, 0.0, 1.0, Easing::Linear), 1.0);
+        assert_eq!(animations.smooth("bar", 3.0, 1.0, 1.0, Easing::Linear), 1.0);
+        assert_eq!(animations.smooth("bar", 3.0, 1.5, 1.0, Easing::Linear), 2.0);
+        assert_eq!(
+            animations.smooth("other", 7.0, 1.5, 1.0, Easing::Linear),
+            7.0
+        );
+
+        let blink = Timeline::new(1.0).then(1.0, 0.0, Easing::Linear);
+        animations.play("alert", blink, 10.0);
+        assert_eq!(animations.timeline("alert", 10.5), Some(0.5));
+        assert!(animations.is_playing("alert", 10.5));
+        assert_eq!(animations.timeline("alert", 11.0), None);
+        assert_eq!(animations.timeline("button", 10.5), None);
+        animations.remove_finished(11.0);
+        assert!(animations.timelines.is_empty());
+    }
+}
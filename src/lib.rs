@@ -1,23 +1,40 @@
 use git_version::git_version;
 
+use crate::external::saved_game;
 use crate::screen::Screen;
 use crate::world::World;
 
 pub mod screen;
 pub mod world;
 pub mod external {
+    pub mod asset_manifest;
+    pub mod atlas;
+    pub mod audio;
     pub mod backends;
     pub mod basic_input;
+    pub mod cli;
+    pub mod embedded_assets;
+    pub mod export;
     // pub mod text_drawer;
     pub mod texture_drawer;
     // pub mod textureless_drawer;
     pub mod loader_stage;
+    pub mod locale;
+    pub mod save_slots;
+    pub mod saved_game;
+    pub mod settings;
+    pub mod sound_loader;
+    pub mod storage;
     pub mod texture_loader;
+    pub mod translation_loader;
     pub mod widgets {
         pub mod anchor;
         pub mod button;
+        pub mod chart;
         pub mod text;
+        pub mod text_input;
         pub mod texture_button;
+        pub mod tween;
     }
 }
 
@@ -25,8 +42,14 @@ pub const GIT_VERSION: &str = git_version!(args = ["--tags"]);
 
 /// returns if should continue looping. In other words, if there should be another future frame.
 pub fn frame(screen: &mut Screen, world: &mut World) -> bool {
-    let gui_actions = screen.get_gui_actions(world);
+    let mut gui_actions = screen.get_gui_actions(world);
+    if let Some(action) = gui_actions.slot_action.take() {
+        saved_game::apply_slot_action(world, action);
+    }
+    let (previous_act, previous_frame) = (world.stage(), world.frame);
     let should_continue = world.update(gui_actions);
+    let run_changed = world.stage() != previous_act || world.frame < previous_frame;
+    saved_game::save_if_needed(world, previous_frame, run_changed, !should_continue);
     screen.draw(world);
     should_continue
 }
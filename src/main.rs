@@ -1,7 +1,10 @@
 use macroquad::prelude::*;
 
+use double_clicker::external::asset_manifest::AssetError;
 use double_clicker::external::backends::{now, Seconds};
+use double_clicker::external::cli::{args, has_flag, CHECK_TRANSLATIONS_FLAG};
 use double_clicker::external::loader_stage::LoaderStage;
+use double_clicker::external::translation_loader::check_translation_files;
 use double_clicker::frame;
 
 const DEFAULT_WINDOW_WIDTH: i32 = 1200;
@@ -9,7 +12,15 @@ const DEFAULT_WINDOW_HEIGHT: i32 = 675;
 const DEFAULT_WINDOW_TITLE: &str = "Double Clicker";
 
 #[macroquad::main(window_conf)]
-async fn main() -> Result<(), FileError> {
+async fn main() -> Result<(), AssetError> {
+    if has_flag(&args(), CHECK_TRANSLATIONS_FLAG) {
+        let issues = check_translation_files(screen_width(), screen_height()).await?;
+        for issue in &issues {
+            println!("{}", issue);
+        }
+        println!("found {} issues in the translations", issues.len());
+        std::process::exit(if issues.is_empty() { 0 } else { 1 });
+    }
     let (mut screen, mut world) = LoaderStage::setup().await?;
     let mut previous_time = now();
     while frame(&mut screen, &mut world) {
@@ -1,12 +1,14 @@
 use crate::screen::drawer_trait::DrawerTrait;
-pub use crate::screen::gui_actions::GuiActions;
+pub use crate::screen::gui_actions::{GuiActions, SlotAction, SlotId};
 use crate::screen::input_source_trait::InputSourceTrait;
 use crate::world::World;
 
 pub mod drawer_trait;
 mod gui_actions;
 pub mod input_source_trait;
+pub mod number_format;
 pub mod textures;
+pub mod translation_check;
 pub mod translations;
 
 pub struct Screen {
@@ -1,5 +1,7 @@
-use crate::screen::GuiActions;
+use crate::screen::{GuiActions, SlotAction};
+use crate::world::config::WorldConfig;
 use crate::world::heores::Hero;
+use crate::world::history::HistoryWindow;
 use crate::world::World;
 
 pub enum Button {
@@ -9,12 +11,21 @@ pub enum Button {
     Restart,
     DebugFps,
     ExtraControls,
+    Stats,
+    Export,
+    History,
+    SaveSlots,
+    HistoryWindow(HistoryWindow),
     ContinuePlaying,
     ContinueAfterGameOver,
     Buy(Hero),
     Sell(Hero),
     ChangeLanguageToSpanish,
     ChangeLanguageToEnglish,
+    VolumeDown,
+    VolumeUp,
+    Mute,
+    Effects,
 }
 
 pub trait DrawerTrait {
@@ -23,6 +34,21 @@ pub trait DrawerTrait {
     /// Returns true if the button was pressed this frame
     fn button(&mut self, button: Button) -> bool;
 
+    /// Returns the config of the new game if the player started one this frame.
+    fn new_game(&mut self) -> Option<WorldConfig>;
+
+    /// Whether the world should wait, e.g. while the player chooses a new game.
+    fn is_paused(&self) -> bool;
+
+    /// Returns the name of the player if they confirmed one this frame.
+    fn player_name(&mut self) -> Option<String>;
+
+    /// Whether the keyboard is typing text, so that its keys are not shortcuts.
+    fn is_typing(&self) -> bool;
+
+    /// Returns what the player did to the save slots this frame.
+    fn slot_action(&mut self) -> Option<SlotAction>;
+
     fn apply_gui_actions(&mut self, gui_actions: &GuiActions);
 
     fn dirtiness(&self) -> f32;
@@ -1,13 +1,42 @@
 use std::collections::HashMap;
 
+use crate::world::config::WorldConfig;
 use crate::world::heores::Hero;
 
+/// Identifies a save slot. Ids are not reused after deleting a slot.
+pub type SlotId = i64;
+
+/// What the player did in the save slots screen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SlotAction {
+    /// Saves the current run and continues the one in the slot.
+    Load(SlotId),
+    /// Saves the current run and starts a new one in a new slot.
+    New,
+    Duplicate {
+        slot: SlotId,
+        name: String,
+    },
+    Delete(SlotId),
+    Rename {
+        slot: SlotId,
+        name: String,
+    },
+}
+
 pub struct GuiActions {
     pub quit: bool,
     pub clean_pressed: bool,
     pub dirty_pressed: bool,
     pub next_arrangement: bool,
     pub restart: bool,
+    /// Restarts with another config, chosen in the new-game screen.
+    pub new_game: Option<WorldConfig>,
+    /// The world doesn't advance, e.g. while choosing a new game.
+    pub paused: bool,
+    /// Chosen in the name-entry screen.
+    pub player_name: Option<String>,
+    pub slot_action: Option<SlotAction>,
     pub continue_playing: bool,
     pub heroes_bought: HashMap<Hero, bool>,
     pub heroes_sold: HashMap<Hero, bool>,
@@ -27,6 +56,10 @@ impl Default for GuiActions {
             dirty_pressed: false,
             next_arrangement: false,
             restart: false,
+            new_game: None,
+            paused: false,
+            player_name: None,
+            slot_action: None,
             continue_playing: false,
             heroes_bought: Default::default(),
             heroes_sold: Default::default(),
@@ -0,0 +1,249 @@
+//! Renders numbers the way each language writes them: thousands separators, decimal separators,
+//! short suffixes for big amounts, and where the currency symbol goes.
+
+use crate::external::backends::Seconds;
+use crate::screen::translations::Language;
+use crate::world::Cents;
+
+/// Below this, numbers are written in full; from this on, with a short suffix like 1.2M.
+const SHORT_FORMAT_THRESHOLD: u64 = 1_000_000;
+
+const CURRENCY_SYMBOL: &str = "€";
+
+struct NumberStyle {
+    thousands_separator: char,
+    decimal_separator: char,
+    /// Digits that the number needs above the first group to use a thousands separator. Spanish
+    /// writes 1000 but 10 000.
+    minimum_grouping_digits: usize,
+    suffix_separator: &'static str,
+    /// Suffixes for thousands, millions, and so on, up to what fits in an i64.
+    suffixes: [&'static str; 6],
+    currency_before_number: bool,
+}
+
+const SPANISH: NumberStyle = NumberStyle {
+    thousands_separator: '.',
+    decimal_separator: ',',
+    minimum_grouping_digits: 2,
+    suffix_separator: " ",
+    suffixes: ["mil", "M", "mil M", "B", "mil B", "Tr"],
+    currency_before_number: false,
+};
+
+const ENGLISH: NumberStyle = NumberStyle {
+    thousands_separator: ',',
+    decimal_separator: '.',
+    minimum_grouping_digits: 1,
+    suffix_separator: "",
+    suffixes: ["K", "M", "B", "T", "Qa", "Qi"],
+    currency_before_number: true,
+};
+
+fn style(language: Language) -> &'static NumberStyle {
+    match language {
+        Language::Spanish => &SPANISH,
+        Language::English => &ENGLISH,
+    }
+}
+
+/// All the digits, with thousands separators: 1,234,567 or 1.234.567.
+pub fn format_integer(number: i64, language: Language) -> String {
+    sign(number) + &group_digits(number.unsigned_abs(), style(language))
+}
+
+/// Keeps one truncated decimal after a suffix: 1.2K, 3.4M, or 1,2 mil, 3,4 M.
+pub fn format_short(number: i64, language: Language) -> String {
+    let style = style(language);
+    let magnitude = number.unsigned_abs();
+    let mut unit: u64 = 1;
+    let mut suffix = None;
+    for candidate in style.suffixes {
+        match unit.checked_mul(1000) {
+            Some(bigger_unit) if magnitude >= bigger_unit => {
+                unit = bigger_unit;
+                suffix = Some(candidate);
+            }
+            _ => break,
+        }
+    }
+    match suffix {
+        None => format_integer(number, language),
+        Some(suffix) => {
+            let whole = magnitude / unit;
+            let tenths = magnitude % unit / (unit / 10);
+            let mut text = sign(number) + &group_digits(whole, style);
+            if tenths != 0 {
+                text.push(style.decimal_separator);
+                text += &tenths.to_string();
+            }
+            text + style.suffix_separator + suffix
+        }
+    }
+}
+
+/// Writes small numbers in full and big ones with a suffix.
+pub fn format_number(number: i64, language: Language) -> String {
+    if number.unsigned_abs() < SHORT_FORMAT_THRESHOLD {
+        format_integer(number, language)
+    } else {
+        format_short(number, language)
+    }
+}
+
+/// Whole euros, abbreviated if big: 1,234 € or €1.2M.
+pub fn format_money(cents: Cents, language: Language) -> String {
+    add_currency(format_number(cents / 100, language), language)
+}
+
+/// Euros and cents, never abbreviated: 1.234.567,89 € or €1,234,567.89.
+pub fn format_money_exact(cents: Cents, language: Language) -> String {
+    let style = style(language);
+    let magnitude = cents.unsigned_abs();
+    let mut text = sign(cents) + &group_digits(magnitude / 100, style);
+    text.push(style.decimal_separator);
+    text += &format!("{:02}", magnitude % 100);
+    add_currency(text, language)
+}
+
+/// Hours, minutes and seconds like 1:02:03, or 2:03 below an hour. Fractions of second are dropped.
+pub fn format_duration(seconds: Seconds) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// A unix time as year, month, day, hours and minutes, like 2024-03-09 17:05, in UTC because the
+/// time zone is not available on every platform.
+pub fn format_date(unix_time: Seconds) -> String {
+    let total = unix_time.max(0.0) as i64;
+    let (days, seconds_of_day) = (total / 86400, total % 86400);
+    // civil date from days since 1970-01-01, from Howard Hinnant's date algorithms
+    let shifted = days + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60
+    )
+}
+
+fn sign(number: i64) -> String {
+    if number < 0 {
+        "-".to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn group_digits(magnitude: u64, style: &NumberStyle) -> String {
+    let digits = magnitude.to_string();
+    if digits.len() < 3 + style.minimum_grouping_digits {
+        return digits;
+    }
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i != 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(style.thousands_separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+fn add_currency(number: String, language: Language) -> String {
+    if style(language).currency_before_number {
+        match number.strip_prefix('-') {
+            Some(magnitude) => format!("-{}{}", CURRENCY_SYMBOL, magnitude),
+            None => format!("{}{}", CURRENCY_SYMBOL, number),
+        }
+    } else {
+        format!("{} {}", number, CURRENCY_SYMBOL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ES: Language = Language::Spanish;
+    const EN: Language = Language::English;
+
+    #[test]
+    fn test_thousands_separators() {
+        assert_eq!(format_integer(0, EN), "0");
+        assert_eq!(format_integer(999, EN), "999");
+        assert_eq!(format_integer(1000, EN), "1,000");
+        assert_eq!(format_integer(1000, ES), "1000");
+        assert_eq!(format_integer(10_000, ES), "10.000");
+        assert_eq!(format_integer(-1_234_567, EN), "-1,234,567");
+        assert_eq!(format_integer(-1_234_567, ES), "-1.234.567");
+        assert_eq!(format_integer(i64::MIN, EN), "-9,223,372,036,854,775,808");
+    }
+
+    #[test]
+    fn test_short_suffixes() {
+        assert_eq!(format_short(999, EN), "999");
+        assert_eq!(format_short(1_000, EN), "1K");
+        assert_eq!(format_short(1_299, EN), "1.2K");
+        assert_eq!(format_short(1_299, ES), "1,2 mil");
+        assert_eq!(format_short(3_456_789, EN), "3.4M");
+        assert_eq!(format_short(-3_456_789, ES), "-3,4 M");
+        assert_eq!(format_short(999_999_999, EN), "999.9M");
+        assert_eq!(format_short(2_000_000_000_000, EN), "2T");
+        assert_eq!(format_short(i64::MAX, EN), "9.2Qi");
+        assert_eq!(format_short(i64::MIN, ES), "-9,2 Tr");
+    }
+
+    #[test]
+    fn test_numbers_only_shortened_when_big() {
+        assert_eq!(format_number(54_321, EN), "54,321");
+        assert_eq!(format_number(999_999, ES), "999.999");
+        assert_eq!(format_number(1_000_000, ES), "1 M");
+    }
+
+    #[test]
+    fn test_money() {
+        assert_eq!(format_money(123_456, EN), "€1,234");
+        assert_eq!(format_money(123_456, ES), "1234 €");
+        assert_eq!(format_money(-250_000_000, EN), "-€2.5M");
+        assert_eq!(format_money_exact(123_456_789, ES), "1.234.567,89 €");
+        assert_eq!(format_money_exact(5, EN), "€0.05");
+        assert_eq!(format_money_exact(-105, EN), "-€1.05");
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(format_duration(0.0), "0:00");
+        assert_eq!(format_duration(59.9), "0:59");
+        assert_eq!(format_duration(61.0), "1:01");
+        assert_eq!(format_duration(3723.0), "1:02:03");
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(format_date(0.0), "1970-01-01 00:00");
+        assert_eq!(format_date(951_782_400.0), "2000-02-29 00:00");
+        assert_eq!(format_date(1_710_003_900.7), "2024-03-09 17:05");
+        assert_eq!(format_date(1_767_225_599.0), "2025-12-31 23:59");
+    }
+}
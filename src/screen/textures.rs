@@ -1,108 +1,171 @@
-use crate::external::backends::now;
-use macroquad::prelude::*;
-use macroquad::prelude::{load_texture, trace, FilterMode, Texture2D};
-use macroquad::ui::root_ui;
+use std::collections::HashMap;
 
-#[derive(Copy, Clone)]
+use macroquad::prelude::{draw_texture_ex, Color, DrawTextureParams, Rect, Texture2D, Vec2};
+
+/// The textures that the game draws. Which file each one is comes from the asset manifest, under
+/// the id of the texture.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Texture {
-    CleanBackground = 0,
-    CleanBackgroundOff = 1,
-    DirtyBackground = 2,
-    DirtyBackgroundOff = 3,
-    Hero1 = 4,
-    Villain1 = 5,
-    Hero2 = 6,
-    Villain2 = 7,
-    Hero3 = 8,
-    Villain3 = 9,
-    CleanFgBroom = 10,
-    CleanFgSpray = 11,
-    CleanFgSponge = 12,
-    DirtyFgFish = 13,
-    DirtyFgBanana = 14,
-    DirtyFgCigar = 15,
-    BackgroundPattern = 16,
-    BackgroundMargin = 17,
+    CleanBackground,
+    CleanBackgroundOff,
+    DirtyBackground,
+    DirtyBackgroundOff,
+    Hero1,
+    Villain1,
+    Hero2,
+    Villain2,
+    Hero3,
+    Villain3,
+    CleanFgBroom,
+    CleanFgSpray,
+    CleanFgSponge,
+    DirtyFgFish,
+    DirtyFgBanana,
+    DirtyFgCigar,
+    BackgroundPattern,
+    BackgroundMargin,
 }
 
-pub struct Textures {
-    inner: Vec<Texture2D>,
-}
-impl Textures {
-    pub fn new(textures: Vec<Texture2D>) -> Self {
-        Self { inner: textures }
+const TEXTURES_LIST: [Texture; 18] = [
+    Texture::CleanBackground,
+    Texture::CleanBackgroundOff,
+    Texture::DirtyBackground,
+    Texture::DirtyBackgroundOff,
+    Texture::Hero1,
+    Texture::Villain1,
+    Texture::Hero2,
+    Texture::Villain2,
+    Texture::Hero3,
+    Texture::Villain3,
+    Texture::CleanFgBroom,
+    Texture::CleanFgSpray,
+    Texture::CleanFgSponge,
+    Texture::DirtyFgFish,
+    Texture::DirtyFgBanana,
+    Texture::DirtyFgCigar,
+    Texture::BackgroundPattern,
+    Texture::BackgroundMargin,
+];
+
+impl Texture {
+    pub fn list() -> &'static [Texture] {
+        &TEXTURES_LIST
     }
-    pub fn get(&self, texture: Texture) -> Texture2D {
-        self.inner[texture as usize]
+
+    /// The id in the asset manifest.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Texture::CleanBackground => "clean_background",
+            Texture::CleanBackgroundOff => "clean_background_off",
+            Texture::DirtyBackground => "dirty_background",
+            Texture::DirtyBackgroundOff => "dirty_background_off",
+            Texture::Hero1 => "hero_1",
+            Texture::Villain1 => "villain_1",
+            Texture::Hero2 => "hero_2",
+            Texture::Villain2 => "villain_2",
+            Texture::Hero3 => "hero_3",
+            Texture::Villain3 => "villain_3",
+            Texture::CleanFgBroom => "clean_fg_broom",
+            Texture::CleanFgSpray => "clean_fg_spray",
+            Texture::CleanFgSponge => "clean_fg_sponge",
+            Texture::DirtyFgFish => "dirty_fg_fish",
+            Texture::DirtyFgBanana => "dirty_fg_banana",
+            Texture::DirtyFgCigar => "dirty_fg_cigar",
+            Texture::BackgroundPattern => "background_pattern",
+            Texture::BackgroundMargin => "background_margin",
+        }
     }
 }
-impl Default for Textures {
-    fn default() -> Self {
-        Self::new(Vec::new())
+
+/// A loaded image: a whole texture, or the part of an atlas where it was packed.
+#[derive(Copy, Clone, Debug)]
+pub struct Sprite {
+    pub texture: Texture2D,
+    /// In pixels of the texture.
+    pub source: Rect,
+}
+
+impl Sprite {
+    pub fn whole(texture: Texture2D) -> Self {
+        let source = Rect::new(0.0, 0.0, texture.width(), texture.height());
+        Self { texture, source }
+    }
+
+    /// Of the original image.
+    pub fn width(&self) -> f32 {
+        self.source.w
+    }
+
+    pub fn height(&self) -> f32 {
+        self.source.h
     }
+
+    /// Stretches the sprite to `dest_size` pixels, with its top left corner at `x`, `y`.
+    pub fn draw(&self, x: f32, y: f32, color: Color, dest_size: Vec2) {
+        self.draw_ex(x, y, color, dest_size, 0.0, false);
+    }
+
+    /// Like `draw`, also turning it `rotation` radians around its center, and mirroring it
+    /// horizontally if `flip_x`.
+    pub fn draw_ex(
+        &self,
+        x: f32,
+        y: f32,
+        color: Color,
+        dest_size: Vec2,
+        rotation: f32,
+        flip_x: bool,
+    ) {
+        draw_texture_ex(
+            self.texture,
+            x,
+            y,
+            color,
+            DrawTextureParams {
+                dest_size: Some(dest_size),
+                source: Some(self.source),
+                rotation,
+                flip_x,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// The loaded textures by id.
+#[derive(Default)]
+pub struct Textures {
+    inner: HashMap<String, Sprite>,
+    /// Paths of the files that couldn't be loaded, which show a placeholder instead.
+    missing: Vec<String>,
 }
 
-pub async fn load_textures() -> Vec<Texture2D> {
-    let start = now();
-    trace!("before loading at {}", start);
-    let mut textures = Vec::new();
-    let texture_paths = [
-        "assets/images/buttons/buttonBLU-256-yes.png",
-        "assets/images/buttons/buttonBLU-256-no.png",
-        "assets/images/buttons/buttonPUR-256-yes.png",
-        "assets/images/buttons/buttonPUR-256-no.png",
-        "assets/images/characters/heroe1.png",
-        "assets/images/characters/villano1.png",
-        "assets/images/characters/heroe2.png",
-        "assets/images/characters/villano2.png",
-        "assets/images/characters/heroe3.png",
-        "assets/images/characters/villano3.png",
-        "assets/images/buttons/buttonBLU256-1.png",
-        "assets/images/buttons/buttonBLU256-2.png",
-        "assets/images/buttons/buttonBLU256-3.png",
-        "assets/images/buttons/buttonPUR256-1.png",
-        "assets/images/buttons/buttonPUR256-2.png",
-        "assets/images/buttons/buttonPUR256-3.png",
-        "assets/images/background/BGpatronsmol.png",
-        "assets/images/background/BGmargin.png",
-    ];
-    for (i, path) in texture_paths.iter().enumerate() {
-        let t = load_texture(path).await.unwrap();
-        t.set_filter(FilterMode::Linear);
-        textures.push(t);
-
-        clear_background(LIGHTGRAY);
-        root_ui().label(None, &format!("Loading... ({}/{})", i, texture_paths.len()));
-        trace!("painted frame of loading screen");
-        next_frame().await;
+impl Textures {
+    pub fn new(textures: HashMap<String, Sprite>, missing: Vec<String>) -> Self {
+        Self {
+            inner: textures,
+            missing,
+        }
+    }
+
+    /// The loader checks that every `Texture` is in the manifest, so this only draws nothing if a
+    /// `Textures` was built without checking.
+    pub fn get(&self, texture: Texture) -> Sprite {
+        self.get_by_id(texture.id())
+            .unwrap_or_else(|| Sprite::whole(Texture2D::empty()))
     }
 
-    // TODO: remove this after testing the loading screen
-    textures.clear();
-    for (i, path) in texture_paths.iter().enumerate() {
-        let t = load_texture(path).await.unwrap();
-        t.set_filter(FilterMode::Linear);
-        textures.push(t);
-
-        clear_background(LIGHTGRAY);
-        root_ui().label(None, &format!("Loading... ({}/{})", i, texture_paths.len()));
-        trace!("painted frame of loading screen");
-        next_frame().await;
+    /// Replaces the sprite of `id`, or adds it.
+    pub fn insert(&mut self, id: &str, sprite: Sprite) {
+        self.inner.insert(id.to_string(), sprite);
     }
-    textures.clear();
-    for (i, path) in texture_paths.iter().enumerate() {
-        let t = load_texture(path).await.unwrap();
-        t.set_filter(FilterMode::Linear);
-        textures.push(t);
-
-        clear_background(LIGHTGRAY);
-        root_ui().label(None, &format!("Loading... ({}/{})", i, texture_paths.len()));
-        trace!("painted frame of loading screen");
-        next_frame().await;
+
+    /// Textures that the game doesn't know about, which only the manifest lists.
+    pub fn get_by_id(&self, id: &str) -> Option<Sprite> {
+        self.inner.get(id).copied()
     }
-    //TODO: until here
 
-    let end = now();
-    trace!("after loading at {}, took {:.3}s", end, end - start);
-    textures
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
 }
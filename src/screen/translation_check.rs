@@ -0,0 +1,369 @@
+//! Finds mistakes in the translation files by comparing every language with each other, and by
+//! measuring the texts that have to fit in a widget of fixed width.
+
+use std::fmt::{Display, Formatter};
+
+use crate::external::widgets::text::Pixels;
+use crate::screen::translations::{Language, Messages, Translation};
+use crate::world::acts::Act;
+
+/// The default font of macroquad (ProggyClean) is monospaced, and each letter is about this many
+/// times the font size.
+const APPROX_LETTER_WIDTH: f32 = 0.54;
+
+const LORE_PREFIXES: &[(Act, &str)] = &[
+    (Act::Act1, "lore.act_1"),
+    (Act::Act2, "lore.act_2"),
+    (Act::Act3, "lore.act_3"),
+    (Act::GameOver, "lore.game_over"),
+    (Act::GameWon, "lore.game_won"),
+];
+
+/// Maximum width that the texts of some message ids can take without overflowing their widget.
+pub struct TextWidthLimit {
+    /// Applies to the id `prefix` and to every id starting with `<prefix>.`
+    pub prefix: &'static str,
+    pub max_width: Pixels,
+    pub font_size: f32,
+}
+
+impl TextWidthLimit {
+    fn applies_to(&self, id: &str) -> bool {
+        id == self.prefix
+            || (id.starts_with(self.prefix) && id[self.prefix.len()..].starts_with('.'))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TranslationIssue {
+    Missing {
+        language: Language,
+        id: String,
+    },
+    Untranslated {
+        language: Language,
+        same_as: Language,
+        id: String,
+    },
+    Placeholders {
+        language: Language,
+        id: String,
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    LoreLength {
+        language: Language,
+        act: Act,
+        expected: usize,
+        found: usize,
+    },
+    TooLong {
+        language: Language,
+        id: String,
+        width: Pixels,
+        max_width: Pixels,
+    },
+}
+
+impl Display for TranslationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranslationIssue::Missing { language, id } => {
+                write!(f, "{}: {} is missing", language.code(), id)
+            }
+            TranslationIssue::Untranslated {
+                language,
+                same_as,
+                id,
+            } => write!(
+                f,
+                "{}: {} is identical to {}, is it translated?",
+                language.code(),
+                id,
+                same_as.code()
+            ),
+            TranslationIssue::Placeholders {
+                language,
+                id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: {} has placeholders {:?} but expected {:?}",
+                language.code(),
+                id,
+                found,
+                expected
+            ),
+            TranslationIssue::LoreLength {
+                language,
+                act,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: lore of {:?} has {} sentences but expected {}",
+                language.code(),
+                act,
+                found,
+                expected
+            ),
+            TranslationIssue::TooLong {
+                language,
+                id,
+                width,
+                max_width,
+            } => write!(
+                f,
+                "{}: {} is {:.0} pixels wide but its widget only fits {:.0}",
+                language.code(),
+                id,
+                width,
+                max_width
+            ),
+        }
+    }
+}
+
+/// The first language is the reference for placeholders and lore lengths, and the others are
+/// compared against it. `measure_text` returns the width of a line of text at a font size.
+pub fn check_translations<F>(
+    translations: &[(Language, Messages)],
+    limits: &[TextWidthLimit],
+    measure_text: &F,
+) -> Vec<TranslationIssue>
+where
+    F: Fn(&str, f32) -> Pixels,
+{
+    let mut issues = Vec::new();
+    for (language, messages) in translations {
+        issues.extend(
+            Translation::missing_ids(*language, messages)
+                .into_iter()
+                .map(|id| TranslationIssue::Missing {
+                    language: *language,
+                    id,
+                }),
+        );
+        issues.extend(check_widths(*language, messages, limits, measure_text));
+    }
+    if let Some(((reference_language, reference), others)) = translations.split_first() {
+        for (language, messages) in others {
+            issues.extend(check_untranslated(
+                *reference_language,
+                reference,
+                *language,
+                messages,
+            ));
+            issues.extend(check_placeholders(reference, *language, messages));
+            issues.extend(check_lore_lengths(reference, *language, messages));
+        }
+    }
+    issues
+}
+
+pub fn estimate_text_width(text: &str, font_size: f32) -> Pixels {
+    text.chars().count() as f32 * font_size * APPROX_LETTER_WIDTH
+}
+
+fn sorted_ids(messages: &Messages) -> Vec<&String> {
+    let mut ids = messages.keys().collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+fn check_untranslated(
+    reference_language: Language,
+    reference: &Messages,
+    language: Language,
+    messages: &Messages,
+) -> Vec<TranslationIssue> {
+    sorted_ids(messages)
+        .into_iter()
+        .filter(|id| reference.get(*id) == messages.get(*id))
+        .map(|id| TranslationIssue::Untranslated {
+            language,
+            same_as: reference_language,
+            id: id.clone(),
+        })
+        .collect()
+}
+
+fn check_placeholders(
+    reference: &Messages,
+    language: Language,
+    messages: &Messages,
+) -> Vec<TranslationIssue> {
+    let mut issues = Vec::new();
+    for id in sorted_ids(messages) {
+        if let Some(reference_text) = reference.get(id) {
+            let expected = placeholders(reference_text);
+            let found = placeholders(&messages[id]);
+            if expected != found {
+                issues.push(TranslationIssue::Placeholders {
+                    language,
+                    id: id.clone(),
+                    expected,
+                    found,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Returns the sorted `{placeholder}`s of a text.
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut placeholders = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        match rest[start..].find('}') {
+            Some(length) => {
+                placeholders.push(rest[start..=start + length].to_string());
+                rest = &rest[start + length + 1..];
+            }
+            None => break,
+        }
+    }
+    placeholders.sort();
+    placeholders
+}
+
+fn check_lore_lengths(
+    reference: &Messages,
+    language: Language,
+    messages: &Messages,
+) -> Vec<TranslationIssue> {
+    let count = |messages: &Messages, prefix: &str| {
+        (1..)
+            .take_while(|i| messages.contains_key(&format!("{}.{}", prefix, i)))
+            .count()
+    };
+    LORE_PREFIXES
+        .iter()
+        .filter_map(|(act, prefix)| {
+            let expected = count(reference, prefix);
+            let found = count(messages, prefix);
+            (expected != found).then_some(TranslationIssue::LoreLength {
+                language,
+                act: *act,
+                expected,
+                found,
+            })
+        })
+        .collect()
+}
+
+fn check_widths<F>(
+    language: Language,
+    messages: &Messages,
+    limits: &[TextWidthLimit],
+    measure_text: &F,
+) -> Vec<TranslationIssue>
+where
+    F: Fn(&str, f32) -> Pixels,
+{
+    let mut issues = Vec::new();
+    for id in sorted_ids(messages) {
+        for limit in limits.iter().filter(|limit| limit.applies_to(id)) {
+            let width = messages[id]
+                .lines()
+                .map(|line| measure_text(line, limit.font_size))
+                .fold(0.0, f32::max);
+            if width > limit.max_width {
+                issues.push(TranslationIssue::TooLong {
+                    language,
+                    id: id.clone(),
+                    width,
+                    max_width: limit.max_width,
+                });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::texture_drawer::TextureDrawer;
+    use crate::screen::translations::parse_messages;
+
+    fn shipped_translations() -> Vec<(Language, Messages)> {
+        Language::list()
+            .iter()
+            .map(|language| {
+                let messages = parse_messages(language.compiled_in_translation());
+                (*language, messages)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shipped_translations_are_consistent() {
+        // the biggest 16:9 window that still uses the minimum font size
+        let limits = TextureDrawer::text_width_limits(1599.0, 899.0);
+        let issues = check_translations(&shipped_translations(), &limits, &estimate_text_width);
+        let issues = issues.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(issues, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_detect_issues() {
+        let mut translations = shipped_translations();
+        let spanish_restart = translations[0].1["restart"].clone();
+        let (english, messages) = &mut translations[1];
+        let english = *english;
+        messages.remove("buy");
+        messages.insert("restart".to_string(), spanish_restart);
+        messages.insert("sell".to_string(), "Sell {count}".to_string());
+        messages.remove("lore.act_2.8");
+        let limits = [TextWidthLimit {
+            prefix: "name",
+            max_width: estimate_text_width("Dirt Lord", 10.0),
+            font_size: 10.0,
+        }];
+
+        let issues = check_translations(&translations, &limits, &estimate_text_width);
+
+        let missing_buy = TranslationIssue::Missing {
+            language: english,
+            id: "buy".to_string(),
+        };
+        assert!(issues.contains(&missing_buy));
+        let untranslated = TranslationIssue::Untranslated {
+            language: english,
+            same_as: Language::Spanish,
+            id: "restart".to_string(),
+        };
+        assert!(issues.contains(&untranslated));
+        let placeholders = TranslationIssue::Placeholders {
+            language: english,
+            id: "sell".to_string(),
+            expected: vec![],
+            found: vec!["{count}".to_string()],
+        };
+        assert!(issues.contains(&placeholders));
+        let lore = TranslationIssue::LoreLength {
+            language: english,
+            act: Act::Act2,
+            expected: 8,
+            found: 7,
+        };
+        assert!(issues.contains(&lore));
+        let too_long = |issue: &&TranslationIssue| matches!(issue, TranslationIssue::TooLong { id, .. } if id == "name.hero_1");
+        assert_eq!(issues.iter().filter(too_long).count(), 2);
+        let fits = |issue: &&TranslationIssue| {
+            matches!(issue, TranslationIssue::TooLong { id, language, .. }
+                if id == "name.villain_3" && *language == english)
+        };
+        assert_eq!(issues.iter().filter(fits).count(), 0);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders("no placeholders"), Vec::<String>::new());
+        assert_eq!(placeholders("{b} and {a}"), vec!["{a}", "{b}"]);
+        assert_eq!(placeholders("unclosed {a"), Vec::<String>::new());
+    }
+}
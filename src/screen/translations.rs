@@ -1,252 +1,693 @@
-#[derive(Copy, Clone)]
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use macroquad::prelude::warn;
+
+pub use crate::screen::translations::message::{Message, PluralCategory};
+use crate::world::acts::Act;
+use crate::world::config::{ConfigField, Difficulty, GameMode};
+use crate::world::high_scores::Category;
+use crate::world::history::HistoryWindow;
+use crate::world::stats::Stats;
+
+mod message;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Language {
     Spanish,
     English,
 }
 
+const LANGUAGES: [Language; 2] = [Language::Spanish, Language::English];
+
+impl Language {
+    pub fn list() -> &'static [Language] {
+        &LANGUAGES
+    }
+
+    /// ISO 639-1 code, as used in `--lang`, the settings file and locales like "en_US.UTF-8".
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Spanish => "es",
+            Language::English => "en",
+        }
+    }
+
+    /// Accepts bare codes ("es") and locales from the OS or browser ("es_ES.UTF-8", "en-US").
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match code.as_str() {
+            "es" => Some(Language::Spanish),
+            "en" => Some(Language::English),
+            _ => None,
+        }
+    }
+
+    /// Which plural form to use for a `{count}`, following the CLDR rules for integers.
+    pub fn plural_category(&self, count: i64) -> PluralCategory {
+        match self {
+            Language::Spanish | Language::English => {
+                if count == 1 {
+                    PluralCategory::One
+                } else {
+                    PluralCategory::Other
+                }
+            }
+        }
+    }
+
+    /// Where the loader stage looks for the translation file of this language.
+    pub fn translation_path(&self) -> String {
+        format!("assets/translations/{}.txt", self.code())
+    }
+
+    /// The translation file as it was at compile time, used if the file can't be loaded at runtime
+    /// or lacks some message.
+    pub fn compiled_in_translation(&self) -> &'static str {
+        match self {
+            Language::Spanish => include_str!("../../assets/translations/es.txt"),
+            Language::English => include_str!("../../assets/translations/en.txt"),
+        }
+    }
+}
+
 pub struct Translation {
-    pub restart: &'static str,
-    pub continue_playing: &'static str,
-    pub buy: &'static str,
-    pub sell: &'static str,
-    pub change_style: &'static str,
-    pub cleanings: &'static str,
-    pub cleaning: &'static str,
-    pub dirtyings: &'static str,
-    pub dirtying: &'static str,
-    pub you_hired: &'static str,
-    pub investing: &'static str,
-    pub producing: &'static str,
-    pub per_second: &'static str,
-    pub price: &'static str,
-    pub over_greedy: &'static str,
-    pub owned_by_dirt: &'static str,
-    pub you_won: &'static str,
-    pub retire: &'static str,
-    pub you_can_continue_playing: &'static str,
-    pub savings: &'static str,
-    pub cleaning_speed: &'static str,
-    pub dirtying_speed: &'static str,
-    pub dirts: &'static str,
-    pub description: CharacterText,
+    /// Also decides how numbers are written, see `number_format`.
+    pub language: Language,
+    pub restart: String,
+    pub continue_playing: String,
+    pub buy: String,
+    pub sell: String,
+    pub change_style: String,
+    pub cleaning: Message,
+    pub dirtying: Message,
+    pub you_hired: Message,
+    pub producing: ProductionMessages,
+    pub price: Message,
+    pub over_greedy: String,
+    pub owned_by_dirt: String,
+    pub you_won: String,
+    pub retire: String,
+    pub you_can_continue_playing: String,
+    pub time_left: Message,
+    pub time_up: String,
+    pub time_up_savings: Message,
+    pub endless_lasted: Message,
+    pub zen_won: String,
+    pub high_score: HighScoreMessages,
+    pub savings: Message,
+    pub cleaning_speed: Message,
+    pub dirtying_speed: Message,
+    pub dirts: Message,
+    pub description: ProductionMessages,
     pub long_description: CharacterText,
     pub name: CharacterText,
     pub lore: Lore,
     pub alerts: AlertMessages,
-    pub extra_controls: &'static str,
+    pub extra_controls: String,
+    pub export: String,
+    pub exported: Message,
+    pub export_failed: Message,
+    pub missing_assets: Message,
+    pub missing_assets_more: Message,
+    pub stats: StatsMessages,
+    pub history: HistoryMessages,
+    pub new_game: NewGameMessages,
+    pub leaderboard: LeaderboardMessages,
+    pub name_entry: NameEntryMessages,
+    pub slots: SlotsMessages,
+    pub sound: SoundMessages,
+    pub effects: EffectsMessages,
 }
 
 pub struct Lore {
-    pub act_1: &'static [&'static str],
-    pub act_2: &'static [&'static str],
-    pub act_3: &'static [&'static str],
-    pub game_over: &'static [&'static str],
-    pub game_won: &'static [&'static str],
+    pub act_1: Vec<String>,
+    pub act_2: Vec<String>,
+    pub act_3: Vec<String>,
+    pub game_over: Vec<String>,
+    pub game_won: Vec<String>,
+}
+
+/// Messages with a `{count}` of tasks, with a version for heroes and one for villains.
+pub struct ProductionMessages {
+    pub cleaning: Message,
+    pub dirtying: Message,
 }
 
 pub struct CharacterText {
-    pub hero_1: &'static str,
-    pub villain_1: &'static str,
-    pub hero_2: &'static str,
-    pub villain_2: &'static str,
-    pub hero_3: &'static str,
-    pub villain_3: &'static str,
+    pub hero_1: String,
+    pub villain_1: String,
+    pub hero_2: String,
+    pub villain_2: String,
+    pub hero_3: String,
+    pub villain_3: String,
 }
 pub struct AlertMessages {
-    pub inefficient_cleaners: &'static str,
-    pub cannot_clean: &'static str,
-    pub insufficient_money: &'static str,
-    pub cannot_sell: &'static str,
-    pub too_dirty: &'static str,
-}
-
-const SPANISH: Translation = Translation {
-    restart: "Reiniciar",
-    continue_playing: "Continuar jugando",
-    buy: "Comprar",
-    sell: "Vender",
-    change_style: "Cambiar estilo",
-    cleanings: "limpiezas",
-    cleaning: "Limpiando",
-    dirtyings: "suciedades",
-    dirtying: "Ensuciando",
-    you_hired: "Has contratado",
-    investing: "invirtiendo",
-    producing: "Produciendo",
-    per_second: "por segundo",
-    price: "Precio",
-    over_greedy: "Te has pasado de avaricioso.",
-    owned_by_dirt: "La suciedad se ha apoderado de ti.",
-    you_won: "Has ganado!",
-    retire: "Tienes bastante dinero para jubilarte.",
-    you_can_continue_playing: "Puedes seguir jugando si quieres.",
-    savings: "Ahorros",
-    cleaning_speed: "Velocidad de limpieza",
-    dirtying_speed: "Velocidad de ensuciamiento",
-    dirts: "Suciedades",
-    description : CharacterText {
-        hero_1: "Hace 1 tarea de limpieza por segundo",
-        villain_1: "Hace 2 tareas de ensuciar por segundo",
-        hero_2: "Hace 10 tareas de limpieza por segundo",
-        villain_2: "Hace 21 tareas de ensuciar por segundo",
-        hero_3: "Hace 100 tareas de limpieza por segundo",
-        villain_3: "Hace 221 tareas de ensuciar por segundo",
-    },
-    long_description : CharacterText {
-        hero_1: "El maestro del cepillo, el genio de la limpieza. Siempre encuentra la solución más efectiva.",
-        villain_1: "Un alborotador profesional, experto en ingerir líquidos sospechosos.",
-        hero_2: "Obsesionado con la limpieza, no hay mancha que pueda resistírsele. No sería quien es de no ser por su hermano: Harry el sucio.",
-        villain_2: "Sus mejores amigos: caos y confusión. Siempre buscando formas nuevas de ensuciar.",
-        hero_3: "La mayor IA jamás creada para la limpieza. Programada para la perfección (aunque se haya programado en java).",
-        villain_3: "El insaciable líder supremo de la suciedad. Nacido de la probeta más sucia escondida en el laboratorio más mugriento.",
-    },
-    name: CharacterText {
-        hero_1: "Técnico Operario de Cepillo",
-        villain_1: "Sucio Steve",
-        hero_2: "Larry el Limpio",
-        villain_2: "Caos Adora",
-        hero_3: "Aspiradora Autónoma Andy",
-        villain_3: "Lord de la Mugre",
-    },
-    lore: Lore {
-        act_1: &[
-            "Tu jefe quiere hablar contigo, está contento.\nDemasiado contento.",
-            "\"¿Cómo que despedido?\"\n - Malik",
-            "Tu departamento va a desaparecer, debes hacer algo.",
-            "\"Debo encontrar 'Los Trapos Sucios'.\"\n - Malik",
-            "\"Si pudiese librarme de la limpieza.\"\n - Malik",
-            "\"¿Me dejas chupar ese moho?\"\n - Sucio Steve",
-            "\"El jugo de contenedor es el mejor despertador.\"\n - Sucio Steve",
-            "\"Es mi turno para brillar.\"\n - Técnico Operario de Cepillo",
-            "\"El Cepillo es mi micrófono, las calles son el escenario. Que empiece la función.\"\n - Técnico Operario de Cepillo",
-        ],
-        act_2: &[
-            "Desconfías de Sucio Steve y le sigues a través de una alcantarilla.",
-            "\"Algo se está gestando en ese laboratorio, y no huele bien precisamente.\"\n - Malik",
-            "\"Esto no me gusta nada, debo volver para activar a triple A.\"\n - Malik",
-            "\"¿Crees que habrá suficiente compuesto, Bata Negra?\"\n - Caos Adora",
-            "\"El candidato perfecto eres tú, Sucio Steve. Serás capaz de soportar el compuesto.\"\n - Bata Negra",
-            "\"Me recuerda al sabor de las patatas maduras con gusanos.\"\n - Sucio Steve",
-            "\"¿Triple A? Nunca hemos tenido que recurrir a eso, deje que me encargue yo jefe.\"\n - Larry el Limpio",
-            "\"El caos es como una cucaracha. Si la aplastas solo ensuciarás, y si aplastas a la equivocada salen más y más.\"\n - Caos Adora",
-        ],
-        act_3: &[
-            "\"Gracias a esos locos por fin podré expandirme, comprar todos los servicios de limpieza del mundo.\"\n - Malik",
-            "\"No hay suciedad que por limpieza no venga.\"\n - Malik",
-            "\"La ciudad está en equilibrio. Lord de la Mugre y Triple A son incansables.\"\n - Malik",
-            "\"La perfección es una mentira que la limpieza nos hace creer.\nEn mi mundo, celebramos nuestas imperfecciones, nuestras manchas, nuestras sombras.\"\n - Lord de la Mugre",
-            "\"La igualdad llega a través de la suciedad. No hay juicio por la apariencia; todos somos igualmente sucios.\"\n - Lord de la Mugre",
-            "\"He descifrado los patrones de la suciedad, su forma de bailar. Soy el epítome de la eficiencia, el castigo del polvo.\"\n - Triple A",
-            "\"Soy la ecuación impecable, la solución a la impureza, el arquitecto de la pulcritud.\"\n - Triple A",
-        ],
-        game_over: &["Todo se acaba, excepto la suciedad."],
-        game_won: &["Contra todo pronóstico, te has salido con la tuya."],
-    },
-    alerts: AlertMessages {
-        inefficient_cleaners: "Tienes limpiadores sin suficiente suciedad que limpiar",
-        cannot_clean: "No se puede limpiar si no hay nada sucio",
-        insufficient_money: "No tienes suficiente dinero para comprar esto",
-        cannot_sell: "No puedes vender porque tienes 0 unidades",
-        too_dirty: "¡Si no hay nada limpio te despedirán!",
-    },
-    extra_controls: "Controles extra",
-};
-
-const ENGLISH: Translation = Translation {
-    restart: "Restart",
-    continue_playing: "Continue playing",
-    buy: "Buy",
-    sell: "Sell",
-    change_style: "Change Style",
-    cleanings: "cleanings",
-    cleaning: "Cleaning",
-    dirtyings: "dirtyings",
-    dirtying: "Dirtying",
-    you_hired: "You hired",
-    investing: "investing",
-    producing: "Producing",
-    per_second: "per second",
-    price: "Price",
-    over_greedy: "You were too greedy.",
-    owned_by_dirt: "The dirt owns you now.",
-    you_won: "Has ganado!",
-    retire: "You earned enough money to retire.",
-    you_can_continue_playing: "You can continue playing if you want.",
-    savings: "Savings",
-    cleaning_speed: "Cleaning speed",
-    dirtying_speed: "Dirtying speed",
-    dirts: "Dirtiness",
-    description: CharacterText {
-        hero_1: "Completes 1 cleaning task per second",
-        villain_1: "Completes 2 dirtying tasks per second",
-        hero_2: "Completes 10 cleaning tasks per second",
-        villain_2: "Completes 21 dirtying tasks per second",
-        hero_3: "Completes 100 cleaning tasks per second",
-        villain_3: "Completes 221 dirtying tasks per second",
-    },
-    long_description: CharacterText {
-        hero_1: "The brush master, cleaning genius. He always finds the most effective solution.",
-        villain_1: "A professional troublemaker, an expert in consuming suspicious liquids.",
-        hero_2: "Obsessed with cleanliness, no stain can stand to him. He wouldn't be who he is without his brother: Dirty Derek.",
-        villain_2: "Her best friends: chaos and confusion. Always looking for new ways to get dirty.",
-        hero_3: "The greatest AI ever created for cleaning. Programmed for perfection (even though it's written in Java).",
-        villain_3: "The insatiable supreme leader of filth. Born from the dirtiest test tube hidden in the grimiest laboratory.",
-    },
-    name: CharacterText {
-        hero_1: "Operator of Cleaning Devices",
-        villain_1: "Dirty Derek",
-        hero_2: "Clean Carl",
-        villain_2: "Chaos Adora",
-        hero_3: "Autonomous Absterging Andy",
-        villain_3: "Dirt Lord",
-    },
-    lore: Lore {
-        act_1: &[
-            "Your boss wants to talk to you. He's happy. Too happy.",
-            "\"Fired? Me?\"\n - Malik",
-            "They are making your department redundant. You must do something.",
-            "\"I must find 'The Dirty Rags'.\"\n - Malik",
-            "\"If only I could get rid of the cleanliness.\"\n - Malik",
-            "\"¿Can I lick that mold?\"\n - Dirty Derek",
-            "\"Trash juice is the best breakfast.\"\n - Dirty Derek",
-            "\"It's my turn to shine.\"\n - Operator of Cleaning Devices",
-            "\"The brush is my microphone, the streets are my stage. Let the show begin.\"\n - Operator of Cleaning Devices",
-        ],
-        act_2: &[
-            "You don't trust Dirty Derek so you follow him through a sewer.",
-            "\"Something is brewing in that lab, and it doesn't smell good.\"\n - Malik",
-            "\"I don't like this at all, I must come back and activate Triple A.\"\n - Malik",
-            "\"Do you think there will be enough compound, Black Coat?\"\n - Chaos Adora",
-            "\"You are the perfect candidate, Dirty Derek. You'll be able to withstand the compound.\"\n - Black Coat",
-            "\"It reminds me of the taste of ripe, wormy potatoes.\"\n - Dirty Derek",
-            "\"Triple A? We never had to resort to that, let me take care of it, boss.\"\n - Clean Carl",
-            "\"Chaos is like a cockroach.\nIf you crush it you'll only make a mess, and if you crush the wrong one, more and more come out.\"\n - Chaos Adora",
-        ],
-        act_3: &[
-            "\"Thanks to these crazy people, I will finally be able to expand, buy all the cleaning services in the world.\"\n - Malik",
-            "\"Every stain hides a pristine gain.\"\n - Malik",
-            "\"The city is in balance. The Dirt Lord and Triple A are tireless.\"\n - Malik",
-            "\"Perfection is a lie that cleanliness makes us believe. In my world, we celebrate our imperfections, our stains, our shadows.\"\n - Dirt Lord",
-            "\"Equality comes through dirtiness. There is no judgment by appearance; we are all equally dirty.\"\n - Dirt Lord",
-            "\"I deciphered the patterns of dirt, its way of dancing. I am the epitome of efficiency, the punishment of dust.\"\n - Triple A",
-            "\"I'm the dapper equation, the solution to impurity, the architect of neatness.\"\n - Triple A",
-        ],
-        game_over: &["Everything is finite, except dirtiness."],
-        game_won: &["Against all odds, you got away with it."],
-    },
-    alerts: AlertMessages {
-        inefficient_cleaners: "Your cleaners don't have enough dirt to clean",
-        cannot_clean: "You can not clean is there is nothing dirty",
-        insufficient_money: "You don't have enough money to buy this",
-        cannot_sell: "You can not sell this because you have 0 units",
-        too_dirty: "You will be fired if nothing is clean!",
-    },
-    extra_controls: "Extra controls",
-};
-
-pub fn get_translation(language: Language) -> &'static Translation {
-    match language {
-        Language::Spanish => &SPANISH,
-        Language::English => &ENGLISH,
+    pub inefficient_cleaners: String,
+    pub cannot_clean: String,
+    pub insufficient_money: String,
+    pub cannot_sell: String,
+    pub too_dirty: String,
+}
+
+pub struct StatsMessages {
+    pub title: String,
+    pub current_run: String,
+    pub lifetime: String,
+    pub clean_clicks: String,
+    pub dirty_clicks: String,
+    /// Label of the units bought and sold of the character `{name}`.
+    pub hired_and_fired: Message,
+    pub money_spent: String,
+    pub peak_dirtiness: String,
+    /// One label for each of `Stats::played_acts`.
+    pub time_in_act: Vec<(Act, String)>,
+    pub game_overs: String,
+    /// Label of the runs started on `{difficulty}`.
+    pub games: Message,
+}
+
+pub struct HistoryMessages {
+    pub title: String,
+    pub last_minute: String,
+    pub last_10_minutes: String,
+    pub whole_run: String,
+    pub money: String,
+    pub dirtiness: String,
+    pub speeds: String,
+}
+
+impl HistoryMessages {
+    pub fn window(&self, window: HistoryWindow) -> &str {
+        match window {
+            HistoryWindow::LastMinute => &self.last_minute,
+            HistoryWindow::Last10Minutes => &self.last_10_minutes,
+            HistoryWindow::WholeRun => &self.whole_run,
+        }
+    }
+}
+
+/// The `{score}` is a time or an amount of money, depending on the game mode.
+pub struct HighScoreMessages {
+    pub best: Message,
+    pub new_record: Message,
+}
+
+pub struct NewGameMessages {
+    pub title: String,
+    pub start: String,
+    pub cancel: String,
+    /// One name for each of `GameMode::list`.
+    pub modes: Vec<(GameMode, String)>,
+    /// One name for each of `Difficulty::list`.
+    pub difficulties: Vec<(Difficulty, String)>,
+    /// One label for each of `ConfigField::list`.
+    pub fields: Vec<(ConfigField, String)>,
+}
+
+impl NewGameMessages {
+    pub fn mode(&self, mode: GameMode) -> &str {
+        lookup_pair(&self.modes, &mode)
+    }
+
+    pub fn difficulty(&self, difficulty: Difficulty) -> &str {
+        lookup_pair(&self.difficulties, &difficulty)
+    }
+
+    pub fn field(&self, field: ConfigField) -> &str {
+        lookup_pair(&self.fields, &field)
+    }
+}
+
+pub struct SlotsMessages {
+    pub title: String,
+    /// The button that opens the slots screen.
+    pub open: String,
+    /// Name of the slots that weren't renamed, with their `{number}`.
+    pub unnamed: Message,
+    /// Name of a duplicated slot, from the `{name}` of the original.
+    pub copy_name: Message,
+    /// Has the `{act}`, `{savings}` and play `{time}` of the run of a slot.
+    pub summary: Message,
+    /// Instead of the summary, for slots without a run.
+    pub empty: String,
+    pub last_played: Message,
+    /// Next to the name of the slot being played.
+    pub current: String,
+    pub load: String,
+    pub duplicate: String,
+    pub rename: String,
+    pub done: String,
+    pub delete: String,
+    pub confirm_delete: String,
+    pub new: String,
+    pub close: String,
+    /// One name for each of `Act::list`.
+    pub acts: Vec<(Act, String)>,
+}
+
+impl SlotsMessages {
+    pub fn act(&self, act: Act) -> &str {
+        lookup_pair(&self.acts, &act)
+    }
+}
+
+pub struct SoundMessages {
+    /// The button that mutes, with the `{volume}` in percent.
+    pub volume: Message,
+    /// The same button while muted.
+    pub muted: String,
+}
+
+/// The button that toggles the particles, showing whether they are enabled.
+pub struct EffectsMessages {
+    pub on: String,
+    pub off: String,
+}
+
+pub struct NameEntryMessages {
+    pub title: String,
+    pub hint: String,
+    pub confirm: String,
+}
+
+pub struct LeaderboardMessages {
+    /// Has the `{mode}` and `{difficulty}` of the records.
+    pub title: Message,
+    /// One label for each of `Category::list`.
+    pub categories: Vec<(Category, String)>,
+    /// Shown instead of the name of players that didn't choose one.
+    pub anonymous: String,
+}
+
+impl LeaderboardMessages {
+    pub fn category(&self, category: Category) -> &str {
+        lookup_pair(&self.categories, &category)
+    }
+}
+
+fn lookup_pair<'a, K: PartialEq>(pairs: &'a [(K, String)], key: &K) -> &'a str {
+    pairs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, text)| text.as_str())
+        .unwrap_or_default()
+}
+
+/// Message id to text, as read from a translation file.
+pub type Messages = HashMap<String, String>;
+
+/// Parses `id = text` lines. Empty lines and lines starting with `#` are ignored.
+/// `\n` in the text is a line break and `\\` is a backslash.
+pub fn parse_messages(text: &str) -> Messages {
+    let mut messages = Messages::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((id, text)) => {
+                messages.insert(id.trim().to_string(), unescape(text.trim()));
+            }
+            None => warn!("ignoring line {} without '=': {:?}", line_number + 1, line),
+        }
+    }
+    messages
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => unescaped.push(c),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Looks up message ids, resorting to the fallback messages and remembering which ids were missing.
+struct MessageLookup<'a> {
+    language: Language,
+    messages: &'a Messages,
+    fallback: &'a Messages,
+    missing: Vec<String>,
+}
+
+impl<'a> MessageLookup<'a> {
+    fn text(&mut self, id: &str) -> String {
+        if let Some(text) = self.messages.get(id) {
+            text.clone()
+        } else {
+            self.missing.push(id.to_string());
+            self.fallback
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| id.to_string())
+        }
+    }
+
+    /// Reads `id`, or its plural forms `<id>.one`, `<id>.other`, etc. `<id>.other` is mandatory.
+    fn message(&mut self, id: &str) -> Message {
+        let forms = message_forms(self.messages, id);
+        let forms = if forms.is_empty() {
+            self.missing.push(id.to_string());
+            let fallback = message_forms(self.fallback, id);
+            if fallback.is_empty() {
+                vec![(PluralCategory::Other, id.to_string())]
+            } else {
+                fallback
+            }
+        } else {
+            if !forms.iter().any(|(c, _)| *c == PluralCategory::Other) {
+                self.missing
+                    .push(format!("{}.{}", id, PluralCategory::Other.suffix()));
+            }
+            forms
+        };
+        Message::new(self.language, forms)
+    }
+
+    fn production_messages(&mut self, prefix: &str) -> ProductionMessages {
+        ProductionMessages {
+            cleaning: self.message(&format!("{}.cleaning", prefix)),
+            dirtying: self.message(&format!("{}.dirtying", prefix)),
+        }
+    }
+
+    /// Collects the consecutive ids `<prefix>.1`, `<prefix>.2`, etc.
+    fn list(&mut self, prefix: &str) -> Vec<String> {
+        let list = numbered_messages(self.messages, prefix);
+        if list.is_empty() {
+            self.missing.push(format!("{}.1", prefix));
+            let fallback = numbered_messages(self.fallback, prefix);
+            if fallback.is_empty() {
+                vec![prefix.to_string()]
+            } else {
+                fallback
+            }
+        } else {
+            list
+        }
+    }
+
+    fn character_text(&mut self, prefix: &str) -> CharacterText {
+        CharacterText {
+            hero_1: self.text(&format!("{}.hero_1", prefix)),
+            villain_1: self.text(&format!("{}.villain_1", prefix)),
+            hero_2: self.text(&format!("{}.hero_2", prefix)),
+            villain_2: self.text(&format!("{}.villain_2", prefix)),
+            hero_3: self.text(&format!("{}.hero_3", prefix)),
+            villain_3: self.text(&format!("{}.villain_3", prefix)),
+        }
+    }
+}
+
+fn message_forms(messages: &Messages, id: &str) -> Vec<(PluralCategory, String)> {
+    if let Some(text) = messages.get(id) {
+        vec![(PluralCategory::Other, text.clone())]
+    } else {
+        PluralCategory::list()
+            .iter()
+            .filter_map(|category| {
+                let form = messages.get(&format!("{}.{}", id, category.suffix()))?;
+                Some((*category, form.clone()))
+            })
+            .collect()
+    }
+}
+
+fn numbered_messages(messages: &Messages, prefix: &str) -> Vec<String> {
+    (1..)
+        .map_while(|i| messages.get(&format!("{}.{}", prefix, i)).cloned())
+        .collect()
+}
+
+impl Translation {
+    /// Any message missing in `messages` is taken from `fallback` and reported as a warning.
+    pub fn new(language: Language, messages: &Messages, fallback: &Messages) -> Self {
+        let mut lookup = MessageLookup {
+            language,
+            messages,
+            fallback,
+            missing: Vec::new(),
+        };
+        let translation = Self::from_lookup(&mut lookup);
+        if !lookup.missing.is_empty() {
+            warn!(
+                "translation {:?} lacks messages {:?}",
+                language.code(),
+                lookup.missing
+            );
+        }
+        translation
+    }
+
+    /// Returns the ids that the game needs and are not present in `messages`.
+    pub fn missing_ids(language: Language, messages: &Messages) -> Vec<String> {
+        let mut lookup = MessageLookup {
+            language,
+            messages,
+            fallback: &Messages::new(),
+            missing: Vec::new(),
+        };
+        Self::from_lookup(&mut lookup);
+        lookup.missing
+    }
+
+    fn from_lookup(lookup: &mut MessageLookup) -> Self {
+        Self {
+            language: lookup.language,
+            restart: lookup.text("restart"),
+            continue_playing: lookup.text("continue_playing"),
+            buy: lookup.text("buy"),
+            sell: lookup.text("sell"),
+            change_style: lookup.text("change_style"),
+            cleaning: lookup.message("cleaning"),
+            dirtying: lookup.message("dirtying"),
+            you_hired: lookup.message("you_hired"),
+            producing: lookup.production_messages("producing"),
+            price: lookup.message("price"),
+            over_greedy: lookup.text("over_greedy"),
+            owned_by_dirt: lookup.text("owned_by_dirt"),
+            you_won: lookup.text("you_won"),
+            retire: lookup.text("retire"),
+            you_can_continue_playing: lookup.text("you_can_continue_playing"),
+            time_left: lookup.message("time_left"),
+            time_up: lookup.text("time_up"),
+            time_up_savings: lookup.message("time_up_savings"),
+            endless_lasted: lookup.message("endless_lasted"),
+            zen_won: lookup.text("zen_won"),
+            high_score: HighScoreMessages {
+                best: lookup.message("high_score.best"),
+                new_record: lookup.message("high_score.new_record"),
+            },
+            savings: lookup.message("savings"),
+            cleaning_speed: lookup.message("cleaning_speed"),
+            dirtying_speed: lookup.message("dirtying_speed"),
+            dirts: lookup.message("dirts"),
+            description: lookup.production_messages("description"),
+            long_description: lookup.character_text("long_description"),
+            name: lookup.character_text("name"),
+            lore: Lore {
+                act_1: lookup.list("lore.act_1"),
+                act_2: lookup.list("lore.act_2"),
+                act_3: lookup.list("lore.act_3"),
+                game_over: lookup.list("lore.game_over"),
+                game_won: lookup.list("lore.game_won"),
+            },
+            alerts: AlertMessages {
+                inefficient_cleaners: lookup.text("alerts.inefficient_cleaners"),
+                cannot_clean: lookup.text("alerts.cannot_clean"),
+                insufficient_money: lookup.text("alerts.insufficient_money"),
+                cannot_sell: lookup.text("alerts.cannot_sell"),
+                too_dirty: lookup.text("alerts.too_dirty"),
+            },
+            extra_controls: lookup.text("extra_controls"),
+            export: lookup.text("export"),
+            exported: lookup.message("exported"),
+            export_failed: lookup.message("export_failed"),
+            missing_assets: lookup.message("missing_assets"),
+            missing_assets_more: lookup.message("missing_assets_more"),
+            stats: StatsMessages {
+                title: lookup.text("stats.title"),
+                current_run: lookup.text("stats.current_run"),
+                lifetime: lookup.text("stats.lifetime"),
+                clean_clicks: lookup.text("stats.clean_clicks"),
+                dirty_clicks: lookup.text("stats.dirty_clicks"),
+                hired_and_fired: lookup.message("stats.hired_and_fired"),
+                money_spent: lookup.text("stats.money_spent"),
+                peak_dirtiness: lookup.text("stats.peak_dirtiness"),
+                time_in_act: Stats::played_acts()
+                    .iter()
+                    .map(|act| (*act, lookup.text(&format!("stats.time.{}", act.id()))))
+                    .collect(),
+                game_overs: lookup.text("stats.game_overs"),
+                games: lookup.message("stats.games"),
+            },
+            history: HistoryMessages {
+                title: lookup.text("history.title"),
+                last_minute: lookup.text("history.last_minute"),
+                last_10_minutes: lookup.text("history.last_10_minutes"),
+                whole_run: lookup.text("history.whole_run"),
+                money: lookup.text("history.money"),
+                dirtiness: lookup.text("history.dirtiness"),
+                speeds: lookup.text("history.speeds"),
+            },
+            new_game: NewGameMessages {
+                title: lookup.text("new_game.title"),
+                start: lookup.text("new_game.start"),
+                cancel: lookup.text("new_game.cancel"),
+                modes: GameMode::list()
+                    .iter()
+                    .map(|m| (*m, lookup.text(&format!("mode.{}", m.id()))))
+                    .collect(),
+                difficulties: Difficulty::list()
+                    .iter()
+                    .map(|d| (*d, lookup.text(&format!("difficulty.{}", d.id()))))
+                    .collect(),
+                fields: ConfigField::list()
+                    .iter()
+                    .map(|f| (*f, lookup.text(&format!("new_game.{}", f.id()))))
+                    .collect(),
+            },
+            leaderboard: LeaderboardMessages {
+                title: lookup.message("leaderboard.title"),
+                categories: Category::list()
+                    .iter()
+                    .map(|c| (*c, lookup.text(&format!("leaderboard.category.{}", c.id()))))
+                    .collect(),
+                anonymous: lookup.text("leaderboard.anonymous"),
+            },
+            slots: SlotsMessages {
+                title: lookup.text("slots.title"),
+                open: lookup.text("slots.open"),
+                unnamed: lookup.message("slots.unnamed"),
+                copy_name: lookup.message("slots.copy_name"),
+                summary: lookup.message("slots.summary"),
+                empty: lookup.text("slots.empty"),
+                last_played: lookup.message("slots.last_played"),
+                current: lookup.text("slots.current"),
+                load: lookup.text("slots.load"),
+                duplicate: lookup.text("slots.duplicate"),
+                rename: lookup.text("slots.rename"),
+                done: lookup.text("slots.done"),
+                delete: lookup.text("slots.delete"),
+                confirm_delete: lookup.text("slots.confirm_delete"),
+                new: lookup.text("slots.new"),
+                close: lookup.text("slots.close"),
+                acts: Act::list()
+                    .iter()
+                    .map(|a| (*a, lookup.text(&format!("act.{}", a.id()))))
+                    .collect(),
+            },
+            name_entry: NameEntryMessages {
+                title: lookup.text("name_entry.title"),
+                hint: lookup.text("name_entry.hint"),
+                confirm: lookup.text("name_entry.confirm"),
+            },
+            sound: SoundMessages {
+                volume: lookup.message("sound.volume"),
+                muted: lookup.text("sound.muted"),
+            },
+            effects: EffectsMessages {
+                on: lookup.text("effects.on"),
+                off: lookup.text("effects.off"),
+            },
+        }
+    }
+}
+
+/// The translations of every language, ready to be switched at runtime.
+#[derive(Clone)]
+pub struct Translations {
+    inner: HashMap<Language, Rc<Translation>>,
+}
+
+impl Translations {
+    /// `loaded_files` has the contents of the translation files that could be read at runtime.
+    /// Languages without a loaded file use the compiled-in translation.
+    pub fn new(loaded_files: Vec<(Language, String)>) -> Self {
+        let mut loaded_files = HashMap::<Language, String>::from_iter(loaded_files);
+        let inner = Language::list()
+            .iter()
+            .map(|language| {
+                let compiled_in = parse_messages(language.compiled_in_translation());
+                let messages = loaded_files
+                    .remove(language)
+                    .map(|text| parse_messages(&text))
+                    .unwrap_or_else(|| compiled_in.clone());
+                let translation = Translation::new(*language, &messages, &compiled_in);
+                (*language, Rc::new(translation))
+            })
+            .collect();
+        Self { inner }
+    }
+
+    pub fn compiled_in() -> Self {
+        Self::new(Vec::new())
+    }
+
+    pub fn get(&self, language: Language) -> Rc<Translation> {
+        self.inner[&language].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_shipped_language_has_every_message() {
+        for language in Language::list() {
+            let messages = parse_messages(language.compiled_in_translation());
+            assert_eq!(
+                Translation::missing_ids(*language, &messages),
+                Vec::<String>::new(),
+                "in language {:?}",
+                language
+            );
+            for other in Language::list() {
+                let other_messages = parse_messages(other.compiled_in_translation());
+                for id in other_messages.keys() {
+                    assert!(
+                        messages.contains_key(id),
+                        "{:?} is in {:?} but not in {:?}",
+                        id,
+                        other,
+                        language
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        let messages = parse_messages("# comment\n\na = first\nb=line\\nbreak \n  bad line\n");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages["a"], "first");
+        assert_eq!(messages["b"], "line\nbreak");
+    }
+
+    #[test]
+    fn test_missing_messages_use_fallback() {
+        let fallback = parse_messages(Language::English.compiled_in_translation());
+        let messages = parse_messages("restart = Otra vez");
+        let translation = Translation::new(Language::Spanish, &messages, &fallback);
+        assert_eq!(translation.restart, "Otra vez");
+        assert_eq!(translation.buy, "Buy");
+        assert_eq!(translation.lore.act_1.len(), 9);
     }
 }
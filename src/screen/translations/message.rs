@@ -0,0 +1,145 @@
+use std::fmt::Display;
+
+use crate::screen::number_format::format_integer;
+use crate::screen::translations::Language;
+
+/// The CLDR plural categories. Each language uses a subset of them, see `Language::plural_category`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+const PLURAL_CATEGORIES: [PluralCategory; 6] = [
+    PluralCategory::Zero,
+    PluralCategory::One,
+    PluralCategory::Two,
+    PluralCategory::Few,
+    PluralCategory::Many,
+    PluralCategory::Other,
+];
+
+impl PluralCategory {
+    pub fn list() -> &'static [PluralCategory] {
+        &PLURAL_CATEGORIES
+    }
+
+    /// Suffix of the message id for this plural form, e.g. `producing.cleaning.one`.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// A translated text with `{placeholders}`, which may have a different form for each plural
+/// category. Messages without plural forms only have the `Other` form.
+#[derive(Clone, Debug)]
+pub struct Message {
+    language: Language,
+    forms: Vec<(PluralCategory, String)>,
+}
+
+impl Message {
+    pub fn new(language: Language, forms: Vec<(PluralCategory, String)>) -> Self {
+        Self { language, forms }
+    }
+
+    /// Replaces each `{name}` with the value of the argument with that name.
+    pub fn format(&self, args: &[(&str, &dyn Display)]) -> String {
+        fill(self.form(PluralCategory::Other), args)
+    }
+
+    /// Chooses the plural form for `count`, and replaces `{count}` and the other `{name}`s.
+    /// The count is written with the thousands separators of the language.
+    pub fn format_count(&self, count: i64, args: &[(&str, &dyn Display)]) -> String {
+        let form = self.form(self.language.plural_category(count));
+        let formatted_count = format_integer(count, self.language);
+        let mut args_with_count: Vec<(&str, &dyn Display)> = vec![("count", &formatted_count)];
+        args_with_count.extend_from_slice(args);
+        fill(form, &args_with_count)
+    }
+
+    fn form(&self, category: PluralCategory) -> &str {
+        self.forms
+            .iter()
+            .find(|(form_category, _)| *form_category == category)
+            .or_else(|| self.forms.iter().find(|(c, _)| *c == PluralCategory::Other))
+            .or(self.forms.first())
+            .map(|(_, text)| text.as_str())
+            .unwrap_or_default()
+    }
+}
+
+/// Unknown placeholders are kept as they are, so that they are visible in the game.
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        match placeholder.find('}') {
+            Some(end) => {
+                let name = &placeholder[1..end];
+                match args.iter().find(|(arg_name, _)| *arg_name == name) {
+                    Some((_, value)) => filled.push_str(&value.to_string()),
+                    None => filled.push_str(&placeholder[..=end]),
+                }
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                rest = placeholder;
+                break;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(language: Language) -> Message {
+        Message::new(
+            language,
+            vec![
+                (PluralCategory::One, "{count} task for {who}".to_string()),
+                (PluralCategory::Other, "{count} tasks for {who}".to_string()),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_plural_forms() {
+        let message = tasks(Language::English);
+        assert_eq!(message.format_count(1, &[("who", &"me")]), "1 task for me");
+        assert_eq!(message.format_count(0, &[("who", &"me")]), "0 tasks for me");
+        assert_eq!(
+            message.format_count(10, &[("who", &"you")]),
+            "10 tasks for you"
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let message = Message::new(
+            Language::Spanish,
+            vec![(PluralCategory::Other, "{a}: {b} {unknown} {".to_string())],
+        );
+        assert_eq!(
+            message.format(&[("a", &"x"), ("b", &5)]),
+            "x: 5 {unknown} {"
+        );
+    }
+}
@@ -4,114 +4,164 @@ use crate::external::backends::{now, Seconds};
 use crate::screen::GuiActions;
 use crate::world::acts::Act;
 use crate::world::alerts::Alert;
+use crate::world::config::WorldConfig;
 use crate::world::heores::Hero;
+use crate::world::high_scores::{sanitize_name, Category, HighScores, RunResult, Score};
+use crate::world::history::{History, Sample};
+use crate::world::stats::Stats;
+use crate::world::timeline::{EventKind, Timeline, TimelineEvent, TimelineRow};
 
 pub mod acts;
-mod alerts;
+pub mod alerts;
+pub mod config;
 pub mod heores;
+pub mod high_scores;
+pub mod history;
+mod snapshot;
+pub mod stats;
+pub mod timeline;
 
-type Cents = i64;
-type Units = i64;
+/// Amounts of money and dirtiness saturate at `i64::MAX` instead of overflowing, which is about
+/// 92 thousand billion euros. A saturated amount stays there until something is spent or cleaned.
+pub type Cents = i64;
+pub type Units = i64;
 
 const ALERT_PERSISTENCE: Seconds = 5.0;
 
-pub const TARGET_SAVINGS: Units = 1_000_000;
-// pub const TARGET_SAVINGS: Units = 50;
-const DIRTYING_PER_CLICK: Cents = to_cents(1);
-// const DIRTYING_PER_CLICK: Cents = to_cents(10);
-const CLEANING_PER_CLICK: Cents = to_cents(1);
-// const CLEANING_PER_CLICK: Cents = to_cents(10);
-
 pub struct World {
+    config: WorldConfig,
     pub frame: i64,
     pub previous_frame_timestamp: Seconds,
     pub time_since_last_frame: Seconds,
     dirtiness: Cents,
     max_dirtiness: Units,
     money: Cents,
+    /// Money earned in this run, without subtracting what was spent.
     total_money: Cents,
     target_savings: Units,
     pub heroes_count: HashMap<Hero, i64>,
     pub alerts: Vec<(Seconds, Alert)>,
     inefficient_cleaning_warning: bool,
     act: Act,
+    stats: Stats,
+    history: History,
+    timeline: Timeline,
+    /// Stats of the runs that ended before this one, including other sessions.
+    previous_runs: Stats,
+    high_scores: HighScores,
+    /// Set when the run ends in a way that is scored in the main category of its mode.
+    run_score: Option<Score>,
+    /// The records that this run beat, once it has ended.
+    new_records: Vec<Category>,
+    /// Shown in the leaderboard. Empty until the player chooses one.
+    player_name: String,
 }
 
 impl World {
-    pub fn new() -> Self {
+    pub fn new(config: WorldConfig) -> Self {
+        let mut stats = Stats::default();
+        stats.record_game(config.difficulty);
         Self {
             previous_frame_timestamp: now(),
             frame: 0,
             time_since_last_frame: 0.0,
             dirtiness: to_cents(5),
-            max_dirtiness: 100,
+            max_dirtiness: config.max_dirtiness,
             money: 0,
             total_money: 0,
-            target_savings: TARGET_SAVINGS,
+            target_savings: config.target_savings,
             heroes_count: HashMap::from_iter(Hero::list().iter().map(|h| (*h, 0))),
             alerts: Vec::new(),
             inefficient_cleaning_warning: false,
             act: Act::Act1,
+            config,
+            stats,
+            history: History::default(),
+            timeline: Timeline::default(),
+            previous_runs: Stats::default(),
+            high_scores: HighScores::default(),
+            run_score: None,
+            new_records: Vec::new(),
+            player_name: String::new(),
         }
     }
 
     pub fn update(&mut self, gui_actions: GuiActions) -> bool {
+        self.update_at(gui_actions, now())
+    }
+
+    /// Like `update`, but with the time given instead of read from the clock, for simulations.
+    pub fn update_at(&mut self, gui_actions: GuiActions, now_time: Seconds) -> bool {
         if gui_actions.restart {
             self.restart();
         }
+        if let Some(config) = &gui_actions.new_game {
+            self.restart_with(config.clone());
+        }
+        if let Some(name) = &gui_actions.player_name {
+            self.set_player_name(name);
+        }
+        if gui_actions.paused {
+            self.previous_frame_timestamp = now_time;
+            return gui_actions.should_continue();
+        }
+        let previous_act = self.act;
         if self.act == Act::GameWon {
             if gui_actions.continue_playing {
                 self.act = Act::ContinuePlayingAfterWinning;
             }
-        } else if self.act != Act::GameOver {
+        } else if !self.act.is_final() {
             self.frame += 1;
-            let now_time = now();
             self.time_since_last_frame = now_time - self.previous_frame_timestamp;
             self.previous_frame_timestamp = now_time;
             self.remove_old_alerts(now_time);
+            self.stats.record_time(self.act, self.time_since_last_frame);
 
-            // self.max_dirtiness = 100 + self.total_money_euros();
             if gui_actions.dirty_pressed {
-                self.dirtiness += DIRTYING_PER_CLICK
+                self.stats.dirty_clicks = self.stats.dirty_clicks.saturating_add(1);
+                self.dirtiness = self
+                    .dirtiness
+                    .saturating_add(self.config.dirtying_per_click);
             }
             if gui_actions.clean_pressed {
-                if self.dirtiness >= CLEANING_PER_CLICK {
-                    self.dirtiness -= CLEANING_PER_CLICK;
-                    self.money += CLEANING_PER_CLICK;
-                    self.total_money += 10;
+                self.stats.clean_clicks = self.stats.clean_clicks.saturating_add(1);
+                let cleaning = self.config.cleaning_per_click;
+                if self.dirtiness >= cleaning {
+                    self.dirtiness -= cleaning;
+                    self.money = self.money.saturating_add(cleaning);
+                    self.total_money = self.total_money.saturating_add(cleaning);
                 } else {
                     self.alerts.push((now_time, Alert::CannotClean));
                 }
             }
 
-            for villain in [Hero::Villain1, Hero::Villain2, Hero::Villain3] {
-                let count = self.heroes_count[&villain];
-                self.dirtiness += count * villain.production_dirty();
-            }
-            let mut cleaned = 0;
-            for hero in [Hero::Hero1, Hero::Hero2, Hero::Hero3] {
-                let count = self.heroes_count[&hero];
-                cleaned += count * hero.production_clean();
-            }
+            self.dirtiness = self.dirtiness.saturating_add(self.dirtying_speed());
+            let mut cleaned = self.cleaning_speed();
             if cleaned > self.dirtiness {
                 self.inefficient_cleaning_warning = true;
                 self.alerts.push((now_time, Alert::InefficientCleaners))
             }
             cleaned = cleaned.min(self.dirtiness);
-            self.money += cleaned;
-            self.total_money += cleaned / 10;
+            self.money = self.money.saturating_add(cleaned);
+            self.total_money = self.total_money.saturating_add(cleaned);
             self.dirtiness -= cleaned;
+            self.max_dirtiness = self.config.max_dirtiness_at(self.total_money);
             self.dirtiness = to_cents(self.max_dirtiness).min(self.dirtiness);
+            self.stats.peak_dirtiness = self.stats.peak_dirtiness.max(self.dirtiness);
 
-            if self.dirtiness > (to_cents(self.max_dirtiness) as f32 * 0.75) as Cents {
+            if self.config.mode.can_lose() && self.dirtiness > self.too_dirty_threshold() {
                 self.alerts.push((now_time, Alert::TooDirty));
             }
 
             for (hero, bought) in &gui_actions.heroes_bought {
                 if *bought {
                     if self.money_euros() >= self.price(hero) {
-                        self.money -= to_cents(self.price(hero));
-                        *self.heroes_count.get_mut(&hero).unwrap() += 1;
+                        let price = to_cents(self.price(hero));
+                        self.money -= price;
+                        self.stats.record_purchase(hero, price);
+                        self.record_event(EventKind::Bought(*hero), price);
+                        let count = self.heroes_count.get_mut(hero).unwrap();
+                        *count = count.saturating_add(1);
                         if self.act == Act::Act1 && [Hero::Hero2, Hero::Villain2].contains(hero) {
                             self.act = Act::Act2;
                         }
@@ -124,42 +174,204 @@ impl World {
                 }
             }
             for (hero, sold) in &gui_actions.heroes_sold {
-                let count = self.heroes_count.get_mut(&hero).unwrap();
+                let count = self.heroes_count.get_mut(hero).unwrap();
                 if *sold {
                     if *count > 0 {
                         *count -= 1;
-                        self.money += to_cents(self.price(hero));
+                        let price = to_cents(self.price(hero));
+                        self.money = self.money.saturating_add(price);
+                        self.stats.record_sale(hero);
+                        self.record_event(EventKind::Sold(*hero), price);
                     } else {
                         self.alerts.push((now_time, Alert::CannotSell))
                     }
                 }
             }
-            if self.money_euros() >= self.target_savings
+            let time = self.stats.total_time();
+            if self.config.mode.can_win()
+                && self.money_euros() >= self.target_savings
                 && self.act != Act::ContinuePlayingAfterWinning
             {
                 self.act = Act::GameWon;
             }
-            if self.dirtiness_units() >= self.max_dirtiness_units() && self.act != Act::GameWon {
+            if self.config.mode.can_lose()
+                && self.dirtiness >= to_cents(self.max_dirtiness)
+                && self.act != Act::GameWon
+            {
                 self.act = Act::GameOver;
+                self.stats.game_overs = self.stats.game_overs.saturating_add(1);
+            }
+            if self.time_left() == Some(0.0) && !self.act.is_final() {
+                self.act = Act::TimeUp;
+            }
+            self.history.record(Sample {
+                time,
+                money: self.money,
+                dirtiness: self.dirtiness,
+                cleaning_speed: self.cleaning_speed(),
+                dirtying_speed: self.dirtying_speed(),
+            });
+            if self.timeline.is_due(time) {
+                self.timeline.record(TimelineRow {
+                    time,
+                    act: self.act,
+                    money: self.money,
+                    dirtiness: self.dirtiness,
+                    max_dirtiness: self.max_dirtiness,
+                    heroes_count: self.heroes_count.clone(),
+                });
+            }
+        }
+        if self.act != previous_act {
+            self.record_event(EventKind::ActChanged, 0);
+            if self.act.is_final() || self.act == Act::GameWon {
+                self.submit_records();
             }
         }
         gui_actions.should_continue()
     }
+    fn submit_records(&mut self) {
+        let run = RunResult {
+            won: self.act == Act::GameWon,
+            time: self.stats.total_time(),
+            savings: self.money,
+            clicks: self
+                .stats
+                .clean_clicks
+                .saturating_add(self.stats.dirty_clicks),
+        };
+        let (mode, difficulty) = (self.config.mode, self.config.difficulty);
+        self.run_score = run
+            .scores(mode)
+            .into_iter()
+            .find(|(category, _)| *category == mode.main_category())
+            .map(|(_, score)| score);
+        self.new_records = self
+            .high_scores
+            .submit(mode, difficulty, &self.player_name, &run);
+    }
+    fn record_event(&mut self, kind: EventKind, price: Cents) {
+        self.timeline.record_event(TimelineEvent {
+            time: self.stats.total_time(),
+            kind,
+            act: self.act,
+            price,
+        });
+    }
     fn remove_old_alerts(&mut self, now_time: Seconds) {
         self.alerts.retain(|(time_alert_was_raised, alert)| {
-            time_alert_was_raised + ALERT_PERSISTENCE >= now_time
-                && *alert != Alert::InefficientCleaners
-                && *alert != Alert::TooDirty
+            time_alert_was_raised + ALERT_PERSISTENCE >= now_time && !alert.is_continuous()
         });
     }
 
+    /// Starts a new run with the same config.
     pub fn restart(&mut self) {
-        *self = Self::new();
+        self.restart_with(self.config.clone());
+    }
+
+    pub fn restart_with(&mut self, config: WorldConfig) {
+        let mut previous_runs = std::mem::take(&mut self.previous_runs);
+        previous_runs.merge(&self.stats);
+        let high_scores = std::mem::take(&mut self.high_scores);
+        let player_name = std::mem::take(&mut self.player_name);
+        *self = Self::new(config);
+        self.previous_runs = previous_runs;
+        self.high_scores = high_scores;
+        self.player_name = player_name;
+    }
+
+    /// Switches to a run that was saved in another slot, keeping what every run shares: the stats
+    /// of the runs that ended and the high scores.
+    pub fn continue_run(&mut self, run: World) {
+        let previous_runs = std::mem::take(&mut self.previous_runs);
+        let high_scores = std::mem::take(&mut self.high_scores);
+        *self = run;
+        self.previous_runs = previous_runs;
+        self.high_scores = high_scores;
+    }
+
+    pub fn config(&self) -> &WorldConfig {
+        &self.config
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Counters of the current run only.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+    /// Counters of every run, including the current one.
+    pub fn lifetime_stats(&self) -> Stats {
+        let mut lifetime = self.previous_runs.clone();
+        lifetime.merge(&self.stats);
+        lifetime
+    }
+    /// Counters of the runs that ended, without the current one.
+    pub fn previous_runs(&self) -> &Stats {
+        &self.previous_runs
+    }
+    /// Continues the lifetime stats saved in a previous session.
+    pub fn set_previous_runs(&mut self, previous_runs: Stats) {
+        self.previous_runs = previous_runs;
+    }
+    /// The best runs of each mode and difficulty, including other sessions.
+    pub fn high_scores(&self) -> &HighScores {
+        &self.high_scores
+    }
+    pub fn set_high_scores(&mut self, high_scores: HighScores) {
+        self.high_scores = high_scores;
+    }
+    /// The records that this run beat, once it has ended.
+    pub fn new_records(&self) -> &[Category] {
+        &self.new_records
+    }
+    pub fn player_name(&self) -> &str {
+        &self.player_name
+    }
+    pub fn set_player_name(&mut self, name: &str) {
+        self.player_name = sanitize_name(name);
+    }
+    /// The score of this run in the main category of its mode, once it has ended in a way that
+    /// the category scores.
+    pub fn run_score(&self) -> Option<Score> {
+        self.run_score
+    }
+    /// Time until a timed run ends, or None in the other modes.
+    pub fn time_left(&self) -> Option<Seconds> {
+        let limit = self.config.time_limit()?;
+        Some((limit - self.stats.total_time()).max(0.0))
     }
     pub fn price(&self, hero: &Hero) -> Units {
-        (self.heroes_count[&hero] + 1) * hero.base_price()
+        self.heroes_count[hero]
+            .saturating_add(1)
+            .saturating_mul(hero.base_price())
+    }
+    /// What was paid for all the units of `hero` currently hired.
+    pub fn invested(&self, hero: &Hero) -> Cents {
+        to_cents(accumulate_price(self.heroes_count[hero]).saturating_mul(hero.base_price()))
+    }
+    /// Cents cleaned per frame by all the heroes.
+    pub fn cleaning_speed(&self) -> Cents {
+        self.total_production(Hero::production_clean)
+    }
+    /// Cents dirtied per frame by all the villains.
+    pub fn dirtying_speed(&self) -> Cents {
+        self.total_production(Hero::production_dirty)
+    }
+    fn total_production(&self, production: fn(&Hero) -> i64) -> Cents {
+        Hero::list().iter().fold(0, |total: Cents, hero| {
+            total.saturating_add(self.heroes_count[hero].saturating_mul(production(hero)))
+        })
     }
 
+    pub fn money(&self) -> Cents {
+        self.money
+    }
     pub fn money_euros(&self) -> Units {
         self.money / 100
     }
@@ -169,9 +381,14 @@ impl World {
     pub fn dirtiness_units(&self) -> Units {
         self.dirtiness / 100
     }
+    /// Grows with the money earned in this run, see `WorldConfig::max_dirtiness_at`.
     pub fn max_dirtiness_units(&self) -> Units {
         self.max_dirtiness
     }
+    /// Above this, the player is warned that the game is about to be lost.
+    pub fn too_dirty_threshold(&self) -> Cents {
+        to_cents(self.max_dirtiness) / 4 * 3
+    }
 
     pub fn stage(&self) -> Act {
         self.act
@@ -190,27 +407,37 @@ impl World {
     }
 }
 pub const fn to_cents(unit: Units) -> Cents {
-    unit * 100
+    unit.saturating_mul(100)
 }
 
-pub fn accumulate_price(n: i64) -> f32 {
-    ((1 + n) * n) as f32 / 2.0
+/// 1 + 2 + ... + n, the price multiplier of buying n units one by one.
+pub fn accumulate_price(n: i64) -> i64 {
+    if n % 2 == 0 {
+        (n / 2).saturating_mul(n.saturating_add(1))
+    } else {
+        n.saturating_mul(n / 2 + 1)
+    }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::world::acts::Act::TimeUp;
     use crate::world::acts::Act::{Act1, ContinuePlayingAfterWinning, GameOver, GameWon};
+    use crate::world::config::{Difficulty, GameMode};
 
     #[test]
     fn test_invested() {
         let actual = accumulate_price(5);
         let expected = 1 + 2 + 3 + 4 + 5;
-        assert_eq!(actual, expected as f32);
+        assert_eq!(actual, expected);
+        assert_eq!(accumulate_price(4), 1 + 2 + 3 + 4);
+        assert_eq!(accumulate_price(0), 0);
+        assert_eq!(accumulate_price(i64::MAX), i64::MAX);
     }
 
     #[test]
     fn test_restart_game_over() {
-        let mut world = World::new();
+        let mut world = World::new(WorldConfig::default());
         assert_eq!(world.stage(), Act1);
         for _ in 0..world.max_dirtiness_units() {
             world.update(GuiActions {
@@ -229,7 +456,7 @@ mod tests {
 
     #[test]
     fn test_continue_after_winning() {
-        let mut world = World::new();
+        let mut world = World::new(WorldConfig::default());
         let target_savings = 10;
         world.set_target_savings(target_savings);
         assert_eq!(world.stage(), Act1);
@@ -253,7 +480,7 @@ mod tests {
 
     #[test]
     fn test_win_and_lose_and_restart() {
-        let mut world = World::new();
+        let mut world = World::new(WorldConfig::default());
         assert_eq!(world.stage(), Act1);
 
         let target_savings = 10;
@@ -287,4 +514,405 @@ mod tests {
         });
         assert_eq!(world.stage(), Act1);
     }
+
+    #[test]
+    fn test_stats_survive_restart() {
+        let mut world = World::new(WorldConfig::default());
+        let mut clicks = 0;
+        while world.stage() != GameOver {
+            world.update(GuiActions {
+                dirty_pressed: true,
+                ..GuiActions::default()
+            });
+            clicks += 1;
+        }
+        assert_eq!(world.stats().dirty_clicks, clicks);
+        assert_eq!(world.stats().game_overs, 1);
+        assert_eq!(
+            world.stats().peak_dirtiness,
+            to_cents(world.max_dirtiness_units())
+        );
+
+        world.update(GuiActions {
+            restart: true,
+            clean_pressed: true,
+            ..GuiActions::default()
+        });
+        assert_eq!(world.stats().dirty_clicks, 0);
+        assert_eq!(world.stats().clean_clicks, 1);
+        let lifetime = world.lifetime_stats();
+        assert_eq!(lifetime.dirty_clicks, clicks);
+        assert_eq!(lifetime.clean_clicks, 1);
+        assert_eq!(lifetime.game_overs, 1);
+    }
+
+    #[test]
+    fn test_difficulty_changes_the_economy() {
+        let mut easy = World::new(WorldConfig::preset(Difficulty::Easy));
+        let mut hard = World::new(WorldConfig::preset(Difficulty::Hard));
+        let click = || GuiActions {
+            clean_pressed: true,
+            ..GuiActions::default()
+        };
+        easy.update(click());
+        hard.update(click());
+        assert_eq!(easy.money(), to_cents(2));
+        assert_eq!(hard.money(), to_cents(1));
+        assert_eq!(hard.config().max_dirtiness, 50);
+        assert_eq!(easy.stats().games[&Difficulty::Easy], 1);
+        assert_eq!(easy.stats().games[&Difficulty::Normal], 0);
+    }
+
+    #[test]
+    fn test_new_game_keeps_lifetime_stats() {
+        let mut world = World::new(WorldConfig::default());
+        world.update(GuiActions {
+            new_game: Some(WorldConfig::preset(Difficulty::Hard)),
+            dirty_pressed: true,
+            ..GuiActions::default()
+        });
+        assert_eq!(world.config().difficulty, Difficulty::Hard);
+        assert_eq!(world.stats().dirty_clicks, 1);
+        world.restart();
+        assert_eq!(world.config().difficulty, Difficulty::Hard);
+        let lifetime = world.lifetime_stats();
+        assert_eq!(lifetime.games[&Difficulty::Normal], 1);
+        assert_eq!(lifetime.games[&Difficulty::Hard], 2);
+    }
+
+    #[test]
+    fn test_paused_world_does_not_advance() {
+        let mut world = World::new(WorldConfig::default());
+        let start = world.previous_frame_timestamp;
+        world.update_at(
+            GuiActions {
+                paused: true,
+                dirty_pressed: true,
+                ..GuiActions::default()
+            },
+            start + 100.0,
+        );
+        assert_eq!(world.frame, 0);
+        assert_eq!(world.stats().dirty_clicks, 0);
+        world.update_at(GuiActions::default(), start + 101.0);
+        assert_eq!(world.time_since_last_frame, 1.0);
+    }
+
+    fn fixed_capacity() -> WorldConfig {
+        WorldConfig {
+            capacity_growth_percent: 0,
+            capacity_growth_sqrt: 0,
+            ..WorldConfig::default()
+        }
+    }
+
+    fn too_dirty(world: &World) -> bool {
+        world
+            .alerts
+            .iter()
+            .any(|(_, alert)| *alert == Alert::TooDirty)
+    }
+
+    #[test]
+    fn test_too_dirty_threshold_boundary() {
+        let mut world = World::new(fixed_capacity());
+        assert_eq!(world.too_dirty_threshold(), to_cents(75));
+        world.dirtiness = to_cents(75);
+        world.update(GuiActions::default());
+        assert!(!too_dirty(&world));
+        world.dirtiness = to_cents(75) + 1;
+        world.update(GuiActions::default());
+        assert!(too_dirty(&world));
+        assert_eq!(world.stage(), Act1);
+    }
+
+    #[test]
+    fn test_game_over_boundary() {
+        let mut world = World::new(fixed_capacity());
+        world.dirtiness = to_cents(100) - 1;
+        world.update(GuiActions::default());
+        assert_eq!(world.stage(), Act1);
+        world.dirtiness = to_cents(100);
+        world.update(GuiActions::default());
+        assert_eq!(world.stage(), GameOver);
+    }
+
+    #[test]
+    fn test_capacity_grows_with_earnings() {
+        let mut world = World::new(WorldConfig::default());
+        assert_eq!(world.max_dirtiness_units(), 100);
+        world.total_money = to_cents(1_000);
+        world.update(GuiActions::default());
+        assert_eq!(world.max_dirtiness_units(), 200);
+        assert_eq!(world.too_dirty_threshold(), to_cents(150));
+
+        // what would have been game over with the initial capacity isn't anymore
+        world.dirtiness = to_cents(150);
+        world.update(GuiActions::default());
+        assert_eq!(world.stage(), Act1);
+        assert!(!too_dirty(&world));
+
+        // cleaning in the same frame happens before checking for game over
+        world.dirtiness = to_cents(200);
+        world.update(GuiActions {
+            clean_pressed: true,
+            ..GuiActions::default()
+        });
+        assert_eq!(world.max_dirtiness_units(), 200);
+        assert_eq!(world.stage(), Act1);
+    }
+
+    #[test]
+    fn test_capacity_at_saturated_earnings() {
+        let mut world = World::new(WorldConfig::default());
+        world.total_money = i64::MAX;
+        world.dirtiness = i64::MAX;
+        world.update(GuiActions::default());
+        assert_eq!(world.dirtiness, to_cents(world.max_dirtiness_units()));
+        assert!(world.too_dirty_threshold() < world.dirtiness);
+        assert_eq!(world.stage(), GameOver);
+    }
+
+    fn with_mode(mode: GameMode) -> World {
+        let mut world = World::new(WorldConfig {
+            mode,
+            ..fixed_capacity()
+        });
+        world.set_target_savings(10);
+        world
+    }
+
+    fn fill_dirtiness(world: &mut World) {
+        world.dirtiness = to_cents(world.max_dirtiness);
+        world.update(GuiActions::default());
+    }
+
+    fn reach_target(world: &mut World) {
+        world.money = to_cents(world.target_savings);
+        world.update(GuiActions::default());
+    }
+
+    #[test]
+    fn test_timed_mode_ends_when_time_is_up() {
+        let mut world = with_mode(GameMode::Timed);
+        let start = world.previous_frame_timestamp;
+        world.money = to_cents(world.target_savings);
+        world.update_at(GuiActions::default(), start);
+        assert_eq!(world.stage(), Act1);
+        assert_eq!(world.time_left(), Some(300.0));
+
+        world.update_at(GuiActions::default(), start + 299.0);
+        assert_eq!(world.stage(), Act1);
+        world.update_at(GuiActions::default(), start + 300.0);
+        assert_eq!(world.stage(), TimeUp);
+        assert_eq!(world.time_left(), Some(0.0));
+        assert_eq!(world.run_score(), Some(Score::Money(to_cents(10))));
+        assert!(world.new_records().contains(&Category::HighestSavings));
+
+        world.update_at(GuiActions::default(), start + 400.0);
+        assert_eq!(world.stats().total_time(), 300.0);
+        world.update(GuiActions {
+            restart: true,
+            ..GuiActions::default()
+        });
+        assert_eq!(world.stage(), Act1);
+        assert_eq!(world.run_score(), None);
+        let difficulty = world.config().difficulty;
+        let record =
+            world
+                .high_scores()
+                .record(GameMode::Timed, difficulty, Category::HighestSavings);
+        assert_eq!(record.unwrap().score, Score::Money(to_cents(10)));
+    }
+
+    #[test]
+    fn test_endless_mode_has_no_win() {
+        let mut world = with_mode(GameMode::Endless);
+        reach_target(&mut world);
+        assert_eq!(world.stage(), Act1);
+        fill_dirtiness(&mut world);
+        assert_eq!(world.stage(), GameOver);
+        assert!(matches!(world.run_score(), Some(Score::Time(_))));
+        assert!(world.new_records().contains(&Category::LongestSurvival));
+    }
+
+    #[test]
+    fn test_zen_mode_has_no_game_over() {
+        let mut world = with_mode(GameMode::Zen);
+        fill_dirtiness(&mut world);
+        fill_dirtiness(&mut world);
+        assert_eq!(world.stage(), Act1);
+        assert!(!too_dirty(&world));
+        reach_target(&mut world);
+        assert_eq!(world.stage(), GameWon);
+    }
+
+    #[test]
+    fn test_high_scores_record_the_run() {
+        let mut world = with_mode(GameMode::Classic);
+        world.set_player_name(" ana ");
+        world.update(GuiActions {
+            clean_pressed: true,
+            ..GuiActions::default()
+        });
+        reach_target(&mut world);
+        assert_eq!(world.stage(), GameWon);
+        assert_eq!(world.new_records().len(), 3);
+        let difficulty = world.config().difficulty;
+        let record = world
+            .high_scores()
+            .record(GameMode::Classic, difficulty, Category::FewestClicks)
+            .unwrap();
+        assert_eq!(record.score, Score::Clicks(1));
+        assert_eq!(record.name, "ana");
+
+        world.restart();
+        assert!(world.new_records().is_empty());
+        assert_eq!(world.player_name(), "ana");
+        fill_dirtiness(&mut world);
+        assert_eq!(world.stage(), GameOver);
+        assert!(world.new_records().is_empty());
+        assert!(world
+            .high_scores()
+            .record(GameMode::Classic, difficulty, Category::FastestWin)
+            .is_some());
+    }
+
+    #[test]
+    fn test_player_name_is_kept_while_paused() {
+        let mut world = World::new(fixed_capacity());
+        world.update(GuiActions {
+            player_name: Some("bea\n".to_string()),
+            paused: true,
+            ..GuiActions::default()
+        });
+        assert_eq!(world.player_name(), "bea");
+        assert_eq!(world.frame, 0);
+    }
+
+    #[test]
+    fn test_continue_run_keeps_shared_progress() {
+        let mut world = with_mode(GameMode::Classic);
+        world.set_player_name("ana");
+        reach_target(&mut world);
+        world.restart();
+        let played = world.previous_runs().clone();
+
+        let mut other = World::new(WorldConfig::preset(Difficulty::Hard));
+        other.set_player_name("bea");
+        other.money = 123;
+        world.continue_run(other);
+        assert_eq!(world.money(), 123);
+        assert_eq!(world.player_name(), "bea");
+        assert_eq!(world.config().difficulty, Difficulty::Hard);
+        assert_eq!(world.previous_runs(), &played);
+        assert!(!world.high_scores().to_text().is_empty());
+    }
+
+    #[test]
+    fn test_classic_game_over_is_not_scored() {
+        let mut world = with_mode(GameMode::Classic);
+        fill_dirtiness(&mut world);
+        assert_eq!(world.stage(), GameOver);
+        assert_eq!(world.run_score(), None);
+    }
+
+    /// Xorshift, so that the property tests below are reproducible without extra dependencies.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn one_in(&mut self, n: u64) -> bool {
+            self.next().is_multiple_of(n)
+        }
+        /// Mostly values at the edges of i64, where overflows happen.
+        fn amount(&mut self) -> i64 {
+            match self.next() % 6 {
+                0 => 0,
+                1 => (self.next() % 1000) as i64,
+                2 => i64::MAX,
+                3 => i64::MAX - (self.next() % 1000) as i64,
+                4 => i64::MAX / (2 + (self.next() % 1000) as i64),
+                _ => (self.next() >> 1) as i64,
+            }
+        }
+        fn actions(&mut self) -> GuiActions {
+            let mut per_hero = || {
+                Hero::list()
+                    .iter()
+                    .map(|hero| (*hero, self.one_in(3)))
+                    .collect::<HashMap<_, _>>()
+            };
+            let heroes_bought = per_hero();
+            let heroes_sold = per_hero();
+            GuiActions {
+                clean_pressed: self.one_in(2),
+                dirty_pressed: self.one_in(2),
+                restart: self.one_in(50),
+                continue_playing: self.one_in(2),
+                heroes_bought,
+                heroes_sold,
+                ..GuiActions::default()
+            }
+        }
+    }
+
+    fn assert_consistent(world: &World) {
+        assert!(world.dirtiness >= 0);
+        assert!(world.dirtiness <= to_cents(world.max_dirtiness));
+        assert!(world.money >= 0);
+        assert!(world.total_money >= 0);
+        assert!(world.cleaning_speed() >= 0);
+        assert!(world.dirtying_speed() >= 0);
+        for hero in Hero::list() {
+            assert!(world.heroes_count[hero] >= 0);
+            assert!(world.price(hero) >= hero.base_price());
+            assert!(world.invested(hero) >= 0);
+        }
+    }
+
+    #[test]
+    fn test_extreme_counts_do_not_overflow() {
+        for seed in 1..=300 {
+            let mut random = Random(seed);
+            let mut world = World::new(WorldConfig::default());
+            for _ in 0..50 {
+                if random.one_in(5) {
+                    for hero in Hero::list() {
+                        world.heroes_count.insert(*hero, random.amount());
+                    }
+                    world.money = random.amount();
+                    world.total_money = random.amount();
+                }
+                world.update(random.actions());
+                assert_consistent(&world);
+            }
+        }
+    }
+
+    #[test]
+    fn test_saturated_money_stays_saturated() {
+        let mut world = World::new(WorldConfig::default());
+        world.money = i64::MAX;
+        world.heroes_count.insert(Hero::Hero3, i64::MAX);
+        world.update(GuiActions {
+            clean_pressed: true,
+            heroes_sold: HashMap::from([(Hero::Hero3, true)]),
+            ..GuiActions::default()
+        });
+        assert_eq!(world.money, i64::MAX);
+        assert_eq!(world.heroes_count[&Hero::Hero3], i64::MAX - 1);
+
+        world.update(GuiActions {
+            heroes_bought: HashMap::from([(Hero::Hero3, true)]),
+            ..GuiActions::default()
+        });
+        assert_eq!(world.price(&Hero::Hero3), i64::MAX);
+        assert_eq!(world.heroes_count[&Hero::Hero3], i64::MAX - 1);
+    }
 }
@@ -1,4 +1,4 @@
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Act {
     Act1,
     Act2,
@@ -6,4 +6,44 @@ pub enum Act {
     GameOver,
     GameWon,
     ContinuePlayingAfterWinning,
+    /// The end of a timed run.
+    TimeUp,
+}
+
+const ACTS: [Act; 7] = [
+    Act::Act1,
+    Act::Act2,
+    Act::Act3,
+    Act::GameOver,
+    Act::GameWon,
+    Act::ContinuePlayingAfterWinning,
+    Act::TimeUp,
+];
+
+impl Act {
+    pub fn list() -> &'static [Act] {
+        &ACTS
+    }
+
+    /// Stable name for files and message ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Act::Act1 => "act_1",
+            Act::Act2 => "act_2",
+            Act::Act3 => "act_3",
+            Act::GameOver => "game_over",
+            Act::GameWon => "game_won",
+            Act::ContinuePlayingAfterWinning => "continue_playing",
+            Act::TimeUp => "time_up",
+        }
+    }
+
+    /// Whether the run is over and the world doesn't advance anymore, until restarting.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Act::GameOver | Act::TimeUp)
+    }
+
+    pub fn from_id(id: &str) -> Option<Act> {
+        ACTS.iter().find(|act| act.id() == id).copied()
+    }
 }
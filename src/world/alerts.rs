@@ -1,6 +1,6 @@
 use crate::screen::translations::Translation;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Alert {
     InefficientCleaners = 0,
     CannotClean = 1,
@@ -9,14 +9,43 @@ pub enum Alert {
     TooDirty = 4,
 }
 
+const ALERTS: [Alert; 5] = [
+    Alert::InefficientCleaners,
+    Alert::CannotClean,
+    Alert::InsufficientMoney,
+    Alert::CannotSell,
+    Alert::TooDirty,
+];
+
 impl Alert {
-    pub fn to_string(&self, translation: &Translation) -> &str {
+    pub fn list() -> &'static [Alert] {
+        &ALERTS
+    }
+
+    /// Stable name for files and message ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Alert::InefficientCleaners => "inefficient_cleaners",
+            Alert::CannotClean => "cannot_clean",
+            Alert::InsufficientMoney => "insufficient_money",
+            Alert::CannotSell => "cannot_sell",
+            Alert::TooDirty => "too_dirty",
+        }
+    }
+
+    /// Raised again every frame while its condition holds, instead of once per action, so it
+    /// doesn't need to persist.
+    pub fn is_continuous(&self) -> bool {
+        matches!(self, Alert::InefficientCleaners | Alert::TooDirty)
+    }
+
+    pub fn to_string<'a>(&self, translation: &'a Translation) -> &'a str {
         match self {
-            Alert::InefficientCleaners => translation.alerts.inefficient_cleaners,
-            Alert::CannotClean => translation.alerts.cannot_clean,
-            Alert::InsufficientMoney => translation.alerts.insufficient_money,
-            Alert::CannotSell => translation.alerts.cannot_sell,
-            Alert::TooDirty => translation.alerts.too_dirty,
+            Alert::InefficientCleaners => &translation.alerts.inefficient_cleaners,
+            Alert::CannotClean => &translation.alerts.cannot_clean,
+            Alert::InsufficientMoney => &translation.alerts.insufficient_money,
+            Alert::CannotSell => &translation.alerts.cannot_sell,
+            Alert::TooDirty => &translation.alerts.too_dirty,
         }
     }
 }
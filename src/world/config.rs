@@ -0,0 +1,425 @@
+//! Parameters that are fixed during a run: the game mode, and the economy, grouped in
+//! difficulty presets.
+
+use crate::external::backends::Seconds;
+use crate::world::{to_cents, Cents, Units};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum GameMode {
+    /// Win by reaching the savings target, lose when the dirtiness is full.
+    Classic,
+    /// Save as much as possible before the time limit.
+    Timed,
+    /// There is no target to reach, only losing ends the run.
+    Endless,
+    /// The dirtiness can't make you lose.
+    Zen,
+}
+
+const GAME_MODES: [GameMode; 4] = [
+    GameMode::Classic,
+    GameMode::Timed,
+    GameMode::Endless,
+    GameMode::Zen,
+];
+
+impl GameMode {
+    pub fn list() -> &'static [GameMode] {
+        &GAME_MODES
+    }
+
+    /// Stable name for files and message ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::Timed => "timed",
+            GameMode::Endless => "endless",
+            GameMode::Zen => "zen",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<GameMode> {
+        GAME_MODES.iter().find(|mode| mode.id() == id).copied()
+    }
+
+    /// Whether reaching the savings target shows the game-won screen.
+    pub fn can_win(&self) -> bool {
+        matches!(self, GameMode::Classic | GameMode::Zen)
+    }
+
+    /// Whether a full dirtiness ends the run.
+    pub fn can_lose(&self) -> bool {
+        *self != GameMode::Zen
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Custom,
+}
+
+const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Normal,
+    Difficulty::Hard,
+    Difficulty::Custom,
+];
+
+impl Difficulty {
+    pub fn list() -> &'static [Difficulty] {
+        &DIFFICULTIES
+    }
+
+    /// Stable name for files and message ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Custom => "custom",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Difficulty> {
+        DIFFICULTIES.iter().find(|d| d.id() == id).copied()
+    }
+}
+
+/// The parameters that can be changed in a custom game.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ConfigField {
+    MaxDirtiness,
+    TargetSavings,
+    DirtyingPerClick,
+    CleaningPerClick,
+    CapacityGrowthPercent,
+    CapacityGrowthSqrt,
+}
+
+const CONFIG_FIELDS: [ConfigField; 6] = [
+    ConfigField::MaxDirtiness,
+    ConfigField::TargetSavings,
+    ConfigField::DirtyingPerClick,
+    ConfigField::CleaningPerClick,
+    ConfigField::CapacityGrowthPercent,
+    ConfigField::CapacityGrowthSqrt,
+];
+
+impl ConfigField {
+    pub const fn list() -> &'static [ConfigField] {
+        &CONFIG_FIELDS
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            ConfigField::MaxDirtiness => "max_dirtiness",
+            ConfigField::TargetSavings => "target_savings",
+            ConfigField::DirtyingPerClick => "dirtying_per_click",
+            ConfigField::CleaningPerClick => "cleaning_per_click",
+            ConfigField::CapacityGrowthPercent => "capacity_growth_percent",
+            ConfigField::CapacityGrowthSqrt => "capacity_growth_sqrt",
+        }
+    }
+
+    /// Dirtiness is shown in whole units, so it can't be dirtied by less than one per click.
+    fn min(&self) -> i64 {
+        match self {
+            ConfigField::MaxDirtiness => 10,
+            ConfigField::TargetSavings => 10,
+            ConfigField::DirtyingPerClick => to_cents(1),
+            ConfigField::CleaningPerClick => 1,
+            ConfigField::CapacityGrowthPercent => 0,
+            ConfigField::CapacityGrowthSqrt => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldConfig {
+    pub mode: GameMode,
+    /// Only used in `GameMode::Timed`.
+    pub time_limit_minutes: i64,
+    pub difficulty: Difficulty,
+    /// Dirtiness that ends the game at the start of the run. It grows with the money earned, see
+    /// `max_dirtiness_at`.
+    pub max_dirtiness: Units,
+    /// Savings that win the game.
+    pub target_savings: Units,
+    pub dirtying_per_click: Cents,
+    /// Cleaning a cent of dirtiness earns a cent of money.
+    pub cleaning_per_click: Cents,
+    /// Percentage of the money earned that is added to the dirt capacity.
+    pub capacity_growth_percent: i64,
+    /// Times the square root of the money earned, in euros, that is added to the dirt capacity.
+    pub capacity_growth_sqrt: i64,
+}
+
+const DEFAULT_TIME_LIMIT_MINUTES: i64 = 5;
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self::preset(Difficulty::Normal)
+    }
+}
+
+impl WorldConfig {
+    /// The custom preset starts like the normal one, to be changed with `step`. The mode is
+    /// classic.
+    pub fn preset(difficulty: Difficulty) -> Self {
+        let normal = Self {
+            mode: GameMode::Classic,
+            time_limit_minutes: DEFAULT_TIME_LIMIT_MINUTES,
+            difficulty,
+            max_dirtiness: 100,
+            target_savings: 1_000_000,
+            dirtying_per_click: to_cents(1),
+            cleaning_per_click: to_cents(1),
+            capacity_growth_percent: 10,
+            capacity_growth_sqrt: 0,
+        };
+        match difficulty {
+            Difficulty::Easy => Self {
+                max_dirtiness: 200,
+                target_savings: 500_000,
+                cleaning_per_click: to_cents(2),
+                capacity_growth_percent: 20,
+                ..normal
+            },
+            Difficulty::Normal | Difficulty::Custom => normal,
+            Difficulty::Hard => Self {
+                max_dirtiness: 50,
+                target_savings: 2_000_000,
+                capacity_growth_percent: 0,
+                capacity_growth_sqrt: 1,
+                ..normal
+            },
+        }
+    }
+
+    /// Same mode and time limit, with the economy of another difficulty.
+    pub fn with_difficulty(&self, difficulty: Difficulty) -> Self {
+        let economy = if difficulty == Difficulty::Custom {
+            self.clone()
+        } else {
+            Self::preset(difficulty)
+        };
+        Self {
+            mode: self.mode,
+            time_limit_minutes: self.time_limit_minutes,
+            difficulty,
+            ..economy
+        }
+    }
+
+    /// How long a timed run lasts, or None in the other modes.
+    pub fn time_limit(&self) -> Option<Seconds> {
+        if self.mode == GameMode::Timed {
+            Some(self.time_limit_minutes as Seconds * 60.0)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, field: ConfigField) -> i64 {
+        match field {
+            ConfigField::MaxDirtiness => self.max_dirtiness,
+            ConfigField::TargetSavings => self.target_savings,
+            ConfigField::DirtyingPerClick => self.dirtying_per_click,
+            ConfigField::CleaningPerClick => self.cleaning_per_click,
+            ConfigField::CapacityGrowthPercent => self.capacity_growth_percent,
+            ConfigField::CapacityGrowthSqrt => self.capacity_growth_sqrt,
+        }
+    }
+
+    /// The dirt capacity after earning `earned` in this run:
+    ///
+    /// `max_dirtiness + earned * capacity_growth_percent / 100 + capacity_growth_sqrt * √earned`
+    ///
+    /// with `earned` in euros. It never shrinks while the money earned grows.
+    pub fn max_dirtiness_at(&self, earned: Cents) -> Units {
+        let earned = (earned / 100).max(0);
+        let percent = self.capacity_growth_percent;
+        let linear = (earned / 100)
+            .saturating_mul(percent)
+            .saturating_add((earned % 100).saturating_mul(percent) / 100);
+        let sqrt = self.capacity_growth_sqrt.saturating_mul(earned.isqrt());
+        self.max_dirtiness
+            .saturating_add(linear)
+            .saturating_add(sqrt)
+    }
+
+    /// Sets a field, which makes this a custom game. Values below the minimum of the field are
+    /// raised to it.
+    pub fn set(&mut self, field: ConfigField, value: i64) {
+        let value = value.max(field.min());
+        match field {
+            ConfigField::MaxDirtiness => self.max_dirtiness = value,
+            ConfigField::TargetSavings => self.target_savings = value,
+            ConfigField::DirtyingPerClick => self.dirtying_per_click = value,
+            ConfigField::CleaningPerClick => self.cleaning_per_click = value,
+            ConfigField::CapacityGrowthPercent => self.capacity_growth_percent = value,
+            ConfigField::CapacityGrowthSqrt => self.capacity_growth_sqrt = value,
+        }
+        self.difficulty = Difficulty::Custom;
+    }
+
+    /// Doubles or halves a field. Doubling 0 gives 1.
+    pub fn step(&mut self, field: ConfigField, increase: bool) {
+        let value = self.get(field);
+        let value = if increase {
+            value.saturating_mul(2).max(1)
+        } else {
+            value / 2
+        };
+        self.set(field, value);
+    }
+
+    /// `key=value` lines, the same as in the save file.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("mode={}\n", self.mode.id());
+        text += &format!("time_limit_minutes={}\n", self.time_limit_minutes);
+        text += &format!("difficulty={}\n", self.difficulty.id());
+        for field in ConfigField::list() {
+            text += &format!("{}={}\n", field.id(), self.get(*field));
+        }
+        text
+    }
+
+    /// Starts from the preset of the difficulty, and only a custom game reads the values of the
+    /// economy, so that changing a preset also changes the saved games that use it.
+    pub fn from_text(text: &str) -> Self {
+        let values = text
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect::<Vec<_>>();
+        let difficulty = values
+            .iter()
+            .find(|(key, _)| *key == "difficulty")
+            .and_then(|(_, value)| Difficulty::from_id(value))
+            .unwrap_or(Difficulty::Normal);
+        let mut config = Self::preset(difficulty);
+        let value = |id: &str| values.iter().find(|(key, _)| *key == id).map(|(_, v)| *v);
+        if let Some(mode) = value("mode").and_then(GameMode::from_id) {
+            config.mode = mode;
+        }
+        if let Some(minutes) = value("time_limit_minutes").and_then(|v| v.parse().ok()) {
+            config.time_limit_minutes = i64::max(minutes, 1);
+        }
+        if difficulty == Difficulty::Custom {
+            for field in ConfigField::list() {
+                let value = values.iter().find(|(key, _)| *key == field.id());
+                if let Some(value) = value.and_then(|(_, value)| value.parse().ok()) {
+                    config.set(*field, value);
+                }
+            }
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_get_harder() {
+        let easy = WorldConfig::preset(Difficulty::Easy);
+        let normal = WorldConfig::preset(Difficulty::Normal);
+        let hard = WorldConfig::preset(Difficulty::Hard);
+        assert!(easy.max_dirtiness > normal.max_dirtiness);
+        assert!(normal.max_dirtiness > hard.max_dirtiness);
+        assert!(easy.target_savings < normal.target_savings);
+        assert!(normal.target_savings < hard.target_savings);
+    }
+
+    #[test]
+    fn test_custom_round_trip() {
+        let mut config = WorldConfig::preset(Difficulty::Hard);
+        config.step(ConfigField::TargetSavings, false);
+        config.step(ConfigField::DirtyingPerClick, true);
+        assert_eq!(config.difficulty, Difficulty::Custom);
+        assert_eq!(config.target_savings, 1_000_000);
+        assert_eq!(config.max_dirtiness, 50);
+        assert_eq!(WorldConfig::from_text(&config.to_text()), config);
+    }
+
+    #[test]
+    fn test_mode_is_kept_across_difficulties() {
+        let config = WorldConfig {
+            mode: GameMode::Timed,
+            time_limit_minutes: 3,
+            ..WorldConfig::default()
+        };
+        let hard = config.with_difficulty(Difficulty::Hard);
+        assert_eq!(hard.mode, GameMode::Timed);
+        assert_eq!(hard.time_limit(), Some(180.0));
+        assert_eq!(hard.max_dirtiness, 50);
+        let custom = hard.with_difficulty(Difficulty::Custom);
+        assert_eq!(custom.max_dirtiness, 50);
+        assert_eq!(WorldConfig::from_text(&custom.to_text()), custom);
+        assert_eq!(WorldConfig::default().time_limit(), None);
+    }
+
+    #[test]
+    fn test_presets_ignore_saved_values() {
+        let text = "difficulty=easy\nmax_dirtiness=1\n";
+        assert_eq!(
+            WorldConfig::from_text(text),
+            WorldConfig::preset(Difficulty::Easy)
+        );
+        assert_eq!(WorldConfig::from_text(""), WorldConfig::default());
+    }
+
+    #[test]
+    fn test_capacity_growth() {
+        let mut config = WorldConfig {
+            capacity_growth_percent: 0,
+            capacity_growth_sqrt: 0,
+            ..WorldConfig::default()
+        };
+        assert_eq!(config.max_dirtiness_at(to_cents(1_000_000)), 100);
+
+        config.capacity_growth_percent = 10;
+        assert_eq!(config.max_dirtiness_at(0), 100);
+        assert_eq!(config.max_dirtiness_at(to_cents(9)), 100);
+        assert_eq!(config.max_dirtiness_at(to_cents(10)), 101);
+        assert_eq!(config.max_dirtiness_at(to_cents(1_005)), 200);
+        assert_eq!(config.max_dirtiness_at(-5), 100);
+
+        config.capacity_growth_percent = 0;
+        config.capacity_growth_sqrt = 3;
+        assert_eq!(config.max_dirtiness_at(to_cents(1)), 103);
+        assert_eq!(config.max_dirtiness_at(to_cents(99)), 127);
+        assert_eq!(config.max_dirtiness_at(to_cents(100)), 130);
+    }
+
+    #[test]
+    fn test_capacity_saturates() {
+        let mut config = WorldConfig::default();
+        config.set(ConfigField::CapacityGrowthPercent, i64::MAX);
+        config.set(ConfigField::CapacityGrowthSqrt, i64::MAX);
+        assert_eq!(config.max_dirtiness_at(i64::MAX), i64::MAX);
+        assert_eq!(config.max_dirtiness_at(to_cents(50)), i64::MAX);
+        config.set(ConfigField::CapacityGrowthPercent, 1000);
+        config.set(ConfigField::CapacityGrowthSqrt, 0);
+        assert!(config.max_dirtiness_at(i64::MAX) > 0);
+    }
+
+    #[test]
+    fn test_minimums() {
+        let mut config = WorldConfig::default();
+        config.set(ConfigField::CleaningPerClick, 0);
+        assert_eq!(config.cleaning_per_click, 1);
+        for _ in 0..10 {
+            config.step(ConfigField::MaxDirtiness, false);
+        }
+        assert_eq!(config.max_dirtiness, 10);
+        config.set(ConfigField::CapacityGrowthSqrt, 0);
+        config.step(ConfigField::CapacityGrowthSqrt, true);
+        assert_eq!(config.capacity_growth_sqrt, 1);
+    }
+}
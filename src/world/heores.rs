@@ -1,7 +1,7 @@
 use crate::screen::textures::Texture;
-use crate::screen::translations::Translation;
+use crate::screen::translations::{Message, ProductionMessages, Translation};
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Hero {
     Hero1,
     Villain1,
@@ -34,7 +34,18 @@ impl Hero {
             Hero::Villain3 => 5,
         }
     }
-    pub fn texture_index(&self) -> Texture {
+    /// Stable name for files, like the suffix of the message ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Hero::Hero1 => "hero_1",
+            Hero::Villain1 => "villain_1",
+            Hero::Hero2 => "hero_2",
+            Hero::Villain2 => "villain_2",
+            Hero::Hero3 => "hero_3",
+            Hero::Villain3 => "villain_3",
+        }
+    }
+    pub fn texture(&self) -> Texture {
         match self {
             Hero::Hero1 => Texture::Hero1,
             Hero::Villain1 => Texture::Villain1,
@@ -44,34 +55,36 @@ impl Hero {
             Hero::Villain3 => Texture::Villain3,
         }
     }
-    pub fn name(&self, translation: &Translation) -> &'static str {
+    pub fn name<'a>(&self, translation: &'a Translation) -> &'a str {
         match self {
-            Hero::Hero1 => translation.name.hero_1,
-            Hero::Villain1 => translation.name.villain_1,
-            Hero::Hero2 => translation.name.hero_2,
-            Hero::Villain2 => translation.name.villain_2,
-            Hero::Hero3 => translation.name.hero_3,
-            Hero::Villain3 => translation.name.villain_3,
+            Hero::Hero1 => &translation.name.hero_1,
+            Hero::Villain1 => &translation.name.villain_1,
+            Hero::Hero2 => &translation.name.hero_2,
+            Hero::Villain2 => &translation.name.villain_2,
+            Hero::Hero3 => &translation.name.hero_3,
+            Hero::Villain3 => &translation.name.villain_3,
         }
     }
-    pub fn short_description(&self, translation: &Translation) -> &'static str {
-        match self {
-            Hero::Hero1 => translation.description.hero_1,
-            Hero::Villain1 => translation.description.villain_1,
-            Hero::Hero2 => translation.description.hero_2,
-            Hero::Villain2 => translation.description.villain_2,
-            Hero::Hero3 => translation.description.hero_3,
-            Hero::Villain3 => translation.description.villain_3,
+    pub fn short_description(&self, translation: &Translation) -> String {
+        self.production_message(&translation.description)
+            .format_count(self.production(), &[])
+    }
+    /// Chooses the cleaning message for heroes and the dirtying message for villains.
+    pub fn production_message<'a>(&self, messages: &'a ProductionMessages) -> &'a Message {
+        if self.is_hero() {
+            &messages.cleaning
+        } else {
+            &messages.dirtying
         }
     }
-    pub fn long_description(&self, translation: &Translation) -> &'static str {
+    pub fn long_description<'a>(&self, translation: &'a Translation) -> &'a str {
         match self {
-            Hero::Hero1 => translation.long_description.hero_1,
-            Hero::Villain1 => translation.long_description.villain_1,
-            Hero::Hero2 => translation.long_description.hero_2,
-            Hero::Villain2 => translation.long_description.villain_2,
-            Hero::Hero3 => translation.long_description.hero_3,
-            Hero::Villain3 => translation.long_description.villain_3,
+            Hero::Hero1 => &translation.long_description.hero_1,
+            Hero::Villain1 => &translation.long_description.villain_1,
+            Hero::Hero2 => &translation.long_description.hero_2,
+            Hero::Villain2 => &translation.long_description.villain_2,
+            Hero::Hero3 => &translation.long_description.hero_3,
+            Hero::Villain3 => &translation.long_description.villain_3,
         }
     }
     pub fn base_price(&self) -> i64 {
@@ -88,6 +101,18 @@ impl Hero {
             // Hero::Villain3 => 5,
         }
     }
+    /// Heroes clean and villains dirty.
+    pub fn is_hero(&self) -> bool {
+        matches!(self, Hero::Hero1 | Hero::Hero2 | Hero::Hero3)
+    }
+    /// Cleaning tasks per second for heroes, dirtying tasks per second for villains.
+    pub fn production(&self) -> i64 {
+        if self.is_hero() {
+            self.production_clean()
+        } else {
+            self.production_dirty()
+        }
+    }
     pub fn production_clean(&self) -> i64 {
         match self {
             Hero::Hero1 => 1,
@@ -0,0 +1,354 @@
+//! The best runs of each mode and difficulty, with the name of who played them. There is a record
+//! per category, and each mode has a main category, which is the score shown when a run ends.
+
+use std::collections::HashMap;
+
+use crate::external::backends::Seconds;
+use crate::world::config::{Difficulty, GameMode};
+use crate::world::Cents;
+
+/// Names longer than this are cut, so that they fit in the leaderboard panel.
+pub const MAX_NAME_LENGTH: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Score {
+    /// Time to win, or time survived in endless mode.
+    Time(Seconds),
+    /// Savings when the run ended.
+    Money(Cents),
+    /// Clicks on the clean and dirty buttons.
+    Clicks(i64),
+}
+
+impl Score {
+    /// The number alone, as saved in the `key=value` files.
+    pub fn value_text(&self) -> String {
+        match self {
+            Score::Time(time) => time.to_string(),
+            Score::Money(money) => money.to_string(),
+            Score::Clicks(clicks) => clicks.to_string(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    FastestWin,
+    LongestSurvival,
+    HighestSavings,
+    FewestClicks,
+}
+
+const CATEGORIES: [Category; 4] = [
+    Category::FastestWin,
+    Category::LongestSurvival,
+    Category::HighestSavings,
+    Category::FewestClicks,
+];
+
+impl Category {
+    pub fn list() -> &'static [Category] {
+        &CATEGORIES
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Category::FastestWin => "fastest_win",
+            Category::LongestSurvival => "longest_survival",
+            Category::HighestSavings => "highest_savings",
+            Category::FewestClicks => "fewest_clicks",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Category> {
+        CATEGORIES
+            .iter()
+            .find(|category| category.id() == id)
+            .copied()
+    }
+
+    /// Whether runs of `mode` have a record in this category.
+    pub fn applies_to(&self, mode: GameMode) -> bool {
+        match self {
+            Category::FastestWin | Category::FewestClicks => mode.can_win(),
+            Category::LongestSurvival => mode == GameMode::Endless,
+            Category::HighestSavings => true,
+        }
+    }
+
+    fn is_better(&self, new: Score, old: Score) -> bool {
+        match (self, new, old) {
+            (Category::FastestWin, Score::Time(new), Score::Time(old)) => new < old,
+            (Category::LongestSurvival, Score::Time(new), Score::Time(old)) => new > old,
+            (Category::HighestSavings, Score::Money(new), Score::Money(old)) => new > old,
+            (Category::FewestClicks, Score::Clicks(new), Score::Clicks(old)) => new < old,
+            _ => true,
+        }
+    }
+
+    fn parse_score(&self, value: &str) -> Option<Score> {
+        match self {
+            Category::FastestWin | Category::LongestSurvival => value.parse().ok().map(Score::Time),
+            Category::HighestSavings => value.parse().ok().map(Score::Money),
+            Category::FewestClicks => value.parse().ok().map(Score::Clicks),
+        }
+    }
+}
+
+impl GameMode {
+    /// Classic and zen reward winning fast, timed rewards saving more, and endless rewards lasting
+    /// longer.
+    pub fn main_category(&self) -> Category {
+        match self {
+            GameMode::Classic | GameMode::Zen => Category::FastestWin,
+            GameMode::Timed => Category::HighestSavings,
+            GameMode::Endless => Category::LongestSurvival,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub score: Score,
+    /// Empty if the player didn't choose a name.
+    pub name: String,
+}
+
+/// How a run ended, to be compared with the records of its mode and difficulty.
+pub struct RunResult {
+    pub won: bool,
+    /// Time played in the run.
+    pub time: Seconds,
+    pub savings: Cents,
+    pub clicks: i64,
+}
+
+impl RunResult {
+    /// The score of the run in each category of `mode` it competes in. Only won runs compete for
+    /// the fastest win and the fewest clicks.
+    pub fn scores(&self, mode: GameMode) -> Vec<(Category, Score)> {
+        Category::list()
+            .iter()
+            .filter(|category| category.applies_to(mode))
+            .filter_map(|category| {
+                let score = match category {
+                    Category::FastestWin => self.won.then_some(Score::Time(self.time)),
+                    Category::LongestSurvival => Some(Score::Time(self.time)),
+                    Category::HighestSavings => Some(Score::Money(self.savings)),
+                    Category::FewestClicks => self.won.then_some(Score::Clicks(self.clicks)),
+                };
+                score.map(|score| (*category, score))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    records: HashMap<(GameMode, Difficulty, Category), Record>,
+}
+
+impl HighScores {
+    pub fn record(
+        &self,
+        mode: GameMode,
+        difficulty: Difficulty,
+        category: Category,
+    ) -> Option<&Record> {
+        self.records.get(&(mode, difficulty, category))
+    }
+
+    /// Keeps the scores of the run that beat the records, and returns their categories.
+    pub fn submit(
+        &mut self,
+        mode: GameMode,
+        difficulty: Difficulty,
+        name: &str,
+        run: &RunResult,
+    ) -> Vec<Category> {
+        let mut beaten = Vec::new();
+        for (category, score) in run.scores(mode) {
+            let key = (mode, difficulty, category);
+            let is_record = match self.records.get(&key) {
+                Some(record) => category.is_better(score, record.score),
+                None => true,
+            };
+            if is_record {
+                let name = sanitize_name(name);
+                self.records.insert(key, Record { score, name });
+                beaten.push(category);
+            }
+        }
+        beaten
+    }
+
+    /// `key=value` lines like the stats: `<mode>.<difficulty>.<category>` has the score and
+    /// `<mode>.<difficulty>.<category>.name` the name.
+    pub fn from_text(text: &str) -> Self {
+        let mut scores = HashMap::new();
+        let mut names = HashMap::new();
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let (key, name_key) = match key.trim().strip_suffix(".name") {
+                Some(key) => (key, true),
+                None => (key.trim(), false),
+            };
+            let mut parts = key.split('.');
+            let mode = parts.next().and_then(GameMode::from_id);
+            let difficulty = parts.next().and_then(Difficulty::from_id);
+            let category = parts.next().and_then(Category::from_id);
+            if let (Some(mode), Some(difficulty), Some(category), None) =
+                (mode, difficulty, category, parts.next())
+            {
+                let key = (mode, difficulty, category);
+                if name_key {
+                    names.insert(key, sanitize_name(value));
+                } else if let Some(score) = category.parse_score(value.trim()) {
+                    scores.insert(key, score);
+                }
+            }
+        }
+        let records = scores
+            .into_iter()
+            .map(|(key, score)| {
+                let name = names.remove(&key).unwrap_or_default();
+                (key, Record { score, name })
+            })
+            .collect();
+        Self { records }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for mode in GameMode::list() {
+            for difficulty in Difficulty::list() {
+                for category in Category::list() {
+                    if let Some(record) = self.record(*mode, *difficulty, *category) {
+                        let key = format!("{}.{}.{}", mode.id(), difficulty.id(), category.id());
+                        text += &format!("{}={}\n", key, record.score.value_text());
+                        text += &format!("{}.name={}\n", key, record.name);
+                    }
+                }
+            }
+        }
+        text
+    }
+}
+
+/// One line without surrounding spaces, at most `MAX_NAME_LENGTH` characters.
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn won(time: Seconds, savings: Cents, clicks: i64) -> RunResult {
+        RunResult {
+            won: true,
+            time,
+            savings,
+            clicks,
+        }
+    }
+
+    fn lost(time: Seconds, savings: Cents) -> RunResult {
+        RunResult {
+            won: false,
+            time,
+            savings,
+            clicks: 1,
+        }
+    }
+
+    #[test]
+    fn test_records_are_kept_per_category() {
+        let mut high_scores = HighScores::default();
+        let (mode, difficulty) = (GameMode::Classic, Difficulty::Hard);
+        let beaten = high_scores.submit(mode, difficulty, "ana", &won(60.0, 500, 100));
+        assert_eq!(
+            beaten,
+            vec![
+                Category::FastestWin,
+                Category::HighestSavings,
+                Category::FewestClicks
+            ]
+        );
+
+        let beaten = high_scores.submit(mode, difficulty, "bea", &won(90.0, 700, 80));
+        assert_eq!(
+            beaten,
+            vec![Category::HighestSavings, Category::FewestClicks]
+        );
+        let beaten = high_scores.submit(mode, difficulty, "carla", &lost(10.0, 900));
+        assert_eq!(beaten, vec![Category::HighestSavings]);
+
+        let record = |category| high_scores.record(mode, difficulty, category).unwrap();
+        assert_eq!(record(Category::FastestWin).name, "ana");
+        assert_eq!(record(Category::FastestWin).score, Score::Time(60.0));
+        assert_eq!(record(Category::FewestClicks).name, "bea");
+        assert_eq!(record(Category::HighestSavings).name, "carla");
+        assert_eq!(
+            high_scores.record(mode, Difficulty::Easy, Category::FastestWin),
+            None
+        );
+        assert_eq!(
+            high_scores.record(GameMode::Zen, difficulty, Category::FastestWin),
+            None
+        );
+    }
+
+    #[test]
+    fn test_best_depends_on_mode() {
+        let mut high_scores = HighScores::default();
+        let difficulty = Difficulty::Normal;
+        let mut submit = |mode, run| high_scores.submit(mode, difficulty, "", &run);
+        assert_eq!(
+            submit(GameMode::Endless, lost(60.0, 0)),
+            [Category::LongestSurvival, Category::HighestSavings]
+        );
+        assert_eq!(
+            submit(GameMode::Endless, lost(90.0, 0)),
+            [Category::LongestSurvival]
+        );
+        assert_eq!(
+            submit(GameMode::Timed, won(300.0, 100, 5)),
+            [Category::HighestSavings]
+        );
+        assert_eq!(submit(GameMode::Timed, won(300.0, 100, 5)), []);
+
+        for mode in GameMode::list() {
+            assert!(mode.main_category().applies_to(*mode));
+        }
+        let best = |mode: GameMode| {
+            let record = high_scores.record(mode, difficulty, mode.main_category());
+            record.map(|record| record.score)
+        };
+        assert_eq!(best(GameMode::Endless), Some(Score::Time(90.0)));
+        assert_eq!(best(GameMode::Timed), Some(Score::Money(100)));
+        assert_eq!(best(GameMode::Zen), None);
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut high_scores = HighScores::default();
+        high_scores.submit(GameMode::Zen, Difficulty::Easy, "a=b", &won(12.5, 123, 4));
+        high_scores.submit(GameMode::Endless, Difficulty::Custom, "", &lost(1.5, 5));
+        assert_eq!(HighScores::from_text(&high_scores.to_text()), high_scores);
+        assert_eq!(
+            HighScores::from_text("classic.easy.fewest_clicks=abc\nclassic.nothing.fastest_win=1"),
+            HighScores::default()
+        );
+    }
+
+    #[test]
+    fn test_names_are_sanitized() {
+        assert_eq!(sanitize_name("  ana\n "), "ana");
+        assert_eq!(sanitize_name(&"x".repeat(40)).len(), MAX_NAME_LENGTH);
+    }
+}
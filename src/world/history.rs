@@ -0,0 +1,184 @@
+//! Samples of the economy over time, to show trends in a chart.
+
+use std::collections::VecDeque;
+
+use crate::external::backends::Seconds;
+use crate::world::Cents;
+
+const RECENT_SAMPLE_PERIOD: Seconds = 1.0;
+/// Enough samples to cover the longest window that is not the whole run.
+const RECENT_CAPACITY: usize = 10 * 60;
+const WHOLE_RUN_CAPACITY: usize = 600;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// Time played in this run.
+    pub time: Seconds,
+    pub money: Cents,
+    pub dirtiness: Cents,
+    pub cleaning_speed: Cents,
+    pub dirtying_speed: Cents,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum HistoryWindow {
+    LastMinute,
+    Last10Minutes,
+    WholeRun,
+}
+
+const HISTORY_WINDOWS: [HistoryWindow; 3] = [
+    HistoryWindow::LastMinute,
+    HistoryWindow::Last10Minutes,
+    HistoryWindow::WholeRun,
+];
+
+impl HistoryWindow {
+    pub fn list() -> &'static [HistoryWindow] {
+        &HISTORY_WINDOWS
+    }
+
+    /// None for the whole run.
+    pub fn duration(&self) -> Option<Seconds> {
+        match self {
+            HistoryWindow::LastMinute => Some(60.0),
+            HistoryWindow::Last10Minutes => Some(10.0 * 60.0),
+            HistoryWindow::WholeRun => None,
+        }
+    }
+}
+
+/// Keeps at most `capacity` samples taken every `period`. If `decimate` is set, when full it
+/// drops every other sample and doubles the period, so that it always spans since the first
+/// sample. Otherwise it drops the oldest sample, like a ring buffer.
+struct SampleBuffer {
+    samples: VecDeque<Sample>,
+    capacity: usize,
+    period: Seconds,
+    decimate: bool,
+}
+
+impl SampleBuffer {
+    fn new(capacity: usize, period: Seconds, decimate: bool) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            period,
+            decimate,
+        }
+    }
+
+    fn record(&mut self, sample: Sample) {
+        let due = match self.samples.back() {
+            Some(last) => sample.time >= last.time + self.period,
+            None => true,
+        };
+        if !due {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            if self.decimate {
+                let mut i = 0;
+                self.samples.retain(|_| {
+                    i += 1;
+                    i % 2 == 1
+                });
+                self.period *= 2.0;
+                if sample.time < self.samples.back().unwrap().time + self.period {
+                    return;
+                }
+            } else {
+                self.samples.pop_front();
+            }
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+pub struct History {
+    recent: SampleBuffer,
+    whole_run: SampleBuffer,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            recent: SampleBuffer::new(RECENT_CAPACITY, RECENT_SAMPLE_PERIOD, false),
+            whole_run: SampleBuffer::new(WHOLE_RUN_CAPACITY, RECENT_SAMPLE_PERIOD, true),
+        }
+    }
+}
+
+impl History {
+    /// Can be called every frame; only keeps the samples that are due.
+    pub fn record(&mut self, sample: Sample) {
+        self.recent.record(sample);
+        self.whole_run.record(sample);
+    }
+
+    /// Samples in chronological order, ending at the latest one.
+    pub fn samples(&self, window: HistoryWindow) -> Vec<Sample> {
+        match window.duration() {
+            None => self.whole_run.samples.iter().copied().collect(),
+            Some(duration) => {
+                let latest = self.recent.samples.back().map(|s| s.time).unwrap_or(0.0);
+                self.recent
+                    .samples
+                    .iter()
+                    .filter(|sample| sample.time >= latest - duration)
+                    .copied()
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: Seconds) -> Sample {
+        Sample {
+            time,
+            money: time as Cents,
+            dirtiness: 0,
+            cleaning_speed: 0,
+            dirtying_speed: 0,
+        }
+    }
+
+    fn record_every_frame(history: &mut History, until: Seconds) {
+        let frame = 1.0 / 60.0;
+        for i in 0..(until / frame) as i64 {
+            history.record(sample(i as Seconds * frame));
+        }
+    }
+
+    #[test]
+    fn test_samples_once_per_period() {
+        let mut history = History::default();
+        record_every_frame(&mut history, 30.0);
+        let samples = history.samples(HistoryWindow::LastMinute);
+        assert_eq!(samples.len(), 30);
+        assert_eq!(samples[0].time, 0.0);
+    }
+
+    #[test]
+    fn test_windows_are_bounded() {
+        let mut history = History::default();
+        record_every_frame(&mut history, 3600.0);
+
+        let last_minute = history.samples(HistoryWindow::LastMinute);
+        assert!((60..=61).contains(&last_minute.len()));
+        let last_10_minutes = history.samples(HistoryWindow::Last10Minutes);
+        assert_eq!(last_10_minutes.len(), RECENT_CAPACITY);
+        assert!(last_10_minutes[0].time > 3600.0 - 601.0);
+
+        let whole_run = history.samples(HistoryWindow::WholeRun);
+        assert!(whole_run.len() <= WHOLE_RUN_CAPACITY);
+        assert!(whole_run.len() >= WHOLE_RUN_CAPACITY / 2);
+        assert_eq!(whole_run[0].time, 0.0);
+        assert!(whole_run.last().unwrap().time > 3500.0);
+        assert!(whole_run.windows(2).all(|pair| pair[0].time < pair[1].time));
+    }
+}
@@ -0,0 +1,122 @@
+//! Saving a run to continue it later, as `key=value` lines like the settings.
+//!
+//! Only what can't be recomputed is saved. Alerts, the history and the timeline start empty
+//! again when loading.
+
+use crate::external::backends::now;
+use crate::world::acts::Act;
+use crate::world::config::WorldConfig;
+use crate::world::heores::Hero;
+use crate::world::stats::Stats;
+use crate::world::{to_cents, World};
+
+const SNAPSHOT_VERSION: i64 = 1;
+
+impl World {
+    pub fn to_snapshot(&self) -> String {
+        let mut text = format!("version={}\n", SNAPSHOT_VERSION);
+        for line in self.config.to_text().lines() {
+            text += &format!("config.{}\n", line);
+        }
+        text += &format!("player_name={}\n", self.player_name);
+        text += &format!("act={}\n", self.act.id());
+        text += &format!("money={}\n", self.money);
+        text += &format!("total_money={}\n", self.total_money);
+        text += &format!("dirtiness={}\n", self.dirtiness);
+        for hero in Hero::list() {
+            text += &format!("count.{}={}\n", hero.id(), self.heroes_count[hero]);
+        }
+        for line in self.stats.to_text().lines() {
+            text += &format!("stats.{}\n", line);
+        }
+        text
+    }
+
+    /// Fails if the snapshot is from a newer version of the game, or lacks the act, so that a
+    /// broken file doesn't silently become a new run. Other missing values are 0.
+    pub fn from_snapshot(text: &str) -> Result<World, String> {
+        let mut config_text = String::new();
+        let mut stats_text = String::new();
+        let mut act = None;
+        let mut world = World::new(WorldConfig::default());
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let (key, value) = (key.trim(), value.trim());
+            let number = || value.parse::<i64>().unwrap_or_default().max(0);
+            if let Some(key) = key.strip_prefix("config.") {
+                config_text += &format!("{}={}\n", key, value);
+            } else if let Some(key) = key.strip_prefix("stats.") {
+                stats_text += &format!("{}={}\n", key, value);
+            } else if let Some(id) = key.strip_prefix("count.") {
+                if let Some(hero) = Hero::list().iter().find(|hero| hero.id() == id) {
+                    world.heroes_count.insert(*hero, number());
+                }
+            } else {
+                match key {
+                    "version" if number() > SNAPSHOT_VERSION => {
+                        return Err(format!("unsupported save version {}", value));
+                    }
+                    "player_name" => world.set_player_name(value),
+                    "act" => act = Act::from_id(value),
+                    "money" => world.money = number(),
+                    "total_money" => world.total_money = number(),
+                    "dirtiness" => world.dirtiness = number(),
+                    _ => {}
+                }
+            }
+        }
+        let config = WorldConfig::from_text(&config_text);
+        world.max_dirtiness = config.max_dirtiness_at(world.total_money);
+        world.target_savings = config.target_savings;
+        world.dirtiness = world.dirtiness.min(to_cents(world.max_dirtiness));
+        world.config = config;
+        world.act = act.ok_or_else(|| "the save has no valid act".to_string())?;
+        world.stats = Stats::from_text(&stats_text);
+        world.previous_frame_timestamp = now();
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen::GuiActions;
+    use crate::world::config::{ConfigField, Difficulty};
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut config = WorldConfig::preset(Difficulty::Easy);
+        config.step(ConfigField::CleaningPerClick, true);
+        let mut world = World::new(config);
+        for _ in 0..20 {
+            world.update(GuiActions {
+                dirty_pressed: true,
+                clean_pressed: true,
+                ..GuiActions::default()
+            });
+        }
+        world.heroes_count.insert(Hero::Villain2, 3);
+        world.act = Act::Act2;
+        world.set_player_name("ana");
+
+        let loaded = World::from_snapshot(&world.to_snapshot()).unwrap();
+        assert_eq!(loaded.config(), world.config());
+        assert_eq!(loaded.stage(), Act::Act2);
+        assert_eq!(loaded.player_name(), "ana");
+        assert_eq!(loaded.money, world.money);
+        assert_eq!(loaded.total_money, world.total_money);
+        assert_eq!(loaded.dirtiness, world.dirtiness);
+        assert_eq!(loaded.heroes_count, world.heroes_count);
+        assert_eq!(loaded.stats(), world.stats());
+        assert_eq!(loaded.max_dirtiness_units(), world.max_dirtiness_units());
+    }
+
+    #[test]
+    fn test_broken_snapshots() {
+        assert!(World::from_snapshot("").is_err());
+        assert!(World::from_snapshot("version=2\nact=act_1").is_err());
+        let world = World::from_snapshot("act=act_3\nmoney=-5\ndirtiness=99999999").unwrap();
+        assert_eq!(world.money(), 0);
+        assert_eq!(world.dirtiness_units(), world.max_dirtiness_units());
+        assert_eq!(world.config(), &WorldConfig::default());
+    }
+}
@@ -0,0 +1,218 @@
+//! Counters about how the game was played, for one run and for every run together.
+
+use std::collections::HashMap;
+
+use crate::external::backends::Seconds;
+use crate::world::acts::Act;
+use crate::world::config::Difficulty;
+use crate::world::heores::Hero;
+use crate::world::Cents;
+
+/// The acts where time passes. The game doesn't advance while showing the game-over or
+/// game-won screens.
+const PLAYED_ACTS: [Act; 4] = [
+    Act::Act1,
+    Act::Act2,
+    Act::Act3,
+    Act::ContinuePlayingAfterWinning,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub clean_clicks: i64,
+    pub dirty_clicks: i64,
+    pub units_bought: HashMap<Hero, i64>,
+    pub units_sold: HashMap<Hero, i64>,
+    pub money_spent: Cents,
+    pub peak_dirtiness: Cents,
+    pub time_in_act: HashMap<Act, Seconds>,
+    pub game_overs: i64,
+    /// Runs started on each difficulty. A single run has 1 in its own difficulty.
+    pub games: HashMap<Difficulty, i64>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        let per_hero = || HashMap::from_iter(Hero::list().iter().map(|hero| (*hero, 0)));
+        Self {
+            clean_clicks: 0,
+            dirty_clicks: 0,
+            units_bought: per_hero(),
+            units_sold: per_hero(),
+            money_spent: 0,
+            peak_dirtiness: 0,
+            time_in_act: HashMap::from_iter(PLAYED_ACTS.iter().map(|act| (*act, 0.0))),
+            game_overs: 0,
+            games: HashMap::from_iter(Difficulty::list().iter().map(|d| (*d, 0))),
+        }
+    }
+}
+
+impl Stats {
+    pub fn played_acts() -> &'static [Act] {
+        &PLAYED_ACTS
+    }
+
+    /// Adds the counters of another run. The peak dirtiness is the highest of both.
+    pub fn merge(&mut self, other: &Stats) {
+        self.clean_clicks = self.clean_clicks.saturating_add(other.clean_clicks);
+        self.dirty_clicks = self.dirty_clicks.saturating_add(other.dirty_clicks);
+        for hero in Hero::list() {
+            add(&mut self.units_bought, hero, other.units_bought[hero]);
+            add(&mut self.units_sold, hero, other.units_sold[hero]);
+        }
+        self.money_spent = self.money_spent.saturating_add(other.money_spent);
+        self.peak_dirtiness = self.peak_dirtiness.max(other.peak_dirtiness);
+        for act in PLAYED_ACTS {
+            *self.time_in_act.get_mut(&act).unwrap() += other.time_in_act[&act];
+        }
+        self.game_overs = self.game_overs.saturating_add(other.game_overs);
+        for difficulty in Difficulty::list() {
+            let games = self.games.get_mut(difficulty).unwrap();
+            *games = games.saturating_add(other.games[difficulty]);
+        }
+    }
+
+    pub fn record_game(&mut self, difficulty: Difficulty) {
+        let games = self.games.get_mut(&difficulty).unwrap();
+        *games = games.saturating_add(1);
+    }
+
+    pub fn record_purchase(&mut self, hero: &Hero, price: Cents) {
+        add(&mut self.units_bought, hero, 1);
+        self.money_spent = self.money_spent.saturating_add(price);
+    }
+
+    pub fn record_sale(&mut self, hero: &Hero) {
+        add(&mut self.units_sold, hero, 1);
+    }
+
+    pub fn record_time(&mut self, act: Act, time: Seconds) {
+        if let Some(time_in_act) = self.time_in_act.get_mut(&act) {
+            *time_in_act += time;
+        }
+    }
+
+    pub fn total_time(&self) -> Seconds {
+        self.time_in_act.values().sum()
+    }
+
+    /// Stored as `key=value` lines, like the settings. Unknown or malformed lines are ignored, so
+    /// that old files keep loading after adding new counters.
+    pub fn from_text(text: &str) -> Self {
+        let mut stats = Self::default();
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let (key, value) = (key.trim(), value.trim());
+            let count = || value.parse::<i64>().ok();
+            match key.split_once('.') {
+                None => match key {
+                    "clean_clicks" => stats.clean_clicks = count().unwrap_or_default(),
+                    "dirty_clicks" => stats.dirty_clicks = count().unwrap_or_default(),
+                    "money_spent" => stats.money_spent = count().unwrap_or_default(),
+                    "peak_dirtiness" => stats.peak_dirtiness = count().unwrap_or_default(),
+                    "game_overs" => stats.game_overs = count().unwrap_or_default(),
+                    _ => {}
+                },
+                Some((kind, id)) => {
+                    let hero = Hero::list().iter().find(|hero| hero.id() == id);
+                    match (kind, hero) {
+                        ("bought", Some(hero)) => {
+                            stats
+                                .units_bought
+                                .insert(*hero, count().unwrap_or_default());
+                        }
+                        ("sold", Some(hero)) => {
+                            stats.units_sold.insert(*hero, count().unwrap_or_default());
+                        }
+                        ("games", _) => {
+                            if let Some(difficulty) = Difficulty::from_id(id) {
+                                stats.games.insert(difficulty, count().unwrap_or_default());
+                            }
+                        }
+                        ("time", _) => {
+                            if let Some(act) = PLAYED_ACTS.iter().find(|act| act.id() == id) {
+                                let time = value.parse::<Seconds>().unwrap_or_default();
+                                stats.time_in_act.insert(*act, time);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text += &format!("clean_clicks={}\n", self.clean_clicks);
+        text += &format!("dirty_clicks={}\n", self.dirty_clicks);
+        for hero in Hero::list() {
+            text += &format!("bought.{}={}\n", hero.id(), self.units_bought[hero]);
+            text += &format!("sold.{}={}\n", hero.id(), self.units_sold[hero]);
+        }
+        text += &format!("money_spent={}\n", self.money_spent);
+        text += &format!("peak_dirtiness={}\n", self.peak_dirtiness);
+        for act in PLAYED_ACTS {
+            text += &format!("time.{}={}\n", act.id(), self.time_in_act[&act]);
+        }
+        text += &format!("game_overs={}\n", self.game_overs);
+        for difficulty in Difficulty::list() {
+            text += &format!("games.{}={}\n", difficulty.id(), self.games[difficulty]);
+        }
+        text
+    }
+}
+
+fn add(counts: &mut HashMap<Hero, i64>, hero: &Hero, amount: i64) {
+    let count = counts.entry(*hero).or_default();
+    *count = count.saturating_add(amount);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn some_stats() -> Stats {
+        let mut stats = Stats {
+            clean_clicks: 7,
+            dirty_clicks: 3,
+            peak_dirtiness: 4_000,
+            game_overs: 1,
+            ..Stats::default()
+        };
+        stats.record_purchase(&Hero::Villain2, 100_000);
+        stats.record_sale(&Hero::Villain2);
+        stats.record_time(Act::Act2, 1.5);
+        stats.record_game(Difficulty::Hard);
+        stats
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let stats = some_stats();
+        assert_eq!(Stats::from_text(&stats.to_text()), stats);
+        assert_eq!(
+            Stats::from_text("garbage\nclean_clicks=x\nsold.nobody=1"),
+            Stats::default()
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut lifetime = some_stats();
+        let mut run = some_stats();
+        run.peak_dirtiness = 10;
+        lifetime.merge(&run);
+        assert_eq!(lifetime.clean_clicks, 14);
+        assert_eq!(lifetime.units_bought[&Hero::Villain2], 2);
+        assert_eq!(lifetime.units_sold[&Hero::Hero1], 0);
+        assert_eq!(lifetime.money_spent, 200_000);
+        assert_eq!(lifetime.peak_dirtiness, 4_000);
+        assert_eq!(lifetime.time_in_act[&Act::Act2], 3.0);
+        assert_eq!(lifetime.total_time(), 3.0);
+        assert_eq!(lifetime.game_overs, 2);
+        assert_eq!(lifetime.games[&Difficulty::Hard], 2);
+        assert_eq!(lifetime.games[&Difficulty::Easy], 0);
+    }
+}
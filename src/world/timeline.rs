@@ -0,0 +1,258 @@
+//! Per-second record of a whole run, and the events that happened in it, for exporting.
+//!
+//! The exported layout is documented in `docs/export.md`. Columns are only ever added at the end,
+//! and `FORMAT_VERSION` changes if anything else changes.
+
+use std::collections::HashMap;
+
+use crate::external::backends::Seconds;
+use crate::world::acts::Act;
+use crate::world::heores::Hero;
+use crate::world::stats::Stats;
+use crate::world::{Cents, Units};
+
+pub const FORMAT_VERSION: i64 = 1;
+
+const ROW_PERIOD: Seconds = 1.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineRow {
+    /// Time played in this run.
+    pub time: Seconds,
+    pub act: Act,
+    pub money: Cents,
+    pub dirtiness: Cents,
+    pub max_dirtiness: Units,
+    pub heroes_count: HashMap<Hero, i64>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EventKind {
+    Bought(Hero),
+    Sold(Hero),
+    ActChanged,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimelineEvent {
+    pub time: Seconds,
+    pub kind: EventKind,
+    /// The act when the event happened. For act changes, the new act.
+    pub act: Act,
+    /// Paid when buying, or received when selling. 0 for act changes.
+    pub price: Cents,
+}
+
+#[derive(Default)]
+pub struct Timeline {
+    rows: Vec<TimelineRow>,
+    events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    /// Whether a row at this time would be kept, as there is one row per second. Rows are the
+    /// first frame of each second, so that they don't drift apart.
+    pub fn is_due(&self, time: Seconds) -> bool {
+        match self.rows.last() {
+            Some(last) => time >= ((last.time / ROW_PERIOD).floor() + 1.0) * ROW_PERIOD,
+            None => true,
+        }
+    }
+
+    /// Can be called every frame; only keeps the rows that are due.
+    pub fn record(&mut self, row: TimelineRow) {
+        if self.is_due(row.time) {
+            self.rows.push(row);
+        }
+    }
+
+    pub fn record_event(&mut self, event: TimelineEvent) {
+        self.events.push(event);
+    }
+
+    pub fn rows(&self) -> &[TimelineRow] {
+        &self.rows
+    }
+
+    pub fn events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    pub fn timeline_csv(&self) -> String {
+        let mut csv = String::from("time_seconds,act,money_cents,dirtiness_cents,max_dirtiness");
+        for hero in Hero::list() {
+            csv += &format!(",{}", hero.id());
+        }
+        csv.push('\n');
+        for row in &self.rows {
+            csv += &format!(
+                "{:.3},{},{},{},{}",
+                row.time,
+                row.act.id(),
+                row.money,
+                row.dirtiness,
+                row.max_dirtiness
+            );
+            for hero in Hero::list() {
+                csv += &format!(",{}", row.heroes_count[hero]);
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    pub fn events_csv(&self) -> String {
+        let mut csv = String::from("time_seconds,event,hero,act,price_cents\n");
+        for event in &self.events {
+            let (name, hero) = event_name_and_hero(&event.kind);
+            csv += &format!(
+                "{:.3},{},{},{},{}\n",
+                event.time,
+                name,
+                hero,
+                event.act.id(),
+                event.price
+            );
+        }
+        csv
+    }
+
+    /// Everything in one JSON object, including the stats of the run.
+    pub fn to_json(&self, stats: &Stats) -> String {
+        let mut json = format!("{{\n  \"format_version\": {},\n", FORMAT_VERSION);
+        json += "  \"timeline\": [";
+        for (i, row) in self.rows.iter().enumerate() {
+            json += if i == 0 { "\n" } else { ",\n" };
+            let heroes = Hero::list()
+                .iter()
+                .map(|hero| format!("\"{}\": {}", hero.id(), row.heroes_count[hero]))
+                .collect::<Vec<_>>()
+                .join(", ");
+            json += &format!(
+                "    {{\"time_seconds\": {:.3}, \"act\": \"{}\", \"money_cents\": {}, \
+                \"dirtiness_cents\": {}, \"max_dirtiness\": {}, \"heroes_count\": {{{}}}}}",
+                row.time,
+                row.act.id(),
+                row.money,
+                row.dirtiness,
+                row.max_dirtiness,
+                heroes
+            );
+        }
+        json += "\n  ],\n  \"events\": [";
+        for (i, event) in self.events.iter().enumerate() {
+            json += if i == 0 { "\n" } else { ",\n" };
+            let (name, hero) = event_name_and_hero(&event.kind);
+            let hero = if hero.is_empty() {
+                "null".to_string()
+            } else {
+                format!("\"{}\"", hero)
+            };
+            json += &format!(
+                "    {{\"time_seconds\": {:.3}, \"event\": \"{}\", \"hero\": {}, \
+                \"act\": \"{}\", \"price_cents\": {}}}",
+                event.time,
+                name,
+                hero,
+                event.act.id(),
+                event.price
+            );
+        }
+        json += "\n  ],\n  \"stats\": {";
+        let stats_text = stats.to_text();
+        for (i, (key, value)) in stats_text
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .enumerate()
+        {
+            json += if i == 0 { "\n" } else { ",\n" };
+            json += &format!("    \"{}\": {}", key, value);
+        }
+        json += "\n  }\n}\n";
+        json
+    }
+}
+
+fn event_name_and_hero(kind: &EventKind) -> (&'static str, &'static str) {
+    match kind {
+        EventKind::Bought(hero) => ("bought", hero.id()),
+        EventKind::Sold(hero) => ("sold", hero.id()),
+        EventKind::ActChanged => ("act_changed", ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(time: Seconds) -> TimelineRow {
+        TimelineRow {
+            time,
+            act: Act::Act1,
+            money: 150,
+            dirtiness: 500,
+            max_dirtiness: 100,
+            heroes_count: HashMap::from_iter(Hero::list().iter().map(|h| (*h, 0))),
+        }
+    }
+
+    fn timeline() -> Timeline {
+        let mut timeline = Timeline::default();
+        for frame in 0..150 {
+            timeline.record(row(frame as Seconds / 60.0));
+        }
+        timeline.record_event(TimelineEvent {
+            time: 1.5,
+            kind: EventKind::Bought(Hero::Villain1),
+            act: Act::Act1,
+            price: 1200,
+        });
+        timeline.record_event(TimelineEvent {
+            time: 2.0,
+            kind: EventKind::ActChanged,
+            act: Act::Act2,
+            price: 0,
+        });
+        timeline
+    }
+
+    #[test]
+    fn test_one_row_per_second() {
+        assert_eq!(timeline().rows().len(), 3);
+    }
+
+    #[test]
+    fn test_csv_layout() {
+        let timeline = timeline();
+        let csv = timeline.timeline_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "time_seconds,act,money_cents,dirtiness_cents,max_dirtiness,\
+            hero_1,villain_1,hero_2,villain_2,hero_3,villain_3"
+        );
+        assert_eq!(lines[1], "0.000,act_1,150,500,100,0,0,0,0,0,0");
+        assert_eq!(lines.len(), 4);
+
+        let events = timeline.events_csv();
+        let lines = events.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "time_seconds,event,hero,act,price_cents");
+        assert_eq!(lines[1], "1.500,bought,villain_1,act_1,1200");
+        assert_eq!(lines[2], "2.000,act_changed,,act_2,0");
+    }
+
+    #[test]
+    fn test_json_layout() {
+        let json = timeline().to_json(&Stats::default());
+        assert!(json.starts_with("{\n  \"format_version\": 1,\n  \"timeline\": [\n"));
+        assert!(json.contains(
+            "{\"time_seconds\": 1.500, \"event\": \"bought\", \"hero\": \"villain_1\", \
+            \"act\": \"act_1\", \"price_cents\": 1200}"
+        ));
+        assert!(json.contains("\"hero\": null"));
+        assert!(json.contains("\"heroes_count\": {\"hero_1\": 0, \"villain_1\": 0,"));
+        assert!(json.contains("\"game_overs\": 0,\n"));
+        assert!(json.contains("\"games.custom\": 0\n  }\n}"));
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+    }
+}